@@ -0,0 +1,157 @@
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::auth::{has_chatgpt_tokens, read_auth_value, write_auth_value};
+use crate::config::{read_config_doc, set_value_preserving_decor, write_config_doc};
+use crate::error::{AppError, AppResult};
+use crate::nodes::CHATGPT_NODE;
+use crate::storage::{atomic_write, codex_dir};
+
+/// ChatGPT 账号快照目录: ~/.codex/codex-mate/accounts/<name>.json
+/// 快照内容即 auth.json 的完整副本
+pub fn accounts_dir() -> AppResult<PathBuf> {
+  Ok(codex_dir()?.join("codex-mate").join("accounts"))
+}
+
+fn account_snapshot_path(name: &str) -> AppResult<PathBuf> {
+  let trimmed = name.trim();
+  if trimmed.is_empty() || trimmed.starts_with('.') || trimmed.contains(['/', '\\']) {
+    return Err(AppError::invalid(format!("invalid snapshot name '{}'", name)));
+  }
+  Ok(accounts_dir()?.join(format!("{}.json", trimmed)))
+}
+
+/// 从 auth.json 的 id_token (JWT) 中解析出的账号信息
+#[derive(Serialize, Default)]
+pub struct AccountInfo {
+  pub email: Option<String>,
+  pub plan: Option<String>,
+  pub account_id: Option<String>,
+}
+
+/// 解码 tokens.id_token 的 payload（不校验签名，仅用于展示）
+pub fn decode_account_info(auth: &serde_json::Map<String, serde_json::Value>) -> AccountInfo {
+  use base64::Engine;
+  let tokens = auth.get("tokens");
+  let claims = tokens
+    .and_then(|t| t.get("id_token"))
+    .and_then(|v| v.as_str())
+    .and_then(|jwt| jwt.split('.').nth(1))
+    .and_then(|payload| base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok())
+    .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+    .unwrap_or(serde_json::Value::Null);
+  let openai_auth = claims.get("https://api.openai.com/auth");
+  let str_of = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_str()).map(|s| s.to_string());
+  AccountInfo {
+    email: str_of(claims.get("email")),
+    plan: str_of(openai_auth.and_then(|a| a.get("chatgpt_plan_type"))),
+    account_id: str_of(tokens.and_then(|t| t.get("account_id")))
+      .or_else(|| str_of(openai_auth.and_then(|a| a.get("chatgpt_account_id")))),
+  }
+}
+
+fn read_account_snapshot(path: &std::path::Path) -> AppResult<serde_json::Map<String, serde_json::Value>> {
+  let content = fs::read_to_string(path).map_err(|e| match e.kind() {
+    std::io::ErrorKind::NotFound => {
+      AppError::not_found("snapshot", path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default())
+    }
+    _ => AppError::io("read", path, e),
+  })?;
+  let v: serde_json::Value = serde_json::from_str(&content).map_err(|e| AppError::json(path, e))?;
+  v.as_object().cloned().ok_or_else(|| AppError::json(path, "not a JSON object"))
+}
+
+#[derive(Serialize)]
+pub struct AccountSnapshot {
+  pub name: String,
+  #[serde(flatten)]
+  pub info: AccountInfo,
+  /// 快照保存时间（Unix 秒）
+  pub saved_at: Option<u64>,
+  /// 是否为 auth.json 当前登录的账号
+  pub is_current: bool,
+}
+
+/// 列出所有 ChatGPT 账号快照
+pub fn list_account_snapshots() -> AppResult<Vec<AccountSnapshot>> {
+  let dir = accounts_dir()?;
+  if !dir.exists() {
+    return Ok(vec![]);
+  }
+  let current_id = decode_account_info(&read_auth_value()?).account_id;
+  let entries = fs::read_dir(&dir).map_err(|e| AppError::io("read", &dir, e))?;
+  let mut res = vec![];
+  for entry in entries.flatten() {
+    let path = entry.path();
+    if path.extension().and_then(|e| e.to_str()) != Some("json") {
+      continue;
+    }
+    let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+    let info = match read_account_snapshot(&path) {
+      Ok(auth) => decode_account_info(&auth),
+      Err(e) => {
+        log::warn!("skip unreadable snapshot: {}", e);
+        AccountInfo::default()
+      }
+    };
+    let saved_at = entry
+      .metadata()
+      .and_then(|m| m.modified())
+      .ok()
+      .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+      .map(|d| d.as_secs());
+    let is_current = info.account_id.is_some() && info.account_id == current_id;
+    res.push(AccountSnapshot { name, info, saved_at, is_current });
+  }
+  res.sort_by(|a, b| a.name.cmp(&b.name));
+  Ok(res)
+}
+
+/// 将当前 auth.json 保存为指定名称的账号快照（同名覆盖）
+pub fn save_account_snapshot(name: String) -> AppResult<()> {
+  let path = account_snapshot_path(&name)?;
+  let auth = read_auth_value()?;
+  if !has_chatgpt_tokens(&auth) {
+    return Err(AppError::ChatgptLoginMissing);
+  }
+  let s = serde_json::to_string_pretty(&serde_json::Value::Object(auth))?;
+  atomic_write(&path, &s)
+}
+
+/// 恢复账号快照到 auth.json，并切换到 ChatGPT 账号节点
+/// 恢复前会把当前登录态写回其对应的快照，避免刷新后的 tokens 丢失
+pub fn restore_account_snapshot(name: String) -> AppResult<()> {
+  let path = account_snapshot_path(&name)?;
+  let mut snapshot = read_account_snapshot(&path)?;
+  if !has_chatgpt_tokens(&snapshot) {
+    return Err(AppError::invalid(format!("snapshot '{}' has no ChatGPT tokens", name)));
+  }
+
+  let current = read_auth_value()?;
+  if let Some(current_id) = decode_account_info(&current).account_id {
+    for snap in list_account_snapshots()? {
+      if snap.info.account_id.as_deref() == Some(current_id.as_str()) && snap.name != name.trim() {
+        save_account_snapshot(snap.name)?;
+      }
+    }
+  }
+
+  // 快照只用于 ChatGPT 登录态，确保不会带入 API Key
+  snapshot.insert("OPENAI_API_KEY".into(), serde_json::Value::Null);
+  write_auth_value(&snapshot)?;
+
+  let mut doc = read_config_doc()?;
+  set_value_preserving_decor(doc.as_table_mut(), "model_provider", CHATGPT_NODE.into());
+  write_config_doc(&doc)?;
+  log::info!("restored account snapshot '{}'", name);
+  Ok(())
+}
+
+pub fn delete_account_snapshot(name: String) -> AppResult<()> {
+  let path = account_snapshot_path(&name)?;
+  fs::remove_file(&path).map_err(|e| match e.kind() {
+    std::io::ErrorKind::NotFound => AppError::not_found("snapshot", name.trim()),
+    _ => AppError::io("remove", &path, e),
+  })
+}
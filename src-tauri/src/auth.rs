@@ -0,0 +1,30 @@
+use std::fs;
+
+use crate::error::{AppError, AppResult};
+use crate::storage::{atomic_write, codex_dir};
+
+/// 读取 ~/.codex/auth.json，文件不存在或为空时返回空 Map
+/// 解析失败直接报错，避免后续写回时覆盖掉登录信息
+pub fn read_auth_value() -> AppResult<serde_json::Map<String, serde_json::Value>> {
+  let path = codex_dir()?.join("auth.json");
+  if !path.exists() {
+    return Ok(serde_json::Map::new());
+  }
+  let content = fs::read_to_string(&path).map_err(|e| AppError::io("read", &path, e))?;
+  if content.trim().is_empty() {
+    return Ok(serde_json::Map::new());
+  }
+  let v: serde_json::Value = serde_json::from_str(&content).map_err(|e| AppError::json(&path, e))?;
+  v.as_object().cloned().ok_or_else(|| AppError::json(&path, "not a JSON object"))
+}
+
+pub fn write_auth_value(map: &serde_json::Map<String, serde_json::Value>) -> AppResult<()> {
+  let path = codex_dir()?.join("auth.json");
+  let s = serde_json::to_string_pretty(&serde_json::Value::Object(map.clone()))?;
+  atomic_write(&path, &s)
+}
+
+/// auth.json 中是否存在 ChatGPT 登录的 tokens
+pub fn has_chatgpt_tokens(auth: &serde_json::Map<String, serde_json::Value>) -> bool {
+  auth.get("tokens").map(|v| v.is_object()).unwrap_or(false)
+}
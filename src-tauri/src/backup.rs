@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::error::{AppError, AppResult};
+use crate::permissions;
+use crate::settings::{read_settings, write_settings};
+use crate::storage::{atomic_write, codex_dir};
+
+/// 备份保留策略：每个文件最多保留 max_count 份、最长 max_age_days 天，0 表示不限制
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct BackupSettings {
+  pub max_count: usize,
+  pub max_age_days: u64,
+}
+
+impl Default for BackupSettings {
+  fn default() -> Self {
+    BackupSettings { max_count: 20, max_age_days: 30 }
+  }
+}
+
+/// 备份目录: ~/.codex/codex-mate/backups/<相对路径>/<毫秒时间戳>.bak
+/// 例如 backups/config.toml/1760000000000.bak、backups/codex-mate/credentials.json/1760000000000.bak
+pub fn backups_dir() -> AppResult<PathBuf> {
+  Ok(codex_dir()?.join("codex-mate").join("backups"))
+}
+
+pub fn now_millis() -> u64 {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+/// 写入前备份旧版本并返回备份 ID：文件不存在、位于备份目录内或内容未变化时跳过，返回 None
+pub fn backup_before_write(path: &std::path::Path, new_content: &str) -> AppResult<Option<String>> {
+  let root = codex_dir()?;
+  let backups = backups_dir()?;
+  // 备份目录与可重新生成的缓存（codex-mate/cache）不做备份
+  let cache = root.join("codex-mate").join("cache");
+  let rel = match path.strip_prefix(&root) {
+    Ok(rel) if !path.starts_with(&backups) && !path.starts_with(&cache) => rel.to_path_buf(),
+    _ => return Ok(None),
+  };
+  let old = match fs::read(path) {
+    Ok(old) => old,
+    Err(_) => return Ok(None),
+  };
+  if old == new_content.as_bytes() {
+    return Ok(None);
+  }
+
+  let dir = backups.join(&rel);
+  permissions::create_dir_all(&dir).map_err(|e| AppError::io("create dir", &dir, e))?;
+  let mut ts = now_millis();
+  while dir.join(format!("{}.bak", ts)).exists() {
+    ts += 1;
+  }
+  let backup_path = dir.join(format!("{}.bak", ts));
+  permissions::create_file(&backup_path, true, None)
+    .and_then(|mut f| f.write_all(&old))
+    .map_err(|e| AppError::io("write backup", &backup_path, e))?;
+  log::debug!("backup {} -> {}", path.display(), backup_path.display());
+
+  prune_backups(&dir, &read_settings().backup);
+  Ok(Some(format!("{}/{}", rel.to_string_lossy().replace('\\', "/"), ts)))
+}
+
+/// 删除某个文件的全部备份（如凭据加密后清除旧的明文副本）
+pub fn purge_backups(path: &std::path::Path) -> AppResult<()> {
+  let root = codex_dir()?;
+  let Ok(rel) = path.strip_prefix(&root) else {
+    return Ok(());
+  };
+  let dir = backups_dir()?.join(rel);
+  match fs::remove_dir_all(&dir) {
+    Ok(()) => Ok(()),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+    Err(e) => Err(AppError::io("remove", &dir, e)),
+  }
+}
+
+/// 列出某个文件备份目录下的所有备份时间戳（从新到旧）
+fn backup_timestamps(dir: &std::path::Path) -> Vec<u64> {
+  let mut res: Vec<u64> = fs::read_dir(dir)
+    .map(|entries| {
+      entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str().and_then(|n| n.strip_suffix(".bak")).and_then(|n| n.parse().ok()))
+        .collect()
+    })
+    .unwrap_or_default();
+  res.sort_unstable_by(|a, b| b.cmp(a));
+  res
+}
+
+/// 按保留策略清理旧备份
+pub fn prune_backups(dir: &std::path::Path, policy: &BackupSettings) {
+  let min_ts = if policy.max_age_days > 0 {
+    now_millis().saturating_sub(policy.max_age_days * 24 * 60 * 60 * 1000)
+  } else {
+    0
+  };
+  for (i, ts) in backup_timestamps(dir).into_iter().enumerate() {
+    let over_count = policy.max_count > 0 && i >= policy.max_count;
+    if over_count || ts < min_ts {
+      let _ = fs::remove_file(dir.join(format!("{}.bak", ts)));
+    }
+  }
+}
+
+#[derive(Serialize)]
+pub struct BackupEntry {
+  /// 备份 ID，形如 "config.toml/1760000000000"
+  pub id: String,
+  /// 被备份文件相对 ~/.codex 的路径
+  pub file: String,
+  /// 备份时间（Unix 毫秒）
+  pub created_at: u64,
+  pub size: u64,
+}
+
+/// 解析备份 ID，返回 (目标文件路径, 备份文件路径)
+fn resolve_backup(id: &str) -> AppResult<(PathBuf, PathBuf)> {
+  let (file, ts) = id.rsplit_once('/').ok_or_else(|| AppError::invalid(format!("invalid backup id '{}'", id)))?;
+  let ts: u64 = ts.parse().map_err(|_| AppError::invalid(format!("invalid backup id '{}'", id)))?;
+  let rel = std::path::Path::new(file);
+  if file.is_empty() || !rel.components().all(|c| matches!(c, std::path::Component::Normal(_))) {
+    return Err(AppError::invalid(format!("invalid backup id '{}'", id)));
+  }
+  let backup_path = backups_dir()?.join(rel).join(format!("{}.bak", ts));
+  if !backup_path.exists() {
+    return Err(AppError::not_found("backup", id));
+  }
+  Ok((codex_dir()?.join(rel), backup_path))
+}
+
+/// 列出备份，可按文件相对路径过滤（如 "config.toml"），按时间从新到旧
+pub fn list_backups(file: Option<String>) -> AppResult<Vec<BackupEntry>> {
+  let root = backups_dir()?;
+  let mut res = vec![];
+  let mut stack = vec![root.clone()];
+  while let Some(dir) = stack.pop() {
+    let Ok(entries) = fs::read_dir(&dir) else { continue };
+    let mut has_backups = false;
+    for entry in entries.flatten() {
+      let path = entry.path();
+      if path.is_dir() {
+        stack.push(path);
+      } else {
+        has_backups = true;
+      }
+    }
+    if !has_backups {
+      continue;
+    }
+    let rel = dir.strip_prefix(&root).unwrap_or(&dir).to_string_lossy().replace('\\', "/");
+    if file.as_deref().is_some_and(|f| f != rel) {
+      continue;
+    }
+    for ts in backup_timestamps(&dir) {
+      let size = fs::metadata(dir.join(format!("{}.bak", ts))).map(|m| m.len()).unwrap_or(0);
+      res.push(BackupEntry { id: format!("{}/{}", rel, ts), file: rel.clone(), created_at: ts, size });
+    }
+  }
+  res.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+  Ok(res)
+}
+
+/// 返回备份与当前文件之间的 unified diff
+pub fn diff_backup(id: String) -> AppResult<String> {
+  let (target, backup_path) = resolve_backup(&id)?;
+  let old = fs::read_to_string(&backup_path).map_err(|e| AppError::io("read", &backup_path, e))?;
+  let current = fs::read_to_string(&target).unwrap_or_default();
+  let diff = similar::TextDiff::from_lines(&old, &current);
+  Ok(diff.unified_diff().context_radius(3).header(&format!("backup/{}", id), "current").to_string())
+}
+
+/// 恢复备份；恢复本身也经过 atomic_write，因此当前版本会先被备份
+pub fn restore_backup(id: String) -> AppResult<()> {
+  let (target, backup_path) = resolve_backup(&id)?;
+  let content = fs::read_to_string(&backup_path).map_err(|e| AppError::io("read", &backup_path, e))?;
+  atomic_write(&target, &content)?;
+  log::info!("restored {} from backup {}", target.display(), id);
+  Ok(())
+}
+
+pub fn get_backup_settings() -> BackupSettings {
+  read_settings().backup
+}
+
+/// 更新备份保留策略，并立即对已有备份生效
+pub fn set_backup_settings(settings: BackupSettings) -> AppResult<()> {
+  let mut app = read_settings();
+  app.backup = settings;
+  write_settings(&app)?;
+  let mut dirs: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
+  for entry in list_backups(None)? {
+    dirs.insert(backups_dir()?.join(entry.file));
+  }
+  for dir in dirs {
+    prune_backups(&dir, &app.backup);
+  }
+  Ok(())
+}
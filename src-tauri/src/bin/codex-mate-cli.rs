@@ -0,0 +1,1242 @@
+//! codex-mate-cli: 无界面的命令行工具，与桌面应用共用 codex_mate 库
+//! 适用于 shell 脚本、dotfiles 初始化等场景
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal};
+use std::process::ExitCode;
+
+use codex_mate::error::{AppError, AppResult};
+use codex_mate::{
+  accounts, backup, config, credentials, effective, health, homes, keys, logging, mcp, migrate, models, nodes, permissions, presets, profiles, projects, relay,
+  schema, storage, vault, version,
+};
+
+#[derive(Parser)]
+#[command(name = "codex-mate-cli", version, about = "Manage Codex CLI nodes, MCP servers, projects and config.toml")]
+struct Cli {
+  /// Print machine-readable JSON output
+  #[arg(long, global = true)]
+  json: bool,
+
+  /// Codex home to operate on: a registered home name or a directory path
+  /// (defaults to $CODEX_HOME or ~/.codex)
+  #[arg(long, global = true)]
+  home: Option<String>,
+
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Manage model providers (nodes)
+  #[command(subcommand)]
+  Nodes(NodesCommand),
+  /// Create nodes from built-in or team provider templates
+  #[command(subcommand)]
+  Presets(PresetsCommand),
+  /// List a provider's models and pick the active model
+  #[command(subcommand)]
+  Models(ModelsCommand),
+  /// Route Codex through a local relay so node switches apply to running sessions
+  #[command(subcommand)]
+  Relay(RelayCommand),
+  /// Inspect stored provider credentials
+  #[command(subcommand)]
+  Credentials(CredentialsCommand),
+  /// Manage multiple labelled API keys per provider
+  #[command(subcommand)]
+  Keys(KeysCommand),
+  /// Save and switch between ChatGPT account logins (auth.json snapshots)
+  #[command(subcommand)]
+  Accounts(AccountsCommand),
+  /// Manage [profiles.*] and the default `profile`
+  #[command(subcommand)]
+  Profiles(ProfilesCommand),
+  /// Manage MCP servers
+  #[command(subcommand)]
+  Mcp(McpCommand),
+  /// Manage project trust levels
+  #[command(subcommand)]
+  Projects(ProjectsCommand),
+  /// Read or write config.toml keys
+  #[command(subcommand)]
+  Config(ConfigCommand),
+  /// Manage registered Codex home directories
+  #[command(subcommand)]
+  Homes(HomesCommand),
+  /// Inspect and restore the backups taken before each write
+  #[command(subcommand)]
+  Backups(BackupsCommand),
+  /// Inspect codex-mate logs
+  #[command(subcommand)]
+  Logs(LogsCommand),
+  /// Manage credential encryption; commands that need the key read the
+  /// passphrase from $CODEX_MATE_VAULT_PASSPHRASE
+  #[command(subcommand)]
+  Vault(VaultCommand),
+  /// Report overly permissive file modes under the Codex home
+  Permissions {
+    /// Repair the reported modes
+    #[arg(long)]
+    fix: bool,
+  },
+}
+
+#[derive(Subcommand)]
+enum NodesCommand {
+  /// List all nodes
+  Ls,
+  /// Show every model_providers field of a node
+  Show { name: String },
+  /// Switch the active node
+  Switch { name: String },
+  /// Add or update a node
+  Add {
+    name: String,
+    #[command(flatten)]
+    fields: Box<ProviderArgs>,
+    /// API key; use "-" to read it from stdin
+    #[arg(long)]
+    key: Option<String>,
+  },
+  /// Rename a node, moving its credential and updating profiles that use it
+  Mv { name: String, new_name: String },
+  /// Copy a node and its credential under a new name
+  Cp { name: String, new_name: String },
+  /// Remove a node and its credential
+  Rm {
+    name: String,
+    /// Allow removing the active node
+    #[arg(long)]
+    force: bool,
+  },
+  /// Check connectivity of nodes via GET {base_url}/models (all nodes by default)
+  Check {
+    names: Vec<String>,
+    /// Per-request timeout in milliseconds
+    #[arg(long)]
+    timeout_ms: Option<u64>,
+  },
+}
+
+/// model_providers 字段；未给出的字段保持原值
+#[derive(Args)]
+struct ProviderArgs {
+  /// Provider base URL (required for new nodes)
+  #[arg(long)]
+  base_url: Option<String>,
+  /// Wire API: responses or chat
+  #[arg(long)]
+  wire_api: Option<String>,
+  #[arg(long)]
+  requires_openai_auth: Option<bool>,
+  /// Environment variable Codex reads the key from (defaults to OPENAI_API_KEY)
+  #[arg(long)]
+  env_key: Option<String>,
+  /// Hint Codex shows when the env_key variable is missing
+  #[arg(long)]
+  env_key_instructions: Option<String>,
+  /// Bearer token stored directly in config.toml instead of the credential store
+  #[arg(long)]
+  experimental_bearer_token: Option<String>,
+  /// Query parameter added to every request, NAME=VALUE (repeatable; "" clears)
+  #[arg(long = "query-param", value_name = "NAME=VALUE")]
+  query_params: Vec<String>,
+  /// Extra HTTP header, NAME=VALUE (repeatable; "" clears)
+  #[arg(long = "header", value_name = "NAME=VALUE")]
+  http_headers: Vec<String>,
+  /// HTTP header read from an environment variable, NAME=VAR (repeatable; "" clears)
+  #[arg(long = "env-header", value_name = "NAME=VAR")]
+  env_http_headers: Vec<String>,
+  #[arg(long)]
+  request_max_retries: Option<u64>,
+  #[arg(long)]
+  stream_max_retries: Option<u64>,
+  #[arg(long)]
+  stream_idle_timeout_ms: Option<u64>,
+}
+
+impl ProviderArgs {
+  fn into_config(self) -> AppResult<nodes::ProviderConfig> {
+    Ok(nodes::ProviderConfig {
+      base_url: self.base_url,
+      wire_api: self.wire_api,
+      requires_openai_auth: self.requires_openai_auth,
+      env_key: self.env_key,
+      env_key_instructions: self.env_key_instructions,
+      experimental_bearer_token: self.experimental_bearer_token,
+      query_params: parse_pairs("--query-param", self.query_params)?,
+      http_headers: parse_pairs("--header", self.http_headers)?,
+      env_http_headers: parse_pairs("--env-header", self.env_http_headers)?,
+      request_max_retries: self.request_max_retries,
+      stream_max_retries: self.stream_max_retries,
+      stream_idle_timeout_ms: self.stream_idle_timeout_ms,
+      ..Default::default()
+    })
+  }
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+  /// List models offered by a provider (cached after the first fetch)
+  Ls {
+    provider: String,
+    /// Fetch again from GET {base_url}/models instead of using the cache
+    #[arg(long)]
+    refresh: bool,
+  },
+  /// Set the top-level `model` (or a profile's model) after checking the provider offers it
+  Use {
+    model: String,
+    /// Provider to validate against (defaults to the profile's or the active model_provider)
+    #[arg(long)]
+    provider: Option<String>,
+    /// Set profiles.<PROFILE>.model instead of the top-level model
+    #[arg(long)]
+    profile: Option<String>,
+  },
+}
+
+#[derive(Subcommand)]
+enum PresetsCommand {
+  /// List available templates (built-in, ~/.codex/codex-mate/presets.toml and team catalogs)
+  Ls,
+  /// Show a template's fields and variables
+  Show { id: String },
+  /// Create or update a node from a template; missing values are prompted for on a terminal
+  Add {
+    id: String,
+    /// Node name (defaults to the template's node name)
+    #[arg(long)]
+    name: Option<String>,
+    /// Template variable, NAME=VALUE (repeatable)
+    #[arg(long = "set", value_name = "NAME=VALUE")]
+    values: Vec<String>,
+    /// API key; use "-" to read it from stdin
+    #[arg(long)]
+    key: Option<String>,
+  },
+  /// Set the team catalog files loaded after the built-in templates (no paths clears them)
+  Catalogs { paths: Vec<String> },
+}
+
+#[derive(Subcommand)]
+enum ProfilesCommand {
+  /// List profiles; the default profile is marked with *
+  Ls,
+  /// Add or update a profile (only the given fields change; "" removes a field)
+  Add {
+    name: String,
+    #[arg(long)]
+    model: Option<String>,
+    #[arg(long)]
+    model_provider: Option<String>,
+    /// untrusted, on-failure, on-request or never
+    #[arg(long)]
+    approval_policy: Option<String>,
+    /// read-only, workspace-write or danger-full-access
+    #[arg(long)]
+    sandbox_mode: Option<String>,
+    #[arg(long)]
+    model_reasoning_effort: Option<String>,
+    #[arg(long)]
+    model_reasoning_summary: Option<String>,
+    #[arg(long)]
+    model_verbosity: Option<String>,
+  },
+  /// Remove a profile
+  Rm {
+    name: String,
+    /// Allow removing the default profile
+    #[arg(long)]
+    force: bool,
+  },
+  /// Make a profile the default (`profile = "<name>"`); no name clears it
+  Use { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum RelayCommand {
+  /// Show whether config.toml points at the relay and whether it is listening
+  Status,
+  /// Point config.toml at the relay, forwarding to the current node
+  Enable {
+    /// Local port to listen on (remembered for later runs)
+    #[arg(long)]
+    port: Option<u16>,
+  },
+  /// Switch back to the relay target and remove the relay provider
+  Disable,
+  /// Set the ordered failover group tried when the target returns 5xx/429 or times out
+  /// (no nodes clears the group)
+  Failover {
+    nodes: Vec<String>,
+    /// How long a failed node is skipped before being tried again
+    #[arg(long)]
+    cooldown_secs: Option<u64>,
+  },
+  /// Run the relay in the foreground until interrupted
+  Serve,
+}
+
+#[derive(Subcommand)]
+enum CredentialsCommand {
+  /// Report credentials.json health without printing any key
+  Diag,
+  /// Print the plaintext key of one provider (recorded in the audit log)
+  Reveal { name: String },
+  /// Print `export <env_key>=<key>` for a provider (the active node by default),
+  /// for use as `eval "$(codex-mate-cli credentials env)"`
+  Env { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum KeysCommand {
+  /// List a provider's keys (fingerprints only)
+  Ls { provider: String },
+  /// Add a labelled key; the existing single key is kept as "default"
+  Add {
+    provider: String,
+    label: String,
+    /// API key; use "-" to read it from stdin
+    #[arg(long, default_value = "-")]
+    key: String,
+  },
+  /// Remove a key
+  Rm { provider: String, label: String },
+  /// Make a key the one used by Codex and the relay
+  Use { provider: String, label: String },
+  /// Set the rotation policy: manual, round_robin or on_429
+  Rotation { provider: String, policy: String },
+}
+
+#[derive(Subcommand)]
+enum AccountsCommand {
+  /// List saved account snapshots
+  Ls,
+  /// Save the current ChatGPT login under a name (overwrites a snapshot of the same name)
+  Save { name: String },
+  /// Restore a snapshot to auth.json and switch to the ChatGPT account node
+  Use { name: String },
+  /// Delete a snapshot
+  Rm { name: String },
+}
+
+#[derive(Subcommand)]
+enum McpCommand {
+  /// List MCP servers
+  Ls,
+  /// Add or update an MCP server
+  Add {
+    name: String,
+    command: String,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+  },
+  /// Remove an MCP server
+  Rm { name: String },
+}
+
+#[derive(Subcommand)]
+enum ProjectsCommand {
+  /// List projects
+  Ls,
+  /// Mark a project path as trusted
+  Trust {
+    path: String,
+    /// Trust level: trusted or untrusted
+    #[arg(long, default_value = "trusted")]
+    level: String,
+  },
+  /// Remove a project's trust entry
+  Untrust { path: String },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+  /// Print the value at a dotted key path, e.g. model_providers.foo.base_url
+  Get { key: String },
+  /// Set a value at a dotted key path; the value is parsed as a TOML literal when possible
+  Set { key: String, value: String },
+  /// Check config.toml against the known Codex schema
+  Lint {
+    /// Check this file instead of config.toml
+    #[arg(long)]
+    file: Option<String>,
+  },
+  /// Preview rewrites of renamed or retired keys supported by the installed Codex
+  Migrate {
+    /// Apply the rewrites (config.toml is backed up first)
+    #[arg(long)]
+    apply: bool,
+  },
+  /// Show the settings Codex would use and where each value comes from
+  Resolve {
+    /// Profile to apply, as with `codex --profile`; defaults to the root `profile` key
+    #[arg(long, short)]
+    profile: Option<String>,
+    /// Override as with `codex -c key=value`; may be repeated
+    #[arg(long = "config", short = 'c', value_name = "KEY=VALUE")]
+    overrides: Vec<String>,
+  },
+}
+
+#[derive(Subcommand)]
+enum HomesCommand {
+  /// List the default and registered Codex homes
+  Ls,
+  /// Register a Codex home directory
+  Add { name: String, path: String },
+  /// Unregister a Codex home (the directory itself is kept)
+  Rm { name: String },
+  /// Select the home used by the desktop app
+  Use { name: String },
+}
+
+#[derive(Subcommand)]
+enum BackupsCommand {
+  /// List backups, newest first
+  Ls {
+    /// Only list backups of this file, relative to the Codex home (e.g. config.toml)
+    #[arg(long)]
+    file: Option<String>,
+  },
+  /// Show a unified diff between a backup and the current file
+  Diff { id: String },
+  /// Restore a backup (the current file is backed up first)
+  Restore { id: String },
+  /// Show the retention policy, or update it when options are given (0 means unlimited)
+  Retention {
+    /// Backups kept per file
+    #[arg(long)]
+    max_count: Option<usize>,
+    /// Days a backup is kept
+    #[arg(long)]
+    max_age_days: Option<u64>,
+  },
+}
+
+#[derive(Subcommand)]
+enum LogsCommand {
+  /// Print the most recent log entries
+  Tail {
+    /// Number of entries to print
+    #[arg(short = 'n', long, default_value_t = 50)]
+    lines: usize,
+    /// Minimum level: error, warn, info, debug or trace
+    #[arg(long)]
+    level: Option<String>,
+  },
+  /// Print the log directory
+  Path,
+}
+
+#[derive(Subcommand)]
+enum VaultCommand {
+  /// Show whether credentials are encrypted
+  Status,
+  /// Encrypt credentials.json (passphrase from $CODEX_MATE_VAULT_PASSPHRASE or stdin)
+  Enable,
+  /// Decrypt credentials.json back to plaintext (passphrase from $CODEX_MATE_VAULT_PASSPHRASE or stdin)
+  Disable,
+}
+
+/// 命令输出：JSON 模式下序列化 data，否则打印 text
+struct Output {
+  data: serde_json::Value,
+  text: String,
+}
+
+impl Output {
+  fn new<T: Serialize>(data: &T, text: impl Into<String>) -> AppResult<Self> {
+    let data = serde_json::to_value(data)?;
+    Ok(Output { data, text: text.into() })
+  }
+
+  fn done(text: impl Into<String>) -> Self {
+    Output { data: serde_json::json!({ "ok": true }), text: text.into() }
+  }
+}
+
+/// 将项目路径规范化为绝对路径（Codex 以绝对路径作为 projects 的键）
+fn absolute_path(path: &str) -> String {
+  std::fs::canonicalize(path).map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|_| path.to_string())
+}
+
+fn read_key(key: String) -> AppResult<String> {
+  if key != "-" {
+    return Ok(key);
+  }
+  let mut line = String::new();
+  std::io::stdin().lock().read_line(&mut line).map_err(|e| AppError::invalid(format!("read key from stdin failed: {}", e)))?;
+  Ok(line.trim().to_string())
+}
+
+const PASSPHRASE_ENV: &str = "CODEX_MATE_VAULT_PASSPHRASE";
+
+fn read_passphrase() -> AppResult<String> {
+  match std::env::var(PASSPHRASE_ENV) {
+    Ok(p) if !p.is_empty() => Ok(p),
+    _ => read_key("-".into()),
+  }
+}
+
+/// 在单线程运行时中执行异步的库函数（网络请求）
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+  tokio::runtime::Builder::new_current_thread()
+    .enable_all()
+    .build()
+    .expect("failed to start tokio runtime")
+    .block_on(future)
+}
+
+/// 解析重复出现的 NAME=VALUE 参数；未给出时返回 None（保持原值），单个空串表示清空
+fn parse_pairs(flag: &str, pairs: Vec<String>) -> AppResult<Option<BTreeMap<String, String>>> {
+  if pairs.is_empty() {
+    return Ok(None);
+  }
+  pairs
+    .iter()
+    .filter(|p| !p.is_empty())
+    .map(|p| match p.split_once('=') {
+      Some((k, v)) => Ok((k.trim().to_string(), v.to_string())),
+      None => Err(AppError::invalid(format!("{} expects NAME=VALUE, got '{}'", flag, p))),
+    })
+    .collect::<AppResult<_>>()
+    .map(Some)
+}
+
+fn run_nodes(cmd: NodesCommand) -> AppResult<Output> {
+  match cmd {
+    NodesCommand::Ls => {
+      let list = nodes::list_nodes()?;
+      let current = list.current_provider.as_deref();
+      let width = list.providers.iter().map(|p| p.name.len()).max().unwrap_or(0);
+      let text = list
+        .providers
+        .iter()
+        .map(|p| {
+          let mark = if Some(p.name.as_str()) == current { "*" } else { " " };
+          let target = match p.kind {
+            nodes::NodeKind::Chatgpt => "(ChatGPT account)".to_string(),
+            nodes::NodeKind::ApiKey => p.base_url.clone().unwrap_or_else(|| "-".into()),
+          };
+          let cred = match (p.has_credential, p.env_key.as_str()) {
+            (true, credentials::DEFAULT_ENV_KEY) => "credential".to_string(),
+            (true, env_key) => format!("credential: {}", env_key),
+            (false, _) => "no credential".to_string(),
+          };
+          let errors = match p.errors.len() {
+            0 => String::new(),
+            n => format!("  ({} invalid field{}, see `nodes show`)", n, if n == 1 { "" } else { "s" }),
+          };
+          format!("{} {:width$}  {}  [{}]{}", mark, p.name, target, cred, errors, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&list, text)
+    }
+    NodesCommand::Show { name } => {
+      let list = nodes::list_nodes()?;
+      let info = list.providers.iter().find(|p| p.name == name).ok_or_else(|| AppError::not_found("provider", &name))?;
+      let fields = serde_json::to_value(&info.config)?;
+      let mut lines = vec![format!("[model_providers.{}]", name)];
+      if let Some(obj) = fields.as_object() {
+        lines.extend(obj.iter().map(|(k, v)| format!("{} = {}", k, v)));
+      }
+      lines.push(format!("# credential ({}): {}", info.env_key, if info.has_credential { "stored" } else { "missing" }));
+      lines.extend(info.errors.iter().map(|e| format!("# invalid, ignored: {}", e)));
+      Output::new(info, lines.join("\n"))
+    }
+    NodesCommand::Switch { name } => {
+      let warning = nodes::switch_node(name.clone())?.map(|w| format!("\nwarning: {}", w));
+      Ok(Output::done(format!("switched to node '{}'{}", name, warning.unwrap_or_default())))
+    }
+    NodesCommand::Add { name, fields, key } => {
+      let fields = fields.into_config()?;
+      let key = key.map(read_key).transpose()?;
+      nodes::upsert_node(name.clone(), fields, key)?;
+      Ok(Output::done(format!("saved node '{}'", name)))
+    }
+    NodesCommand::Mv { name, new_name } => {
+      nodes::rename_node(name.clone(), new_name.clone())?;
+      Ok(Output::done(format!("renamed node '{}' to '{}'", name, new_name.trim())))
+    }
+    NodesCommand::Cp { name, new_name } => {
+      nodes::duplicate_node(name.clone(), new_name.clone())?;
+      Ok(Output::done(format!("copied node '{}' to '{}'", name, new_name.trim())))
+    }
+    NodesCommand::Rm { name, force } => {
+      nodes::delete_node(name.clone(), force)?;
+      Ok(Output::done(format!("removed node '{}'", name)))
+    }
+    NodesCommand::Check { names, timeout_ms } => {
+      let results = block_on(health::check_providers(Some(names), timeout_ms))?;
+      let width = results.iter().map(|r| r.name.len()).max().unwrap_or(0);
+      let text = results
+        .iter()
+        .map(|r| {
+          let status = r.status.map(|s| s.to_string()).unwrap_or_else(|| "---".into());
+          let latency = r.latency_ms.map(|ms| format!("{}ms", ms)).unwrap_or_else(|| "-".into());
+          let detail = match (&r.issue, &r.message) {
+            (None, _) => "ok".to_string(),
+            (Some(issue), Some(msg)) => format!("{:?}: {}", issue, msg),
+            (Some(issue), None) => format!("{:?}", issue),
+          };
+          format!("{:width$}  {}  {:>7}  {}", r.name, status, latency, detail, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&results, text)
+    }
+  }
+}
+
+fn run_models(cmd: ModelsCommand) -> AppResult<Output> {
+  match cmd {
+    ModelsCommand::Ls { provider, refresh } => {
+      let list = block_on(models::list_provider_models(provider, refresh, None))?;
+      Output::new(&list, list.models.join("\n"))
+    }
+    ModelsCommand::Use { model, provider, profile } => {
+      let target = match &profile {
+        Some(p) => format!("profiles.{}.model", p),
+        None => "model".to_string(),
+      };
+      let warnings = block_on(models::set_model(model.clone(), provider, profile))?;
+      let warnings: String = warnings.iter().map(|w| format!("\nwarning: {}", w)).collect();
+      Ok(Output::done(format!("{} = \"{}\"{}", target, model, warnings)))
+    }
+  }
+}
+
+fn preset_list_text(catalog: &presets::PresetCatalog) -> String {
+  let width = catalog.presets.iter().map(|p| p.id.len()).max().unwrap_or(0);
+  let mut lines: Vec<String> = catalog
+    .presets
+    .iter()
+    .map(|p| {
+      let source = if p.source == "builtin" { String::new() } else { format!("  [{}]", p.source) };
+      format!("{:width$}  {}{}", p.id, p.label, source, width = width)
+    })
+    .collect();
+  lines.extend(catalog.errors.iter().map(|e| format!("warning: {}", e)));
+  lines.join("\n")
+}
+
+/// 在终端上逐个询问模板中未填写的变量
+fn prompt_values(preset: &presets::ProviderPreset, values: &mut BTreeMap<String, String>) -> AppResult<()> {
+  let missing: Vec<presets::PresetVariable> = preset.missing_variables(values).into_iter().cloned().collect();
+  if missing.is_empty() || !std::io::stdin().is_terminal() {
+    return Ok(());
+  }
+  for var in missing {
+    match &var.description {
+      Some(desc) => eprint!("{} ({}): ", var.name, desc),
+      None => eprint!("{}: ", var.name),
+    }
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line).map_err(|e| AppError::invalid(format!("read {} failed: {}", var.name, e)))?;
+    values.insert(var.name.clone(), line.trim().to_string());
+  }
+  Ok(())
+}
+
+fn run_presets(cmd: PresetsCommand) -> AppResult<Output> {
+  match cmd {
+    PresetsCommand::Ls => {
+      let catalog = presets::list_presets()?;
+      Output::new(&catalog, preset_list_text(&catalog))
+    }
+    PresetsCommand::Show { id } => {
+      let catalog = presets::list_presets()?;
+      let preset = catalog.presets.iter().find(|p| p.id == id).ok_or_else(|| AppError::not_found("preset", &id))?;
+      let mut lines = vec![format!("{} ({})", preset.label, preset.source)];
+      lines.extend(preset.description.clone());
+      if let Some(obj) = serde_json::to_value(&preset.fields)?.as_object() {
+        lines.extend(obj.iter().map(|(k, v)| format!("  {} = {}", k, v)));
+      }
+      for var in &preset.variables {
+        let default = var.default.as_deref().map(|d| format!(" [default: {}]", d)).unwrap_or_default();
+        lines.push(format!("  {{{}}}{}  {}", var.name, default, var.description.as_deref().unwrap_or("")));
+      }
+      Output::new(preset, lines.join("\n"))
+    }
+    PresetsCommand::Add { id, name, values, key } => {
+      let mut values = parse_pairs("--set", values)?.unwrap_or_default();
+      let key = key.map(read_key).transpose()?;
+      if let Some(preset) = presets::list_presets()?.presets.iter().find(|p| p.id == id) {
+        prompt_values(preset, &mut values)?;
+      }
+      let name = presets::apply_preset(id.clone(), name, values, key)?;
+      Ok(Output::done(format!("saved node '{}' from preset '{}'", name, id)))
+    }
+    PresetsCommand::Catalogs { paths } => {
+      let catalog = presets::set_preset_catalogs(paths)?;
+      let text = match catalog.catalogs.is_empty() {
+        true => "no catalog files loaded".to_string(),
+        false => format!("loaded: {}", catalog.catalogs.join(", ")),
+      };
+      Output::new(&catalog, text)
+    }
+  }
+}
+
+fn relay_status_text(status: &relay::RelayStatus) -> String {
+  let mut lines = vec![format!(
+    "{}  {}  {}  -> {}",
+    if status.enabled { "enabled" } else { "disabled" },
+    if status.running { "listening" } else { "not running" },
+    status.base_url,
+    status.target.as_deref().unwrap_or("-")
+  )];
+  if !status.failover.is_empty() {
+    lines.push(format!("failover: {}  (cooldown {}s)", status.failover.join(" -> "), status.cooldown_secs));
+  }
+  for d in &status.degraded {
+    lines.push(format!("degraded: {} for {}s ({})", d.name, d.remaining_secs, d.reason));
+  }
+  lines.join("\n")
+}
+
+fn run_relay(cmd: RelayCommand) -> AppResult<Output> {
+  match cmd {
+    RelayCommand::Status => {
+      let status = relay::get_relay_status()?;
+      let text = relay_status_text(&status);
+      Output::new(&status, text)
+    }
+    RelayCommand::Enable { port } => {
+      let status = relay::enable_relay(port)?;
+      let mut text = relay_status_text(&status);
+      if !status.running {
+        text.push_str("\nstart the desktop app or run `codex-mate-cli relay serve` to begin forwarding");
+      }
+      Output::new(&status, text)
+    }
+    RelayCommand::Failover { nodes, cooldown_secs } => {
+      let status = relay::set_relay_failover(nodes, cooldown_secs)?;
+      let text = relay_status_text(&status);
+      Output::new(&status, text)
+    }
+    RelayCommand::Disable => {
+      let warning = relay::disable_relay()?.map(|w| format!("\nwarning: {}", w));
+      Ok(Output::done(format!("relay disabled{}", warning.unwrap_or_default())))
+    }
+    RelayCommand::Serve => {
+      let status = relay::get_relay_status()?;
+      eprintln!("relay listening on {}, forwarding to {}", status.base_url, status.target.as_deref().unwrap_or("-"));
+      block_on(relay::serve_relay())?;
+      Ok(Output::done(""))
+    }
+  }
+}
+
+fn run_keys(cmd: KeysCommand) -> AppResult<Output> {
+  match cmd {
+    KeysCommand::Ls { provider } => {
+      let pool = keys::list_provider_keys(provider)?;
+      let mut lines = vec![format!("rotation: {}", serde_json::to_value(pool.rotation)?.as_str().unwrap_or_default())];
+      for k in &pool.keys {
+        let mark = if k.active { "*" } else { " " };
+        lines.push(format!("{} {}  {}", mark, k.label, k.fingerprint.as_deref().unwrap_or("(locked)")));
+      }
+      Output::new(&pool, lines.join("\n"))
+    }
+    KeysCommand::Add { provider, label, key } => {
+      keys::add_provider_key(provider.clone(), label.clone(), read_key(key)?)?;
+      Ok(Output::done(format!("added key '{}' to '{}'", label, provider)))
+    }
+    KeysCommand::Rm { provider, label } => {
+      keys::remove_provider_key(provider.clone(), label.clone())?;
+      Ok(Output::done(format!("removed key '{}' from '{}'", label, provider)))
+    }
+    KeysCommand::Use { provider, label } => {
+      keys::select_provider_key(provider.clone(), label.clone())?;
+      Ok(Output::done(format!("'{}' now uses key '{}'", provider, label)))
+    }
+    KeysCommand::Rotation { provider, policy } => {
+      let rotation: keys::KeyRotation = serde_json::from_value(serde_json::Value::String(policy.replace('-', "_")))
+        .map_err(|_| AppError::invalid(format!("unknown rotation policy '{}', expected manual, round_robin or on_429", policy)))?;
+      keys::set_key_rotation(provider.clone(), rotation)?;
+      Ok(Output::done(format!("rotation for '{}' set to {}", provider, policy)))
+    }
+  }
+}
+
+fn run_accounts(cmd: AccountsCommand) -> AppResult<Output> {
+  match cmd {
+    AccountsCommand::Ls => {
+      let list = accounts::list_account_snapshots()?;
+      let width = list.iter().map(|a| a.name.len()).max().unwrap_or(0);
+      let text = list
+        .iter()
+        .map(|a| {
+          let mark = if a.is_current { "*" } else { " " };
+          let email = a.info.email.as_deref().unwrap_or("(unknown account)");
+          let plan = a.info.plan.as_deref().map(|p| format!("  [{}]", p)).unwrap_or_default();
+          let saved = a.saved_at.map(|t| format!("  saved {}", format_ts(t * 1000))).unwrap_or_default();
+          format!("{} {:width$}  {}{}{}", mark, a.name, email, plan, saved, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&list, text)
+    }
+    AccountsCommand::Save { name } => {
+      accounts::save_account_snapshot(name.clone())?;
+      Ok(Output::done(format!("saved current login as '{}'", name.trim())))
+    }
+    AccountsCommand::Use { name } => {
+      accounts::restore_account_snapshot(name.clone())?;
+      Ok(Output::done(format!("switched to account '{}'", name.trim())))
+    }
+    AccountsCommand::Rm { name } => {
+      accounts::delete_account_snapshot(name.clone())?;
+      Ok(Output::done(format!("removed account snapshot '{}'", name.trim())))
+    }
+  }
+}
+
+fn run_credentials(cmd: CredentialsCommand) -> AppResult<Output> {
+  match cmd {
+    CredentialsCommand::Diag => {
+      let report = credentials::credentials_diagnostics()?;
+      let mut lines = vec![format!(
+        "{}  {}",
+        report.path,
+        match (&report.parse_error, report.exists) {
+          (_, false) => "missing".to_string(),
+          (Some(e), _) => format!("parse error: {}", e),
+          (None, true) => format!("{} bytes{}", report.size.unwrap_or(0), if report.vault_enabled { ", encrypted" } else { "" }),
+        }
+      )];
+      let width = report.providers.iter().map(|p| p.name.len()).max().unwrap_or(0);
+      for p in &report.providers {
+        let key = match (&p.fingerprint, p.has_key) {
+          (Some(fp), _) => fp.clone(),
+          (None, true) => "(locked)".to_string(),
+          (None, false) => "no key".to_string(),
+        };
+        let configured = if p.configured { "" } else { "  [not configured]" };
+        lines.push(format!("  {:width$}  {}{}", p.name, key, configured, width = width));
+      }
+      lines.extend(report.problems.iter().map(|p| format!("warning: {}", p)));
+      Output::new(&report, lines.join("\n"))
+    }
+    CredentialsCommand::Reveal { name } => {
+      let key = credentials::reveal_credential(name)?;
+      Output::new(&key, key.clone())
+    }
+    CredentialsCommand::Env { name } => {
+      let name = match name {
+        Some(name) => name,
+        None => relay::active_node(config::get_full_config()?.get("model_provider").and_then(|v| v.as_str()).map(|s| s.to_string()))
+          .filter(|n| n != nodes::CHATGPT_NODE)
+          .ok_or_else(|| AppError::invalid("no API key node is active"))?,
+      };
+      let (env_key, key) = credentials::credential_env(name)?;
+      let text = format!("export {}='{}'", env_key, key.replace('\'', "'\\''"));
+      Output::new(&serde_json::json!({ "env_key": env_key, "key": key }), text)
+    }
+  }
+}
+
+fn run_profiles(cmd: ProfilesCommand) -> AppResult<Output> {
+  match cmd {
+    ProfilesCommand::Ls => {
+      let list = profiles::list_profiles()?;
+      let width = list.profiles.iter().map(|p| p.name.len()).max().unwrap_or(0);
+      let text = list
+        .profiles
+        .iter()
+        .map(|p| {
+          let mark = if p.active { "*" } else { " " };
+          let c = &p.config;
+          let summary = [("provider", &c.model_provider), ("model", &c.model), ("approval", &c.approval_policy), ("sandbox", &c.sandbox_mode)]
+            .iter()
+            .filter_map(|(k, v)| v.as_ref().map(|v| format!("{}={}", k, v)))
+            .collect::<Vec<_>>()
+            .join("  ");
+          let errors = match p.errors.as_slice() {
+            [] => String::new(),
+            errors => format!("\n  # invalid, ignored: {}", errors.join("; ")),
+          };
+          format!("{} {:width$}  {}{}", mark, p.name, summary, errors, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&list, text)
+    }
+    ProfilesCommand::Add {
+      name,
+      model,
+      model_provider,
+      approval_policy,
+      sandbox_mode,
+      model_reasoning_effort,
+      model_reasoning_summary,
+      model_verbosity,
+    } => {
+      let fields = profiles::ProfileConfig {
+        model,
+        model_provider,
+        approval_policy,
+        sandbox_mode,
+        model_reasoning_effort,
+        model_reasoning_summary,
+        model_verbosity,
+        ..Default::default()
+      };
+      let warnings = profiles::upsert_profile(name.clone(), fields)?;
+      let warnings: String = warnings.iter().map(|w| format!("\nwarning: {}", w)).collect();
+      Ok(Output::done(format!("saved profile '{}'{}", name.trim(), warnings)))
+    }
+    ProfilesCommand::Rm { name, force } => {
+      profiles::delete_profile(name.clone(), force)?;
+      Ok(Output::done(format!("removed profile '{}'", name)))
+    }
+    ProfilesCommand::Use { name } => {
+      profiles::activate_profile(name.clone())?;
+      Ok(Output::done(match name {
+        Some(name) => format!("default profile set to '{}'", name.trim()),
+        None => "default profile cleared".to_string(),
+      }))
+    }
+  }
+}
+
+fn run_mcp(cmd: McpCommand) -> AppResult<Output> {
+  match cmd {
+    McpCommand::Ls => {
+      let servers = mcp::list_mcp_servers()?;
+      let text = servers
+        .iter()
+        .map(|s| format!("{}  {} {}", s.name, s.command.as_deref().unwrap_or("-"), s.args.as_deref().unwrap_or_default().join(" ")))
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&servers, text)
+    }
+    McpCommand::Add { name, command, args } => {
+      mcp::upsert_mcp_server(name.clone(), command, args)?;
+      Ok(Output::done(format!("saved MCP server '{}'", name)))
+    }
+    McpCommand::Rm { name } => {
+      mcp::delete_mcp_server(name.clone())?;
+      Ok(Output::done(format!("removed MCP server '{}'", name)))
+    }
+  }
+}
+
+fn run_projects(cmd: ProjectsCommand) -> AppResult<Output> {
+  match cmd {
+    ProjectsCommand::Ls => {
+      let list = projects::list_projects()?;
+      let text = list.iter().map(|p| format!("{}  {}", p.trust_level, p.path)).collect::<Vec<_>>().join("\n");
+      Output::new(&list, text)
+    }
+    ProjectsCommand::Trust { path, level } => {
+      let path = absolute_path(&path);
+      projects::upsert_project(path.clone(), level.clone())?;
+      Ok(Output::done(format!("set '{}' to {}", path, level)))
+    }
+    ProjectsCommand::Untrust { path } => {
+      let path = absolute_path(&path);
+      projects::delete_project(path.clone())?;
+      Ok(Output::done(format!("removed trust entry for '{}'", path)))
+    }
+  }
+}
+
+fn run_config(cmd: ConfigCommand) -> AppResult<Output> {
+  match cmd {
+    ConfigCommand::Get { key } => {
+      let value = config::get_config_key(&key)?.ok_or_else(|| AppError::not_found("key", key.as_str()))?;
+      let text = match &value {
+        serde_json::Value::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other)?,
+      };
+      Output::new(&value, text)
+    }
+    ConfigCommand::Set { key, value } => {
+      config::set_config_key(&key, &value)?;
+      // 仅提示，不阻止写入：版本检测可能落后于实际安装的 Codex
+      let warning = version::key_warning(&key, &value).map(|w| format!("\nwarning: {}", w));
+      Ok(Output::done(format!("set {}{}", key, warning.unwrap_or_default())))
+    }
+    ConfigCommand::Lint { file } => {
+      let (name, content) = match file {
+        Some(path) => {
+          let content = std::fs::read_to_string(&path).map_err(|e| AppError::io("read", std::path::Path::new(&path), e))?;
+          (path, content)
+        }
+        None => ("config.toml".to_string(), config::read_config_raw()?),
+      };
+      let diagnostics = schema::validate_config(&content, version::installed_version().as_ref());
+      let text = if diagnostics.is_empty() {
+        format!("{}: no problems found", name)
+      } else {
+        diagnostics
+          .iter()
+          .map(|d| {
+            let severity = match d.severity {
+              schema::Severity::Error => "error",
+              schema::Severity::Warning => "warning",
+            };
+            let at = d.line.map(|l| format!(":{}:{}", l, d.col.unwrap_or(1))).unwrap_or_default();
+            let key = if d.path.is_empty() { String::new() } else { format!(" [{}]", d.path) };
+            format!("{}{}: {}: {}{}", name, at, severity, d.message, key)
+          })
+          .collect::<Vec<_>>()
+          .join("\n")
+      };
+      Output::new(&diagnostics, text)
+    }
+    ConfigCommand::Migrate { apply } => {
+      let plan = if apply { migrate::apply_migrations()? } else { migrate::plan_migrations()? };
+      if plan.migrations.is_empty() {
+        return Output::new(&plan, "no deprecated keys found");
+      }
+      let mut lines: Vec<String> = plan
+        .migrations
+        .iter()
+        .map(|m| {
+          let to = m.to.as_deref().unwrap_or("(removed)");
+          let skipped = if m.supported { String::new() } else { format!("  [skipped: needs codex >= {}]", m.since) };
+          format!("{} -> {}  {}{}", m.from, to, m.note, skipped)
+        })
+        .collect();
+      lines.push(format!("codex: {}", plan.codex_version.as_deref().unwrap_or("not found")));
+      if apply {
+        lines.push(plan.backup.as_ref().map(|b| format!("applied, backup: {}", b)).unwrap_or_else(|| "nothing applied".to_string()));
+      } else if !plan.diff.is_empty() {
+        lines.push(String::new());
+        lines.push(plan.diff.trim_end().to_string());
+      }
+      Output::new(&plan, lines.join("\n"))
+    }
+    ConfigCommand::Resolve { profile, overrides } => {
+      let resolved = effective::resolve_config(profile, overrides)?;
+      let width = resolved.values.iter().map(|v| v.key.len()).max().unwrap_or(0);
+      let mut lines = vec![match (&resolved.profile, resolved.profile_source) {
+        (Some(p), Some(source)) => format!("profile: {}  ({})", p, source.as_str()),
+        _ => "profile: (none)".to_string(),
+      }];
+      lines.extend(resolved.values.iter().map(|v| {
+        let value = match &v.value {
+          serde_json::Value::Null => "(codex default)".to_string(),
+          other => other.to_string(),
+        };
+        format!("{:width$} = {}  ({})", v.key, value, v.source.as_str(), width = width)
+      }));
+      Output::new(&resolved, lines.join("\n"))
+    }
+  }
+}
+
+fn run_homes(cmd: HomesCommand) -> AppResult<Output> {
+  match cmd {
+    HomesCommand::Ls => {
+      let list = homes::list_codex_homes()?;
+      let width = list.homes.iter().map(|h| h.name.len()).max().unwrap_or(0);
+      let text = list
+        .homes
+        .iter()
+        .map(|h| {
+          let mark = if h.is_active { "*" } else { " " };
+          let missing = if h.exists { "" } else { "  (missing)" };
+          format!("{} {:width$}  {}{}", mark, h.name, h.path, missing, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&list, text)
+    }
+    HomesCommand::Add { name, path } => {
+      homes::add_codex_home(name.clone(), absolute_path(&path))?;
+      Ok(Output::done(format!("registered home '{}'", name)))
+    }
+    HomesCommand::Rm { name } => {
+      homes::remove_codex_home(name.clone())?;
+      Ok(Output::done(format!("unregistered home '{}'", name)))
+    }
+    HomesCommand::Use { name } => {
+      homes::set_active_codex_home(name.clone())?;
+      Ok(Output::done(format!("desktop app now uses home '{}'", name)))
+    }
+  }
+}
+
+/// 毫秒时间戳格式化为 UTC 时间 `YYYY-MM-DD HH:MM:SS`
+fn format_ts(ms: u64) -> String {
+  let secs = ms / 1000;
+  let (days, rem) = ((secs / 86400) as i64, secs % 86400);
+  // civil_from_days (Howard Hinnant)
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z - era * 146097;
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+  format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+fn run_backups(cmd: BackupsCommand) -> AppResult<Output> {
+  match cmd {
+    BackupsCommand::Ls { file } => {
+      let list = backup::list_backups(file)?;
+      let text = list
+        .iter()
+        .map(|b| format!("{}  {:>8} B  {}", format_ts(b.created_at), b.size, b.id))
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&list, text)
+    }
+    BackupsCommand::Diff { id } => {
+      let diff = backup::diff_backup(id)?;
+      let text = if diff.is_empty() { "backup matches the current file".to_string() } else { diff.trim_end().to_string() };
+      Output::new(&diff, text)
+    }
+    BackupsCommand::Restore { id } => {
+      backup::restore_backup(id.clone())?;
+      Ok(Output::done(format!("restored backup {}", id)))
+    }
+    BackupsCommand::Retention { max_count, max_age_days } => {
+      let mut settings = backup::get_backup_settings();
+      if max_count.is_some() || max_age_days.is_some() {
+        settings.max_count = max_count.unwrap_or(settings.max_count);
+        settings.max_age_days = max_age_days.unwrap_or(settings.max_age_days);
+        backup::set_backup_settings(settings.clone())?;
+      }
+      let limit = |n: u64, unit: &str| if n == 0 { "unlimited".to_string() } else { format!("{} {}", n, unit) };
+      let text = format!(
+        "max count: {}\nmax age: {}",
+        limit(settings.max_count as u64, "per file"),
+        limit(settings.max_age_days, "days")
+      );
+      Output::new(&settings, text)
+    }
+  }
+}
+
+fn run_logs(cmd: LogsCommand) -> AppResult<Output> {
+  match cmd {
+    LogsCommand::Tail { lines, level } => {
+      let entries = logging::tail_logs(Some(lines), level)?;
+      let text = entries
+        .iter()
+        .map(|e| format!("{} {:5} {}: {}", format_ts(e.ts), e.level, e.target, e.message))
+        .collect::<Vec<_>>()
+        .join("\n");
+      Output::new(&entries, text)
+    }
+    LogsCommand::Path => {
+      let dir = logging::logs_dir()?;
+      Output::new(&dir, dir.display().to_string())
+    }
+  }
+}
+
+fn run_vault(cmd: VaultCommand) -> AppResult<Output> {
+  match cmd {
+    VaultCommand::Status => {
+      let status = vault::get_vault_status()?;
+      let text = if status.enabled { "credentials are encrypted" } else { "credentials are stored in plaintext" };
+      Output::new(&status, text)
+    }
+    VaultCommand::Enable => {
+      vault::enable_vault(read_passphrase()?)?;
+      Ok(Output::done("credentials encrypted"))
+    }
+    VaultCommand::Disable => {
+      vault::disable_vault(read_passphrase()?)?;
+      Ok(Output::done("credentials decrypted"))
+    }
+  }
+}
+
+fn run_permissions(fix: bool) -> AppResult<Output> {
+  let report = permissions::audit_permissions(fix)?;
+  let text = if !report.supported {
+    "permission checks are not supported on this platform".to_string()
+  } else if report.issues.is_empty() {
+    format!("checked {} paths, no issues found", report.checked)
+  } else {
+    report
+      .issues
+      .iter()
+      .map(|i| {
+        let (Some(mode), Some(expected)) = (&i.mode, &i.expected) else {
+          return format!("unreadable  {}  ({})", i.path, i.error.as_deref().unwrap_or_default());
+        };
+        let status = match (&i.error, i.fixed) {
+          (Some(e), _) => format!("  (fix failed: {})", e),
+          (None, true) => "  (fixed)".to_string(),
+          (None, false) => String::new(),
+        };
+        format!("{} -> {}  {}{}", mode, expected, i.path, status)
+      })
+      .collect::<Vec<_>>()
+      .join("\n")
+  };
+  Output::new(&report, text)
+}
+
+fn run(cli: Cli) -> AppResult<Output> {
+  // CLI 默认不跟随桌面应用的选择，与同一 shell 中运行的 codex 保持一致
+  if let Some(home) = &cli.home {
+    storage::set_codex_dir_override(homes::resolve_codex_home(home)?);
+  }
+  logging::init_logging();
+  // 每次调用都是新进程，加密库通过环境变量中的口令自动解锁
+  if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+    if !passphrase.is_empty() && vault::vault_enabled()? {
+      vault::unlock_vault(passphrase)?;
+    }
+  }
+  match cli.command {
+    Command::Nodes(cmd) => run_nodes(cmd),
+    Command::Presets(cmd) => run_presets(cmd),
+    Command::Models(cmd) => run_models(cmd),
+    Command::Relay(cmd) => run_relay(cmd),
+    Command::Credentials(cmd) => run_credentials(cmd),
+    Command::Keys(cmd) => run_keys(cmd),
+    Command::Accounts(cmd) => run_accounts(cmd),
+    Command::Profiles(cmd) => run_profiles(cmd),
+    Command::Mcp(cmd) => run_mcp(cmd),
+    Command::Projects(cmd) => run_projects(cmd),
+    Command::Config(cmd) => run_config(cmd),
+    Command::Homes(cmd) => run_homes(cmd),
+    Command::Backups(cmd) => run_backups(cmd),
+    Command::Logs(cmd) => run_logs(cmd),
+    Command::Vault(cmd) => run_vault(cmd),
+    Command::Permissions { fix } => run_permissions(fix),
+  }
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  let json = cli.json;
+  match run(cli) {
+    Ok(out) => {
+      if json {
+        println!("{}", serde_json::to_string_pretty(&out.data).unwrap_or_default());
+      } else if !out.text.is_empty() {
+        println!("{}", out.text);
+      }
+      ExitCode::SUCCESS
+    }
+    Err(e) => {
+      if json {
+        println!("{}", serde_json::json!({ "error": e }));
+      } else {
+        eprintln!("error: {}", e);
+      }
+      ExitCode::FAILURE
+    }
+  }
+}
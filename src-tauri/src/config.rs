@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::PathBuf;
+use toml::Value as TomlValue;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+use crate::error::{AppError, AppResult};
+use crate::storage::{atomic_write, atomic_write_with_backup, codex_dir};
+
+fn config_path() -> AppResult<PathBuf> {
+  Ok(codex_dir()?.join("config.toml"))
+}
+
+/// 读取 config.toml 原文，文件不存在时返回 ConfigNotFound
+fn read_config_text() -> AppResult<String> {
+  let cfg_path = config_path()?;
+  fs::read_to_string(&cfg_path).map_err(|e| match e.kind() {
+    std::io::ErrorKind::NotFound => AppError::ConfigNotFound { path: cfg_path.display().to_string() },
+    _ => AppError::io("read", &cfg_path, e),
+  })
+}
+
+fn parse_config_value(content: &str) -> AppResult<TomlValue> {
+  toml::from_str(content).map_err(|e| AppError::toml(e.message(), e.span(), content))
+}
+
+pub fn get_full_config() -> AppResult<serde_json::Value> {
+  Ok(toml_to_json(read_config_value()?))
+}
+
+/// TOML 值转为 JSON；日期时间转为其 TOML 文本形式（如 "2025-01-01"），
+/// 避免 serde 序列化时输出 toml 内部的 {"$__toml_private_datetime": ...} 结构
+pub fn toml_to_json(value: TomlValue) -> serde_json::Value {
+  match value {
+    TomlValue::String(s) => s.into(),
+    TomlValue::Integer(i) => i.into(),
+    TomlValue::Float(f) => serde_json::Number::from_f64(f).map_or(serde_json::Value::Null, serde_json::Value::Number),
+    TomlValue::Boolean(b) => b.into(),
+    TomlValue::Datetime(d) => d.to_string().into(),
+    TomlValue::Array(items) => items.into_iter().map(toml_to_json).collect(),
+    TomlValue::Table(tbl) => tbl.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect(),
+  }
+}
+
+pub fn read_config_value() -> AppResult<TomlValue> {
+  parse_config_value(&read_config_text()?)
+}
+
+/// 以保留格式的方式读取 config.toml（注释、键顺序、空行均保留）
+pub fn read_config_doc() -> AppResult<DocumentMut> {
+  let cfg_str = read_config_text()?;
+  cfg_str.parse::<DocumentMut>().map_err(|e| AppError::toml(e.message(), e.span(), &cfg_str))
+}
+
+/// 写回 config.toml：未被修改的部分与原文件逐字节一致
+pub fn write_config_doc(doc: &DocumentMut) -> AppResult<()> {
+  atomic_write(&config_path()?, &doc.to_string())
+}
+
+/// 获取根级子表（如 model_providers / mcp_servers / projects），不存在时创建
+/// 新建的父表为隐式表，只输出 `[parent.child]` 形式的表头
+pub fn ensure_root_table<'a>(doc: &'a mut DocumentMut, key: &str) -> AppResult<&'a mut dyn TableLike> {
+  doc
+    .entry(key)
+    .or_insert_with(|| {
+      let mut t = toml_edit::Table::new();
+      t.set_implicit(true);
+      Item::Table(t)
+    })
+    .as_table_like_mut()
+    .ok_or_else(|| AppError::config(format!("'{}' is not a table", key)))
+}
+
+/// 获取子表中的条目表，不存在时以标准表 `[parent.name]` 的形式追加到文件末尾
+pub fn ensure_entry_table<'a>(parent: &'a mut dyn TableLike, name: &str) -> AppResult<&'a mut dyn TableLike> {
+  if parent.get(name).is_none() {
+    parent.insert(name, Item::Table(toml_edit::Table::new()));
+  }
+  parent
+    .get_mut(name)
+    .and_then(|v| v.as_table_like_mut())
+    .ok_or_else(|| AppError::config(format!("'{}' is not a table", name)))
+}
+
+/// 就地更新键值：保留该键原有的位置和行尾注释，键不存在时追加；
+/// 取值未变化时不做修改，原有写法（引号风格、数字进制、多行字符串等）保持不变
+pub fn set_value_preserving_decor(tbl: &mut dyn TableLike, key: &str, value: toml_edit::Value) {
+  match tbl.get_mut(key).and_then(|v| v.as_value_mut()) {
+    Some(old) if same_value(old, &value) => {}
+    Some(old) => {
+      let decor = old.decor().clone();
+      *old = value;
+      *old.decor_mut() = decor;
+    }
+    None => {
+      tbl.insert(key, Item::Value(value));
+    }
+  }
+}
+
+/// 两个值语义上是否相同，忽略格式差异（如 'x' 与 "x"、0x10 与 16）
+fn same_value(a: &toml_edit::Value, b: &toml_edit::Value) -> bool {
+  let parse = |v: &toml_edit::Value| format!("v = {}", v.to_string().trim()).parse::<toml::Table>().ok();
+  matches!((parse(a), parse(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// 将前端传入的 JSON 值转换为 TOML 值（对象转换为内联表）
+pub fn json_to_toml(v: &serde_json::Value) -> toml_edit::Value {
+  match v {
+    serde_json::Value::Null => String::new().into(),
+    serde_json::Value::Bool(b) => (*b).into(),
+    serde_json::Value::Number(n) => {
+      if let Some(i) = n.as_i64() { i.into() }
+      else if let Some(f) = n.as_f64() { f.into() }
+      else { n.to_string().into() }
+    }
+    serde_json::Value::String(s) => s.as_str().into(),
+    serde_json::Value::Array(arr) => toml_edit::Value::Array(arr.iter().map(json_to_toml).collect()),
+    serde_json::Value::Object(obj) => {
+      let mut t = toml_edit::InlineTable::new();
+      for (k, v) in obj.iter() { t.insert(k, json_to_toml(v)); }
+      toml_edit::Value::InlineTable(t)
+    }
+  }
+}
+
+pub fn read_config_raw() -> AppResult<String> {
+  read_config_text()
+}
+
+/// 校验后整体写入 config.toml，返回写入前生成的备份 ID（内容未变化时为 None）
+pub fn write_config_raw(content: String) -> AppResult<Option<String>> {
+  // validate TOML first
+  parse_config_value(&content)?;
+  let backup = atomic_write_with_backup(&config_path()?, &content)?;
+  log::info!("config.toml saved from raw editor ({} bytes)", content.len());
+  Ok(backup)
+}
+
+/// 解析点分键路径，如 `model_providers.a.base_url`、`projects."/path/to/x".trust_level`
+pub(crate) fn parse_key_path(path: &str) -> AppResult<Vec<String>> {
+  let keys = toml_edit::Key::parse(path).map_err(|e| AppError::invalid(format!("invalid key path '{}': {}", path, e.message().trim())))?;
+  Ok(keys.iter().map(|k| k.get().to_string()).collect())
+}
+
+/// parse_key_path 的逆操作：拼接为点分键路径，必要时为键加引号
+pub(crate) fn display_key_path(path: &[String]) -> String {
+  path.iter().map(|k| toml_edit::Key::new(k.as_str()).display_repr().into_owned()).collect::<Vec<_>>().join(".")
+}
+
+/// 读取点分路径对应的配置值，不存在时返回 None
+pub fn get_config_key(path: &str) -> AppResult<Option<serde_json::Value>> {
+  let keys = parse_key_path(path)?;
+  let cfg = get_full_config()?;
+  let mut cur = &cfg;
+  for k in &keys {
+    match cur.get(k) {
+      Some(v) => cur = v,
+      None => return Ok(None),
+    }
+  }
+  Ok(Some(cur.clone()))
+}
+
+/// 将 `-c key=value` 的取值按 TOML 字面量解析（规则同 set_config_key），无法解析时视为字符串
+pub(crate) fn parse_literal(value: &str) -> TomlValue {
+  value
+    .parse::<toml_edit::Value>()
+    .ok()
+    .and_then(|v| toml::from_str::<toml::Table>(&format!("v = {}", v)).ok())
+    .and_then(|mut t| t.remove("v"))
+    .unwrap_or_else(|| TomlValue::String(value.to_string()))
+}
+
+/// 按点分路径就地设置配置值
+/// value 按 TOML 字面量解析（如 `true`、`3`、`["a", "b"]`），无法解析时作为字符串写入
+pub fn set_config_key(path: &str, value: &str) -> AppResult<()> {
+  let keys = parse_key_path(path)?;
+  let (last, parents) = keys.split_last().ok_or_else(|| AppError::invalid("empty key path"))?;
+  let value = value.parse::<toml_edit::Value>().unwrap_or_else(|_| value.into());
+
+  let mut doc = read_config_doc()?;
+  let tbl = ensure_table_path(doc.as_table_mut(), parents)?;
+  set_value_preserving_decor(tbl, last, value);
+  write_config_doc(&doc)
+}
+
+/// 沿键路径获取子表，缺少的表以隐式表创建（只输出最内层的表头）
+pub fn ensure_table_path<'a>(root: &'a mut dyn TableLike, keys: &[String]) -> AppResult<&'a mut dyn TableLike> {
+  let mut tbl = root;
+  for k in keys {
+    if tbl.get(k).is_none() {
+      let mut t = toml_edit::Table::new();
+      t.set_implicit(true);
+      tbl.insert(k, Item::Table(t));
+    }
+    tbl = tbl
+      .get_mut(k)
+      .and_then(|v| v.as_table_like_mut())
+      .ok_or_else(|| AppError::config(format!("'{}' is not a table", k)))?;
+  }
+  Ok(tbl)
+}
@@ -0,0 +1,300 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+
+use crate::config::get_full_config;
+use crate::error::{AppError, AppResult};
+use crate::nodes::CHATGPT_NODE;
+use crate::relay::RELAY_NODE;
+use crate::storage::{atomic_write, codex_dir};
+use crate::vault::{is_encrypted, reveal, seal_credentials, vault_enabled};
+
+/// 凭据文件: ~/.codex/codex-mate/credentials.json
+pub fn credentials_path() -> AppResult<std::path::PathBuf> {
+  Ok(codex_dir()?.join("codex-mate").join("credentials.json"))
+}
+
+/// 未配置 env_key 的节点使用的凭据字段，即 Codex 默认读取的环境变量
+pub const DEFAULT_ENV_KEY: &str = "OPENAI_API_KEY";
+
+/// 节点的凭据字段名：config.toml 中该节点的 env_key，未配置时为 OPENAI_API_KEY
+pub fn env_key_of(cfg: &serde_json::Value, name: &str) -> String {
+  cfg
+    .get("model_providers")
+    .and_then(|v| v.get(name))
+    .and_then(|v| v.get("env_key"))
+    .and_then(|v| v.as_str())
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .unwrap_or(DEFAULT_ENV_KEY)
+    .to_string()
+}
+
+pub fn credential_field(name: &str) -> AppResult<String> {
+  Ok(env_key_of(&get_full_config()?, name))
+}
+
+/// 记录密钥所在字段名（保存时节点的 env_key）的元数据字段；修改 env_key 后据此找回原字段下的密钥
+pub const STORED_ENV_KEY_FIELD: &str = "stored_env_key";
+
+/// 节点当前密钥所在的字段：依次查找 env_key、保存时记录的旧 env_key 与 OPENAI_API_KEY
+pub fn stored_key_field<'a>(fields: &'a serde_json::Map<String, serde_json::Value>, env_key: &'a str) -> Option<&'a str> {
+  let previous = fields.get(STORED_ENV_KEY_FIELD).and_then(|v| v.as_str());
+  [Some(env_key), previous, Some(DEFAULT_ENV_KEY)]
+    .into_iter()
+    .flatten()
+    .find(|k| fields.get(*k).and_then(|v| v.as_str()).is_some_and(|s| !s.is_empty()))
+}
+
+/// 节点保存的当前密钥（启用加密时为密文）；兼容修改 env_key 之前保存在旧字段下的凭据
+pub fn stored_key<'a>(fields: &'a serde_json::Map<String, serde_json::Value>, env_key: &str) -> Option<&'a str> {
+  stored_key_field(fields, env_key).and_then(|k| fields.get(k)).and_then(|v| v.as_str())
+}
+
+/// 以 env_key 为字段名写入节点当前密钥，移除旧字段（OPENAI_API_KEY 或之前的 env_key）并记录新的字段名
+pub fn put_key(fields: &mut serde_json::Map<String, serde_json::Value>, env_key: &str, key: String) {
+  remove_key(fields, env_key);
+  fields.insert(env_key.to_string(), serde_json::Value::String(key));
+  fields.insert(STORED_ENV_KEY_FIELD.to_string(), serde_json::Value::String(env_key.to_string()));
+}
+
+pub fn remove_key(fields: &mut serde_json::Map<String, serde_json::Value>, env_key: &str) {
+  if let Some(serde_json::Value::String(previous)) = fields.remove(STORED_ENV_KEY_FIELD) {
+    fields.remove(&previous);
+  }
+  fields.remove(env_key);
+  fields.remove(DEFAULT_ENV_KEY);
+}
+
+pub fn get_credentials() -> AppResult<serde_json::Value> {
+  let path = credentials_path()?;
+  if !path.exists() {
+    return Ok(serde_json::json!({}));
+  }
+  match fs::read_to_string(&path) {
+    Ok(content) => {
+      let trimmed = content.trim();
+      if trimmed.is_empty() {
+        // 文件存在但为空：返回 {}，避免前端功能受阻
+        log::warn!("{} is empty, treating as no credentials", path.display());
+        return Ok(serde_json::json!({}));
+      }
+      match serde_json::from_str::<serde_json::Value>(trimmed) {
+        Ok(v) => Ok(v),
+        Err(e) => {
+          // 解析失败也容错返回 {}，并打印警告日志，防止列表页崩溃
+          log::warn!("parse {} failed ({}), treating as no credentials", path.display(), e);
+          Ok(serde_json::json!({}))
+        }
+      }
+    }
+    Err(e) => {
+      log::warn!("read {} failed ({}), treating as no credentials", path.display(), e);
+      Ok(serde_json::json!({}))
+    }
+  }
+}
+
+/// 读取凭据文件，返回凭据的 Map 结构
+/// 文件路径: ~/.codex/codex-mate/credentials.json
+pub fn read_credentials_value() -> AppResult<serde_json::Map<String, serde_json::Value>> {
+  let path = credentials_path()?;
+  if !path.exists() {
+    return Ok(serde_json::Map::new());
+  }
+
+  let content = fs::read_to_string(&path).map_err(|e| AppError::io("read", &path, e))?;
+
+  let trimmed = content.trim();
+  if trimmed.is_empty() {
+    // 文件为空时，容错返回空 Map，避免后续更新失败
+    log::warn!("{} is empty, starting from an empty map", path.display());
+    return Ok(serde_json::Map::new());
+  }
+
+  match serde_json::from_str::<serde_json::Value>(trimmed) {
+    Ok(v) => Ok(v.as_object().cloned().unwrap_or_default()),
+    Err(e) => {
+      // 当文件损坏/格式不正确时，打印警告并返回空 Map，让调用方可以继续写入修复
+      log::warn!("parse {} failed ({}), starting from an empty map", path.display(), e);
+      Ok(serde_json::Map::new())
+    }
+  }
+}
+
+/// 写入凭据文件，使用原子写入保证数据安全；启用加密时明文值会先被加密
+/// 文件路径: ~/.codex/codex-mate/credentials.json
+pub fn write_credentials_value(map: &serde_json::Map<String, serde_json::Value>) -> AppResult<()> {
+  let path = credentials_path()?;
+  let mut sealed = map.clone();
+  seal_credentials(&mut sealed)?;
+  let s = serde_json::to_string_pretty(&serde_json::Value::Object(sealed))?;
+  atomic_write(&path, &s)?;
+  log::debug!("wrote {} credential entries to {}", map.len(), path.display());
+  Ok(())
+}
+
+/// 单个节点的凭据概况，不包含密钥本身
+#[derive(Serialize, Clone, Debug)]
+pub struct ProviderKeyInfo {
+  pub name: String,
+  /// 凭据字段名（节点的 env_key）
+  pub env_key: String,
+  /// 是否在 config.toml 的 model_providers 中配置
+  pub configured: bool,
+  pub has_key: bool,
+  pub encrypted: bool,
+  /// 密钥指纹：前缀 + SHA-256 前 8 位，加密且未解锁时为空
+  pub fingerprint: Option<String>,
+}
+
+/// credentials.json 诊断报告
+#[derive(Serialize, Clone, Debug)]
+pub struct CredentialDiagnostics {
+  pub path: String,
+  pub exists: bool,
+  pub size: Option<u64>,
+  pub parse_ok: bool,
+  pub parse_error: Option<String>,
+  pub vault_enabled: bool,
+  pub providers: Vec<ProviderKeyInfo>,
+  /// 结构问题，如字段类型错误、孤立凭据等
+  pub problems: Vec<String>,
+}
+
+/// 密钥指纹，用于在不暴露密钥的情况下比对两处配置是否一致
+pub fn key_fingerprint(key: &str) -> String {
+  let prefix: String = key.chars().take((key.chars().count() / 4).min(6)).collect();
+  let digest = Sha256::digest(key.as_bytes());
+  let hash: String = digest.iter().take(4).map(|b| format!("{:02x}", b)).collect();
+  format!("{}…#{}", prefix, hash)
+}
+
+/// 诊断 credentials.json：路径、大小、解析状态、各节点密钥是否存在及指纹、结构问题，从不返回密钥明文
+pub fn credentials_diagnostics() -> AppResult<CredentialDiagnostics> {
+  let path = credentials_path()?;
+  let vault_enabled = vault_enabled()?;
+  let mut report = CredentialDiagnostics {
+    path: path.display().to_string(),
+    exists: path.exists(),
+    size: fs::metadata(&path).ok().map(|m| m.len()),
+    parse_ok: false,
+    parse_error: None,
+    vault_enabled,
+    providers: Vec::new(),
+    problems: Vec::new(),
+  };
+
+  let cfg = get_full_config().unwrap_or(serde_json::Value::Null);
+  let configured: BTreeSet<String> = cfg
+    .get("model_providers")
+    .and_then(|v| v.as_object())
+    .map(|m| m.keys().filter(|k| k.as_str() != CHATGPT_NODE && k.as_str() != RELAY_NODE).cloned().collect())
+    .unwrap_or_default();
+
+  let creds = if !report.exists {
+    report.parse_ok = true;
+    serde_json::Map::new()
+  } else {
+    let content = fs::read_to_string(&path).map_err(|e| AppError::io("read", &path, e))?;
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+      report.parse_ok = true;
+      report.problems.push("file is empty".into());
+      serde_json::Map::new()
+    } else {
+      match serde_json::from_str::<serde_json::Value>(trimmed) {
+        Ok(serde_json::Value::Object(map)) => {
+          report.parse_ok = true;
+          map
+        }
+        Ok(_) => {
+          report.parse_ok = true;
+          report.problems.push("root is not a JSON object".into());
+          serde_json::Map::new()
+        }
+        Err(e) => {
+          report.parse_error = Some(e.to_string());
+          serde_json::Map::new()
+        }
+      }
+    }
+  };
+
+  for (name, entry) in &creds {
+    let env_key = env_key_of(&cfg, name);
+    let mut info = ProviderKeyInfo {
+      name: name.clone(),
+      env_key: env_key.clone(),
+      configured: configured.contains(name),
+      has_key: false,
+      encrypted: false,
+      fingerprint: None,
+    };
+    if !info.configured {
+      report.problems.push(format!("'{}' has a credential but no model_providers entry", name));
+    }
+    let Some(fields) = entry.as_object() else {
+      report.problems.push(format!("'{}' is not an object", name));
+      report.providers.push(info);
+      continue;
+    };
+    let field = stored_key_field(fields, &env_key).unwrap_or(env_key.as_str());
+    if field != env_key {
+      report.problems.push(format!("'{}' is stored under {} but its env_key is {}; it moves on next save", name, field, env_key));
+    }
+    match fields.get(field) {
+      None => report.problems.push(format!("'{}' has no {}", name, env_key)),
+      Some(serde_json::Value::String(key)) if key.is_empty() => report.problems.push(format!("'{}' has an empty {}", name, field)),
+      Some(serde_json::Value::String(key)) => {
+        info.has_key = true;
+        info.encrypted = is_encrypted(key);
+        if info.encrypted && !vault_enabled {
+          report.problems.push(format!("'{}' is encrypted but vault.json is missing", name));
+        } else if !info.encrypted && vault_enabled {
+          report.problems.push(format!("'{}' is stored in plaintext and will be encrypted on next write", name));
+        }
+        // 加密值仅在已解锁时计算指纹，解密结果不离开本函数
+        info.fingerprint = match info.encrypted {
+          true => reveal(key).ok().map(|k| key_fingerprint(&k)),
+          false => Some(key_fingerprint(key)),
+        };
+      }
+      Some(_) => report.problems.push(format!("'{}'.{} is not a string", name, field)),
+    }
+    report.providers.push(info);
+  }
+
+  for name in configured.iter().filter(|n| !creds.contains_key(*n)) {
+    report.providers.push(ProviderKeyInfo {
+      name: name.clone(),
+      env_key: env_key_of(&cfg, name),
+      configured: true,
+      has_key: false,
+      encrypted: false,
+      fingerprint: None,
+    });
+  }
+  report.providers.sort_by(|a, b| a.name.cmp(&b.name));
+  Ok(report)
+}
+
+/// 显式取出某个节点的 (env_key, 密钥明文)；每次调用都会写入审计日志
+pub fn credential_env(name: String) -> AppResult<(String, String)> {
+  let env_key = credential_field(&name)?;
+  let map = read_credentials_value()?;
+  let key = map
+    .get(&name)
+    .and_then(|v| v.as_object())
+    .and_then(|f| stored_key(f, &env_key))
+    .ok_or_else(|| AppError::CredentialMissing { provider: name.clone() })?;
+  let plain = reveal(key)?;
+  log::warn!(target: "audit", "revealed credential for '{}' ({})", name, key_fingerprint(&plain));
+  Ok((env_key, plain))
+}
+
+/// 显式查看某个节点的密钥明文
+pub fn reveal_credential(name: String) -> AppResult<String> {
+  credential_env(name).map(|(_, key)| key)
+}
@@ -0,0 +1,276 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use toml::Value as TomlValue;
+
+use crate::config::{display_key_path, parse_key_path, parse_literal, read_config_value, toml_to_json};
+use crate::error::{AppError, AppResult};
+use crate::nodes::ProviderConfig;
+
+/// 不参与逐项解析的根级表：节点、profile、项目信任与 MCP 服务各有专门的页面
+const SECTION_KEYS: [&str; 5] = ["model_providers", "profiles", "projects", "mcp_servers", "profile"];
+
+/// 未配置时 Codex 使用的默认值；None 表示默认值随 Codex 版本变化（如默认模型）
+const CODEX_DEFAULTS: [(&str, Option<&str>); 11] = [
+  ("model", None),
+  ("model_provider", Some("\"openai\"")),
+  ("approval_policy", Some("\"on-request\"")),
+  ("sandbox_mode", Some("\"read-only\"")),
+  ("model_reasoning_effort", None),
+  ("model_reasoning_summary", Some("\"auto\"")),
+  ("model_verbosity", None),
+  ("file_opener", Some("\"vscode\"")),
+  ("hide_agent_reasoning", Some("false")),
+  ("show_raw_agent_reasoning", Some("false")),
+  ("history.persistence", Some("\"save-all\"")),
+];
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+  /// config.toml 根级
+  Root,
+  /// 所选 profile
+  Profile,
+  /// -c key=value 覆盖（或显式指定的 profile）
+  Override,
+  /// Codex 内置默认值
+  Default,
+}
+
+impl ValueSource {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ValueSource::Root => "root",
+      ValueSource::Profile => "profile",
+      ValueSource::Override => "override",
+      ValueSource::Default => "default",
+    }
+  }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ResolvedValue {
+  /// 点分键路径，如 `model`、`history.persistence`
+  pub key: String,
+  /// 取值；为 null 表示使用随版本变化的 Codex 默认值
+  pub value: serde_json::Value,
+  pub source: ValueSource,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EffectiveConfig {
+  /// 生效的 profile，未使用 profile 时为 None
+  pub profile: Option<String>,
+  pub profile_source: Option<ValueSource>,
+  /// 按键名排序
+  pub values: Vec<ResolvedValue>,
+  /// 生效节点在 [model_providers] 中的配置；内置提供方（openai / oss）未声明时为 None
+  pub provider: Option<ProviderConfig>,
+}
+
+/// 解析 `key=value` 形式的覆盖项
+fn parse_override(raw: &str) -> AppResult<(Vec<String>, TomlValue)> {
+  let (key, value) = raw
+    .split_once('=')
+    .ok_or_else(|| AppError::invalid(format!("override '{}' must be key=value", raw)))?;
+  let path = parse_key_path(key.trim())?;
+  Ok((path, parse_literal(value.trim())))
+}
+
+/// 按路径写入值，中间不是表的节点会被替换为表（与 Codex 的 -c 行为一致）
+fn set_path(root: &mut TomlValue, path: &[String], value: TomlValue) {
+  let Some((last, parents)) = path.split_last() else { return };
+  let mut cur = root;
+  for k in parents {
+    let TomlValue::Table(tbl) = cur else { return };
+    let next = tbl.entry(k.clone()).or_insert_with(|| TomlValue::Table(Default::default()));
+    if !next.is_table() {
+      *next = TomlValue::Table(Default::default());
+    }
+    cur = next;
+  }
+  if let TomlValue::Table(tbl) = cur {
+    tbl.insert(last.clone(), value);
+  }
+}
+
+/// 将表展开为叶子键（数组视为叶子），键路径相对于 tbl
+fn flatten(tbl: &toml::Table, prefix: &mut Vec<String>, out: &mut Vec<(Vec<String>, TomlValue)>) {
+  for (k, v) in tbl {
+    prefix.push(k.clone());
+    match v {
+      TomlValue::Table(sub) => flatten(sub, prefix, out),
+      _ => out.push((prefix.clone(), v.clone())),
+    }
+    prefix.pop();
+  }
+}
+
+/// 计算 Codex 实际生效的配置，并给出每一项的来源
+///   - profile: 等同 `codex --profile`，未指定时使用根级 `profile` 键
+///   - overrides: 等同 `codex -c key=value`，值按 TOML 字面量解析
+///
+/// 与 Codex 一致，-c 写入的是根级键，仍会被 profile 中的同名键覆盖；需覆盖 profile 中的值时使用 `profiles.<name>.<key>`
+pub fn resolve_config(profile: Option<String>, overrides: Vec<String>) -> AppResult<EffectiveConfig> {
+  resolve(read_config_value()?, profile, overrides)
+}
+
+fn resolve(mut cfg: TomlValue, profile: Option<String>, overrides: Vec<String>) -> AppResult<EffectiveConfig> {
+  let overrides = overrides.iter().map(|o| parse_override(o)).collect::<AppResult<Vec<_>>>()?;
+  for (path, value) in &overrides {
+    set_path(&mut cfg, path, value.clone());
+  }
+  let overridden = |path: &[String]| overrides.iter().any(|(o, _)| path.starts_with(o) || o.starts_with(path));
+
+  let (profile, profile_source) = match profile.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+    Some(p) => (Some(p), Some(ValueSource::Override)),
+    None => match cfg.get("profile").and_then(|v| v.as_str()) {
+      Some(p) => {
+        let source = if overridden(&["profile".to_string()]) { ValueSource::Override } else { ValueSource::Root };
+        (Some(p.to_string()), Some(source))
+      }
+      None => (None, None),
+    },
+  };
+
+  let empty = toml::Table::new();
+  let root = cfg.as_table().unwrap_or(&empty);
+  let profile_tbl = match &profile {
+    Some(p) => Some(
+      root
+        .get("profiles")
+        .and_then(|v| v.get(p))
+        .and_then(|v| v.as_table())
+        .ok_or_else(|| AppError::not_found("profile", p))?,
+    ),
+    None => None,
+  };
+
+  let mut resolved: BTreeMap<String, ResolvedValue> = BTreeMap::new();
+  // 后面的层覆盖前面的层；base 为该层在配置树中的路径，用于判断是否被 -c 覆盖
+  let profile_base = vec!["profiles".to_string(), profile.clone().unwrap_or_default()];
+  let layers = [(Some(root), Vec::new(), ValueSource::Root), (profile_tbl, profile_base, ValueSource::Profile)];
+  for (tbl, base, source) in layers {
+    let Some(tbl) = tbl else { continue };
+    let mut leaves = Vec::new();
+    flatten(tbl, &mut Vec::new(), &mut leaves);
+    for (path, value) in leaves {
+      if SECTION_KEYS.contains(&path[0].as_str()) {
+        continue;
+      }
+      let full: Vec<String> = base.iter().chain(&path).cloned().collect();
+      let key = display_key_path(&path);
+      let source = if overridden(&full) { ValueSource::Override } else { source };
+      resolved.insert(key.clone(), ResolvedValue { key, value: toml_to_json(value), source });
+    }
+  }
+  for (key, default) in CODEX_DEFAULTS {
+    if resolved.contains_key(key) {
+      continue;
+    }
+    let value = match default {
+      Some(literal) => toml_to_json(parse_literal(literal)),
+      None => serde_json::Value::Null,
+    };
+    resolved.insert(key.to_string(), ResolvedValue { key: key.to_string(), value, source: ValueSource::Default });
+  }
+
+  let cfg = toml_to_json(cfg);
+  let provider = resolved
+    .get("model_provider")
+    .and_then(|v| v.value.as_str())
+    .filter(|name| cfg.get("model_providers").and_then(|v| v.get(name)).is_some())
+    .map(|name| ProviderConfig::from_config(&cfg, name));
+
+  Ok(EffectiveConfig { profile, profile_source, values: resolved.into_values().collect(), provider })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CONFIG: &str = r#"
+model = "gpt-5"
+model_provider = "packy"
+profile = "fast"
+created = 2025-01-01
+approval_policy = "never"
+
+[history]
+persistence = "none"
+
+[model_providers.packy]
+name = "packy"
+base_url = "https://api.example.com/v1"
+
+[profiles.fast]
+model = "gpt-5-mini"
+model_reasoning_effort = "low"
+
+[profiles.deep]
+model_reasoning_effort = "high"
+"#;
+
+  fn resolve_str(profile: Option<&str>, overrides: &[&str]) -> EffectiveConfig {
+    let cfg = toml::from_str(CONFIG).unwrap();
+    resolve(cfg, profile.map(str::to_string), overrides.iter().map(|o| o.to_string()).collect()).unwrap()
+  }
+
+  fn get<'a>(eff: &'a EffectiveConfig, key: &str) -> &'a ResolvedValue {
+    eff.values.iter().find(|v| v.key == key).unwrap_or_else(|| panic!("{} not resolved", key))
+  }
+
+  #[test]
+  fn profile_overrides_root() {
+    let eff = resolve_str(None, &[]);
+    assert_eq!(eff.profile.as_deref(), Some("fast"));
+    assert_eq!(eff.profile_source, Some(ValueSource::Root));
+    assert_eq!(get(&eff, "model").value, "gpt-5-mini");
+    assert_eq!(get(&eff, "model").source, ValueSource::Profile);
+    assert_eq!(get(&eff, "approval_policy").source, ValueSource::Root);
+    assert_eq!(get(&eff, "history.persistence").value, "none");
+    assert_eq!(eff.provider.as_ref().and_then(|p| p.base_url.as_deref()), Some("https://api.example.com/v1"));
+  }
+
+  #[test]
+  fn explicit_profile_and_overrides() {
+    let eff = resolve_str(Some("deep"), &["model=\"o3\"", "sandbox_mode=workspace-write", "profiles.deep.model_reasoning_effort=\"minimal\""]);
+    assert_eq!(eff.profile_source, Some(ValueSource::Override));
+    assert_eq!(get(&eff, "model").value, "o3");
+    assert_eq!(get(&eff, "model").source, ValueSource::Override);
+    assert_eq!(get(&eff, "sandbox_mode").value, "workspace-write");
+    assert_eq!(get(&eff, "model_reasoning_effort").value, "minimal");
+    assert_eq!(get(&eff, "model_reasoning_effort").source, ValueSource::Override);
+  }
+
+  #[test]
+  fn root_override_is_shadowed_by_profile() {
+    let eff = resolve_str(None, &["model=o3"]);
+    assert_eq!(get(&eff, "model").value, "gpt-5-mini");
+    assert_eq!(get(&eff, "model").source, ValueSource::Profile);
+  }
+
+  #[test]
+  fn defaults_fill_missing_keys() {
+    let eff = resolve_str(None, &[]);
+    assert_eq!(get(&eff, "sandbox_mode").value, "read-only");
+    assert_eq!(get(&eff, "sandbox_mode").source, ValueSource::Default);
+    assert_eq!(get(&eff, "hide_agent_reasoning").value, false);
+    assert!(get(&eff, "model_verbosity").value.is_null());
+    assert!(eff.values.iter().all(|v| !v.key.starts_with("profiles") && !v.key.starts_with("model_providers")));
+  }
+
+  #[test]
+  fn datetimes_resolve_to_strings() {
+    let eff = resolve_str(None, &["updated=1979-05-27T07:32:00Z"]);
+    assert_eq!(get(&eff, "created").value, "2025-01-01");
+    assert_eq!(get(&eff, "updated").value, "1979-05-27T07:32:00Z");
+  }
+
+  #[test]
+  fn rejects_bad_overrides_and_unknown_profiles() {
+    let cfg: TomlValue = toml::from_str(CONFIG).unwrap();
+    assert!(resolve(cfg.clone(), None, vec!["model".into()]).is_err());
+    assert!(matches!(resolve(cfg, Some("missing".into()), Vec::new()), Err(AppError::NotFound { .. })));
+  }
+}
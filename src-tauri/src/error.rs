@@ -0,0 +1,165 @@
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::path::Path;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+/// 统一错误类型
+/// 序列化为 `{ "code": "TomlParse", "message": "...", ...详细字段 }`，
+/// code 为稳定的机器可读错误码，供前端本地化提示和定位问题
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+  #[error("{path} not found")]
+  ConfigNotFound { path: String },
+
+  #[error("TOML parse error{}: {message}", location(*.line, *.col))]
+  TomlParse { message: String, line: Option<usize>, col: Option<usize> },
+
+  #[error("parse {path} failed: {message}")]
+  JsonParse { path: String, message: String },
+
+  #[error("credential not found for provider '{provider}'")]
+  CredentialMissing { provider: String },
+
+  #[error("cannot delete active provider '{provider}' without force")]
+  ActiveProviderDelete { provider: String },
+
+  #[error("'{name}' is a reserved node name (ChatGPT account or local relay)")]
+  ReservedName { name: String },
+
+  #[error("ChatGPT login not found in auth.json, run `codex login` first")]
+  ChatgptLoginMissing,
+
+  #[error("credential vault is locked, unlock it first")]
+  VaultLocked,
+
+  #[error("incorrect vault passphrase")]
+  InvalidPassphrase,
+
+  #[error("provider '{provider}' request failed{}: {message}", status.map(|s| format!(" with HTTP {}", s)).unwrap_or_default())]
+  Upstream { provider: String, status: Option<u16>, message: String },
+
+  #[error("{kind} '{name}' not found")]
+  NotFound { kind: &'static str, name: String },
+
+  #[error("{message}")]
+  InvalidInput { message: String },
+
+  #[error("{message}")]
+  InvalidConfig { message: String },
+
+  #[error("cannot determine home directory, set CODEX_HOME explicitly")]
+  HomeDirUnavailable,
+
+  #[error("{op} {path} failed: {message}")]
+  Io { op: &'static str, path: String, message: String },
+
+  #[error("{message}")]
+  Internal { message: String },
+}
+
+fn location(line: Option<usize>, col: Option<usize>) -> String {
+  match (line, col) {
+    (Some(l), Some(c)) => format!(" at line {}, column {}", l, c),
+    (Some(l), None) => format!(" at line {}", l),
+    _ => String::new(),
+  }
+}
+
+/// 将字节偏移换算为 1 起始的行号和列号（列按字符计）
+pub(crate) fn line_col(source: &str, offset: usize) -> (usize, usize) {
+  let offset = offset.min(source.len());
+  let before = source.get(..offset).unwrap_or(source);
+  let line = before.matches('\n').count() + 1;
+  let col = before.rsplit('\n').next().map(|l| l.chars().count()).unwrap_or(0) + 1;
+  (line, col)
+}
+
+impl AppError {
+  /// 稳定的错误码，前端据此做本地化与针对性处理
+  pub fn code(&self) -> &'static str {
+    match self {
+      AppError::ConfigNotFound { .. } => "ConfigNotFound",
+      AppError::TomlParse { .. } => "TomlParse",
+      AppError::JsonParse { .. } => "JsonParse",
+      AppError::CredentialMissing { .. } => "CredentialMissing",
+      AppError::ActiveProviderDelete { .. } => "ActiveProviderDelete",
+      AppError::ReservedName { .. } => "ReservedName",
+      AppError::ChatgptLoginMissing => "ChatgptLoginMissing",
+      AppError::VaultLocked => "VaultLocked",
+      AppError::InvalidPassphrase => "InvalidPassphrase",
+      AppError::Upstream { .. } => "UpstreamError",
+      AppError::NotFound { .. } => "NotFound",
+      AppError::InvalidInput { .. } => "InvalidInput",
+      AppError::InvalidConfig { .. } => "InvalidConfig",
+      AppError::HomeDirUnavailable => "HomeDirUnavailable",
+      AppError::Io { .. } => "IoError",
+      AppError::Internal { .. } => "Internal",
+    }
+  }
+
+  pub fn io(op: &'static str, path: &Path, err: std::io::Error) -> Self {
+    AppError::Io { op, path: path.display().to_string(), message: err.to_string() }
+  }
+
+  pub fn json(path: &Path, err: impl std::fmt::Display) -> Self {
+    AppError::JsonParse { path: path.display().to_string(), message: err.to_string() }
+  }
+
+  /// 从 toml / toml_edit 的解析错误构造，附带出错位置
+  pub fn toml(message: &str, span: Option<std::ops::Range<usize>>, source: &str) -> Self {
+    let (line, col) = match span.map(|s| line_col(source, s.start)) {
+      Some((l, c)) => (Some(l), Some(c)),
+      None => (None, None),
+    };
+    AppError::TomlParse { message: message.trim().to_string(), line, col }
+  }
+
+  pub fn invalid(message: impl Into<String>) -> Self {
+    AppError::InvalidInput { message: message.into() }
+  }
+
+  pub fn config(message: impl Into<String>) -> Self {
+    AppError::InvalidConfig { message: message.into() }
+  }
+
+  pub fn not_found(kind: &'static str, name: impl Into<String>) -> Self {
+    AppError::NotFound { kind, name: name.into() }
+  }
+}
+
+impl From<serde_json::Error> for AppError {
+  fn from(e: serde_json::Error) -> Self {
+    AppError::Internal { message: e.to_string() }
+  }
+}
+
+impl Serialize for AppError {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(None)?;
+    map.serialize_entry("code", self.code())?;
+    map.serialize_entry("message", &self.to_string())?;
+    match self {
+      AppError::ConfigNotFound { path } => map.serialize_entry("path", path)?,
+      AppError::TomlParse { line, col, .. } => {
+        map.serialize_entry("line", line)?;
+        map.serialize_entry("col", col)?;
+      }
+      AppError::JsonParse { path, .. } => map.serialize_entry("path", path)?,
+      AppError::CredentialMissing { provider } | AppError::ActiveProviderDelete { provider } => {
+        map.serialize_entry("provider", provider)?
+      }
+      AppError::ReservedName { name } => map.serialize_entry("name", name)?,
+      AppError::Upstream { provider, status, .. } => {
+        map.serialize_entry("provider", provider)?;
+        map.serialize_entry("status", status)?;
+      }
+      AppError::NotFound { kind, name } => {
+        map.serialize_entry("kind", kind)?;
+        map.serialize_entry("name", name)?;
+      }
+      AppError::Io { path, .. } => map.serialize_entry("path", path)?,
+      _ => {}
+    }
+    map.end()
+  }
+}
@@ -0,0 +1,227 @@
+use futures_util::future::join_all;
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::error::{AppError, AppResult};
+use crate::http::{client, provider_names, resolve_endpoint, ProviderEndpoint};
+
+/// 健康检查失败的原因分类
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthIssue {
+  /// 节点配置不完整（如缺少 base_url）
+  Config,
+  /// 凭据已加密且未解锁
+  VaultLocked,
+  /// 401 / 403：密钥无效或无权限
+  Unauthorized,
+  /// 其他非 2xx 响应
+  HttpStatus,
+  Timeout,
+  /// 证书校验、握手等 TLS 错误
+  Tls,
+  /// DNS 解析失败、连接被拒绝等
+  Connect,
+  Network,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProviderHealth {
+  pub name: String,
+  pub url: Option<String>,
+  pub ok: bool,
+  pub status: Option<u16>,
+  pub latency_ms: Option<u64>,
+  pub has_credential: bool,
+  pub issue: Option<HealthIssue>,
+  pub message: Option<String>,
+}
+
+impl ProviderHealth {
+  fn failed(name: &str, issue: HealthIssue, message: impl Into<String>) -> Self {
+    ProviderHealth {
+      name: name.to_string(),
+      url: None,
+      ok: false,
+      status: None,
+      latency_ms: None,
+      has_credential: false,
+      issue: Some(issue),
+      message: Some(message.into()),
+    }
+  }
+}
+
+/// 根据错误链区分 TLS / 超时 / 连接错误
+/// HTTPS 请求在 TCP 连接建立之后的连接阶段错误均视为 TLS 错误（握手失败、证书无效、对端非 TLS 等）
+fn classify(err: &reqwest::Error, https: bool) -> HealthIssue {
+  let mut chain = String::new();
+  let mut source: Option<&dyn std::error::Error> = Some(err);
+  while let Some(e) = source {
+    chain.push_str(&e.to_string().to_lowercase());
+    chain.push(' ');
+    source = e.source();
+  }
+  if err.is_timeout() {
+    HealthIssue::Timeout
+  } else if ["certificate", "tls", "ssl", "handshake"].iter().any(|k| chain.contains(k)) {
+    HealthIssue::Tls
+  } else if err.is_connect() {
+    if https && !chain.contains("tcp connect") && !chain.contains("dns") {
+      HealthIssue::Tls
+    } else {
+      HealthIssue::Connect
+    }
+  } else {
+    HealthIssue::Network
+  }
+}
+
+pub fn error_chain(err: &reqwest::Error) -> String {
+  let mut parts = vec![err.to_string()];
+  let mut source = std::error::Error::source(err);
+  while let Some(e) = source {
+    parts.push(e.to_string());
+    source = e.source();
+  }
+  parts.join(": ")
+}
+
+/// 从错误响应体中提取可读信息：优先 OpenAI 风格的 error.message
+pub fn response_message(body: &str) -> String {
+  let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+  let msg = parsed
+    .as_ref()
+    .and_then(|v| v.pointer("/error/message").or_else(|| v.get("message")).or_else(|| v.get("error")))
+    .and_then(|v| v.as_str())
+    .map(|s| s.to_string())
+    .unwrap_or_else(|| body.trim().to_string());
+  msg.chars().take(300).collect()
+}
+
+async fn check_one(client: &reqwest::Client, name: &str) -> ProviderHealth {
+  match resolve_endpoint(name) {
+    Ok(endpoint) => check_endpoint(client, &endpoint).await,
+    Err(e @ AppError::VaultLocked) => ProviderHealth::failed(name, HealthIssue::VaultLocked, e.to_string()),
+    Err(e) => ProviderHealth::failed(name, HealthIssue::Config, e.to_string()),
+  }
+}
+
+/// 对已解析的节点执行 GET {base_url}/models
+async fn check_endpoint(client: &reqwest::Client, endpoint: &ProviderEndpoint) -> ProviderHealth {
+  let mut health = ProviderHealth {
+    name: endpoint.name.clone(),
+    url: Some(endpoint.url("models")),
+    ok: false,
+    status: None,
+    latency_ms: None,
+    has_credential: endpoint.api_key.is_some(),
+    issue: None,
+    message: None,
+  };
+
+  let started = Instant::now();
+  match endpoint.request(client, reqwest::Method::GET, "models").send().await {
+    Ok(resp) => {
+      let status = resp.status();
+      health.latency_ms = Some(started.elapsed().as_millis() as u64);
+      health.status = Some(status.as_u16());
+      if status.is_success() {
+        health.ok = true;
+      } else {
+        health.issue = Some(match status.as_u16() {
+          401 | 403 => HealthIssue::Unauthorized,
+          _ => HealthIssue::HttpStatus,
+        });
+        let body = resp.text().await.unwrap_or_default();
+        health.message = Some(response_message(&body)).filter(|m| !m.is_empty());
+      }
+    }
+    Err(e) => {
+      health.issue = Some(classify(&e, endpoint.base_url.starts_with("https://")));
+      health.message = Some(error_chain(&e));
+    }
+  }
+  health
+}
+
+/// 对指定节点（默认全部 model_providers）并发执行 GET {base_url}/models，返回状态码、延迟与失败原因
+pub async fn check_providers(names: Option<Vec<String>>, timeout_ms: Option<u64>) -> AppResult<Vec<ProviderHealth>> {
+  let names = match names.filter(|n| !n.is_empty()) {
+    Some(n) => n,
+    None => provider_names()?,
+  };
+  let client = client(timeout_ms)?;
+  let results = join_all(names.iter().map(|n| check_one(&client, n))).await;
+
+  let healthy = results.iter().filter(|r| r.ok).count();
+  log::info!("health check: {}/{} providers healthy", healthy, results.len());
+  for r in results.iter().filter(|r| !r.ok) {
+    log::warn!("health check '{}' failed: {:?} {}", r.name, r.issue, r.message.as_deref().unwrap_or_default());
+  }
+  Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock;
+
+  #[tokio::test]
+  async fn healthy_provider_reports_status_and_latency() {
+    let server = mock::serve().await;
+    let health = check_endpoint(&client(None).unwrap(), &mock::endpoint(&server, "ok", Some(mock::KEY))).await;
+    assert!(health.ok);
+    assert_eq!(health.status, Some(200));
+    assert!(health.latency_ms.is_some());
+    assert!(health.has_credential);
+    assert_eq!(health.issue, None);
+    assert_eq!(health.url, Some(format!("{}/ok/models", server)));
+  }
+
+  #[tokio::test]
+  async fn wrong_key_is_unauthorized() {
+    let server = mock::serve().await;
+    let health = check_endpoint(&client(None).unwrap(), &mock::endpoint(&server, "ok", Some("sk-wrong"))).await;
+    assert!(!health.ok);
+    assert_eq!(health.status, Some(401));
+    assert_eq!(health.issue, Some(HealthIssue::Unauthorized));
+    assert_eq!(health.message.as_deref(), Some("Incorrect API key provided"));
+  }
+
+  #[tokio::test]
+  async fn missing_key_is_unauthorized() {
+    let server = mock::serve().await;
+    let health = check_endpoint(&client(None).unwrap(), &mock::endpoint(&server, "ok", None)).await;
+    assert!(!health.has_credential);
+    assert_eq!(health.issue, Some(HealthIssue::Unauthorized));
+  }
+
+  #[tokio::test]
+  async fn slow_provider_times_out() {
+    let server = mock::serve().await;
+    let timeout_ms = 200;
+    assert!(u128::from(timeout_ms) < mock::SLOW.as_millis());
+    let health = check_endpoint(&client(Some(timeout_ms)).unwrap(), &mock::endpoint(&server, "slow", Some(mock::KEY))).await;
+    assert!(!health.ok);
+    assert_eq!(health.status, None);
+    assert_eq!(health.issue, Some(HealthIssue::Timeout));
+  }
+
+  #[tokio::test]
+  async fn refused_connection_is_connect_error() {
+    // 绑定后立即释放端口，随后的连接会被拒绝
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+    let health = check_endpoint(&client(None).unwrap(), &mock::endpoint(&format!("http://{}", addr), "ok", Some(mock::KEY))).await;
+    assert_eq!(health.issue, Some(HealthIssue::Connect));
+  }
+
+  #[test]
+  fn response_message_prefers_openai_error() {
+    assert_eq!(response_message(r#"{"error":{"message":"quota exceeded"}}"#), "quota exceeded");
+    assert_eq!(response_message(r#"{"message":"not found"}"#), "not found");
+    assert_eq!(response_message(r#"{"error":"bad gateway"}"#), "bad gateway");
+    assert_eq!(response_message("  plain text\n"), "plain text");
+    assert_eq!(response_message(&"x".repeat(500)).len(), 300);
+  }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{AppError, AppResult};
+use crate::storage::{atomic_write, default_codex_dir, set_codex_dir_override};
+
+/// 默认目录（CODEX_HOME 或 ~/.codex）在列表中使用的名称
+pub const DEFAULT_HOME: &str = "default";
+
+/// 额外 Codex 目录的登记表，与具体的 Codex 目录无关，因此保存在系统配置目录下
+/// 路径: <config_dir>/codex-mate/homes.json
+fn registry_path() -> AppResult<PathBuf> {
+  dirs::config_dir()
+    .map(|d| d.join("codex-mate").join("homes.json"))
+    .ok_or(AppError::HomeDirUnavailable)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HomeEntry {
+  pub name: String,
+  pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct HomeRegistry {
+  homes: Vec<HomeEntry>,
+  /// GUI 当前选中的目录名称，None 表示默认目录
+  active: Option<String>,
+}
+
+fn read_registry() -> AppResult<HomeRegistry> {
+  let path = registry_path()?;
+  if !path.exists() {
+    return Ok(HomeRegistry::default());
+  }
+  let content = fs::read_to_string(&path).map_err(|e| AppError::io("read", &path, e))?;
+  if content.trim().is_empty() {
+    return Ok(HomeRegistry::default());
+  }
+  serde_json::from_str(&content).map_err(|e| AppError::json(&path, e))
+}
+
+fn write_registry(registry: &HomeRegistry) -> AppResult<()> {
+  let s = serde_json::to_string_pretty(registry)?;
+  atomic_write(&registry_path()?, &s)
+}
+
+#[derive(Serialize)]
+pub struct HomeInfo {
+  pub name: String,
+  pub path: String,
+  pub exists: bool,
+  pub is_active: bool,
+}
+
+#[derive(Serialize)]
+pub struct HomeList {
+  pub active: String,
+  pub homes: Vec<HomeInfo>,
+}
+
+/// 列出默认目录与所有登记的 Codex 目录
+pub fn list_codex_homes() -> AppResult<HomeList> {
+  let registry = read_registry()?;
+  let active = registry.active.clone().unwrap_or_else(|| DEFAULT_HOME.to_string());
+  let default_path = default_codex_dir()?.to_string_lossy().to_string();
+  let mut homes = vec![HomeEntry { name: DEFAULT_HOME.to_string(), path: default_path }];
+  homes.extend(registry.homes);
+  let homes = homes
+    .into_iter()
+    .map(|h| HomeInfo {
+      exists: std::path::Path::new(&h.path).is_dir(),
+      is_active: h.name == active,
+      name: h.name,
+      path: h.path,
+    })
+    .collect();
+  Ok(HomeList { active, homes })
+}
+
+/// 登记一个 Codex 目录（同名覆盖）
+pub fn add_codex_home(name: String, path: String) -> AppResult<()> {
+  let name = name.trim().to_string();
+  let path = path.trim().to_string();
+  if name.is_empty() || name == DEFAULT_HOME {
+    return Err(AppError::invalid(format!("invalid home name '{}'", name)));
+  }
+  if path.is_empty() {
+    return Err(AppError::invalid("home path is empty"));
+  }
+  let mut registry = read_registry()?;
+  match registry.homes.iter_mut().find(|h| h.name == name) {
+    Some(existing) => existing.path = path,
+    None => registry.homes.push(HomeEntry { name, path }),
+  }
+  write_registry(&registry)
+}
+
+/// 移除登记的目录（不会删除目录本身）；若为当前选中目录则恢复为默认目录
+pub fn remove_codex_home(name: String) -> AppResult<()> {
+  let mut registry = read_registry()?;
+  let before = registry.homes.len();
+  registry.homes.retain(|h| h.name != name);
+  if registry.homes.len() == before {
+    return Err(AppError::not_found("home", name));
+  }
+  if registry.active.as_deref() == Some(name.as_str()) {
+    registry.active = None;
+    set_codex_dir_override(None);
+  }
+  write_registry(&registry)
+}
+
+/// 解析目录名称或路径：登记过的名称返回其路径，"default" 返回 None，其余按路径处理
+pub fn resolve_codex_home(name_or_path: &str) -> AppResult<Option<PathBuf>> {
+  if name_or_path == DEFAULT_HOME {
+    return Ok(None);
+  }
+  let registry = read_registry()?;
+  if let Some(h) = registry.homes.iter().find(|h| h.name == name_or_path) {
+    return Ok(Some(PathBuf::from(&h.path)));
+  }
+  Ok(Some(PathBuf::from(name_or_path)))
+}
+
+/// 切换 GUI 操作的 Codex 目录，并持久化以便下次启动恢复
+pub fn set_active_codex_home(name: String) -> AppResult<()> {
+  let mut registry = read_registry()?;
+  let path = if name == DEFAULT_HOME {
+    None
+  } else {
+    let entry = registry.homes.iter().find(|h| h.name == name).ok_or_else(|| AppError::not_found("home", name.as_str()))?;
+    Some(PathBuf::from(&entry.path))
+  };
+  registry.active = path.as_ref().map(|_| name.clone());
+  write_registry(&registry)?;
+  set_codex_dir_override(path);
+  log::info!("active codex home set to '{}'", name);
+  Ok(())
+}
+
+/// 启动时恢复上次选中的目录；登记表损坏或目录已被移除时使用默认目录
+pub fn init_active_codex_home() {
+  let registry = match read_registry() {
+    Ok(r) => r,
+    Err(e) => {
+      log::warn!("read codex home registry failed, using default: {}", e);
+      return;
+    }
+  };
+  let path = registry
+    .active
+    .and_then(|active| registry.homes.into_iter().find(|h| h.name == active))
+    .map(|h| PathBuf::from(h.path));
+  set_codex_dir_override(path);
+}
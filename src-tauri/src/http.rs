@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use crate::config::get_full_config;
+use crate::credentials::{env_key_of, read_credentials_value, stored_key};
+use crate::error::{AppError, AppResult};
+use crate::keys::{read_pool, KeyRotation};
+use crate::nodes::{ProviderConfig, CHATGPT_NODE};
+use crate::relay::RELAY_NODE;
+use crate::vault::reveal;
+
+/// 默认请求超时
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// 访问某个节点所需的信息：由 config.toml 的 model_providers 与 credentials.json 合并而来
+#[derive(Clone, Debug)]
+pub struct ProviderEndpoint {
+  pub name: String,
+  pub base_url: String,
+  /// 已解密的 API Key，未配置凭据时为空
+  pub api_key: Option<String>,
+  /// 已解密的密钥池 (label, key)，未使用密钥池时为空
+  pub key_pool: Vec<(String, String)>,
+  pub active_key: Option<String>,
+  pub rotation: KeyRotation,
+  pub query_params: Vec<(String, String)>,
+  pub http_headers: Vec<(String, String)>,
+}
+
+impl ProviderEndpoint {
+  /// 拼接 base_url 与相对路径，如 url("models") -> https://host/v1/models
+  pub fn url(&self, path: &str) -> String {
+    format!("{}/{}", self.base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+  }
+
+  /// 构造带鉴权头、自定义头和查询参数的请求
+  pub fn request(&self, client: &reqwest::Client, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+    let mut req = client.request(method, self.url(path)).query(&self.query_params);
+    for (k, v) in &self.http_headers {
+      req = req.header(k, v);
+    }
+    if let Some(key) = &self.api_key {
+      req = req.bearer_auth(key);
+    }
+    req
+  }
+}
+
+/// 列出 config.toml 中配置的节点名（不含 ChatGPT 账号节点与本地中转节点）
+pub fn provider_names() -> AppResult<Vec<String>> {
+  let cfg = get_full_config()?;
+  Ok(
+    cfg
+      .get("model_providers")
+      .and_then(|v| v.as_object())
+      .map(|m| m.keys().filter(|k| k.as_str() != CHATGPT_NODE && k.as_str() != RELAY_NODE).cloned().collect())
+      .unwrap_or_default(),
+  )
+}
+
+/// 解析节点的访问信息；凭据已加密时需先解锁
+pub fn resolve_endpoint(name: &str) -> AppResult<ProviderEndpoint> {
+  if name == CHATGPT_NODE {
+    return Err(AppError::invalid("the ChatGPT account node has no base_url to call"));
+  }
+  let cfg = get_full_config()?;
+  if cfg.get("model_providers").and_then(|v| v.get(name)).is_none() {
+    return Err(AppError::not_found("provider", name));
+  }
+  let provider = ProviderConfig::from_config(&cfg, name);
+  let base_url = provider
+    .base_url
+    .as_deref()
+    .filter(|s| !s.trim().is_empty())
+    .ok_or_else(|| AppError::config(format!("provider '{}' has no base_url", name)))?
+    .trim()
+    .to_string();
+
+  let creds = read_credentials_value()?;
+  let fields = creds.get(name).and_then(|v| v.as_object()).cloned().unwrap_or_default();
+  // 未保存密钥时与 Codex 一致，使用配置中的 experimental_bearer_token
+  let api_key = match stored_key(&fields, &env_key_of(&cfg, name)) {
+    Some(key) => Some(reveal(key)?),
+    None => provider.experimental_bearer_token.clone().filter(|t| !t.trim().is_empty()),
+  };
+  let pool = read_pool(&fields);
+  let key_pool = pool.keys.iter().map(|k| Ok((k.label.clone(), reveal(&k.key)?))).collect::<AppResult<Vec<_>>>()?;
+
+  Ok(ProviderEndpoint {
+    name: name.to_string(),
+    base_url,
+    api_key,
+    key_pool,
+    active_key: pool.active,
+    rotation: pool.rotation,
+    query_params: provider.query_params.unwrap_or_default().into_iter().collect(),
+    http_headers: provider
+      .http_headers
+      .unwrap_or_default()
+      .into_iter()
+      .chain(
+        // env_http_headers：与 Codex 一致，环境变量存在且非空时才发送
+        provider
+          .env_http_headers
+          .unwrap_or_default()
+          .into_iter()
+          .filter_map(|(header, var)| std::env::var(var).ok().filter(|v| !v.trim().is_empty()).map(|v| (header, v))),
+      )
+      .collect(),
+  })
+}
+
+pub fn client(timeout_ms: Option<u64>) -> AppResult<reqwest::Client> {
+  reqwest::Client::builder()
+    .timeout(Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)))
+    .user_agent(concat!("codex-mate/", env!("CARGO_PKG_VERSION")))
+    .build()
+    .map_err(|e| AppError::Internal { message: format!("build http client failed: {}", e) })
+}
+
+/// 用于转发流式响应的客户端：只限制连接超时，不限制整体耗时
+pub fn streaming_client() -> AppResult<reqwest::Client> {
+  reqwest::Client::builder()
+    .connect_timeout(Duration::from_millis(DEFAULT_TIMEOUT_MS))
+    .user_agent(concat!("codex-mate/", env!("CARGO_PKG_VERSION")))
+    .build()
+    .map_err(|e| AppError::Internal { message: format!("build http client failed: {}", e) })
+}
+
+/// 本地 mock 服务，供健康检查与模型列表的测试使用
+#[cfg(test)]
+pub(crate) mod mock {
+  use axum::http::{header, HeaderMap, StatusCode, Uri};
+  use axum::response::{IntoResponse, Response};
+  use axum::routing::get;
+  use axum::Router;
+  use std::time::Duration;
+
+  use super::ProviderEndpoint;
+
+  /// mock 服务接受的 API Key
+  pub const KEY: &str = "sk-test";
+  /// /slow 路由的响应延迟，测试时的客户端超时应小于该值
+  pub const SLOW: Duration = Duration::from_secs(3);
+
+  fn json(status: StatusCode, body: String) -> Response {
+    (status, [(header::CONTENT_TYPE, "application/json")], body).into_response()
+  }
+
+  fn authorized(headers: &HeaderMap) -> bool {
+    headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) == Some(&format!("Bearer {}", KEY))
+  }
+
+  async fn models(headers: HeaderMap) -> Response {
+    if !authorized(&headers) {
+      return json(StatusCode::UNAUTHORIZED, r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error"}}"#.into());
+    }
+    json(StatusCode::OK, r#"{"object":"list","data":[{"id":"gpt-b"},{"id":"gpt-a"},{"id":"gpt-a"}]}"#.into())
+  }
+
+  async fn slow(headers: HeaderMap) -> Response {
+    tokio::time::sleep(SLOW).await;
+    models(headers).await
+  }
+
+  /// 回显请求的查询串与 x-test 请求头
+  async fn echo(uri: Uri, headers: HeaderMap) -> Response {
+    let header = headers.get("x-test").and_then(|v| v.to_str().ok()).unwrap_or_default();
+    json(StatusCode::OK, serde_json::json!({ "query": uri.query(), "x-test": header, "authorized": authorized(&headers) }).to_string())
+  }
+
+  /// 启动 mock 服务，返回其地址（如 http://127.0.0.1:12345）；路由均以 /<场景>/models 结尾
+  pub async fn serve() -> String {
+    let app = Router::new()
+      .route("/ok/models", get(models))
+      .route("/slow/models", get(slow))
+      .route("/malformed/models", get(|| async { json(StatusCode::OK, "<html>502 Bad Gateway</html>".into()) }))
+      .route("/unexpected/models", get(|| async { json(StatusCode::OK, r#"{"object":"list"}"#.into()) }))
+      .route("/echo/models", get(echo));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind mock server");
+    let addr = listener.local_addr().expect("mock server address");
+    tokio::spawn(async move { axum::serve(listener, app).await.expect("mock server") });
+    format!("http://{}", addr)
+  }
+
+  /// 指向 mock 服务某个场景的节点
+  pub fn endpoint(server: &str, scenario: &str, api_key: Option<&str>) -> ProviderEndpoint {
+    ProviderEndpoint {
+      name: scenario.to_string(),
+      base_url: format!("{}/{}/", server, scenario),
+      api_key: api_key.map(str::to_string),
+      key_pool: Vec::new(),
+      active_key: None,
+      rotation: Default::default(),
+      query_params: Vec::new(),
+      http_headers: Vec::new(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn url_joins_base_and_path() {
+    let ep = mock::endpoint("https://host", "v1", None);
+    assert_eq!(ep.url("models"), "https://host/v1/models");
+    assert_eq!(ep.url("/models"), "https://host/v1/models");
+  }
+
+  #[tokio::test]
+  async fn request_sends_key_query_params_and_headers() {
+    let server = mock::serve().await;
+    let mut ep = mock::endpoint(&server, "echo", Some(mock::KEY));
+    ep.query_params = vec![("api-version".into(), "2025-04-01-preview".into())];
+    ep.http_headers = vec![("x-test".into(), "1".into())];
+
+    let resp = ep.request(&client(None).unwrap(), reqwest::Method::GET, "models").send().await.unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["query"], "api-version=2025-04-01-preview");
+    assert_eq!(body["x-test"], "1");
+    assert_eq!(body["authorized"], true);
+  }
+}
@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{read_auth_value, write_auth_value};
+use crate::config::get_full_config;
+use crate::credentials::{
+  credential_field, key_fingerprint, put_key, read_credentials_value, remove_key, stored_key, write_credentials_value,
+  DEFAULT_ENV_KEY,
+};
+use crate::error::{AppError, AppResult};
+use crate::nodes::CHATGPT_NODE;
+use crate::relay::RELAY_NODE;
+use crate::vault::{ensure_unlocked, is_encrypted, reveal};
+
+/// 节点下的密钥池字段：{ "keys": [{ "label", "key" }], "active": label, "rotation": policy }
+pub const KEY_POOL_FIELD: &str = "key_pool";
+
+/// 密钥轮换策略
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyRotation {
+  /// 只使用手动选择的密钥
+  #[default]
+  #[serde(rename = "manual")]
+  Manual,
+  /// 每次切换到该节点时换用下一个密钥；中转按请求轮流使用
+  #[serde(rename = "round_robin")]
+  RoundRobin,
+  /// 上游返回 429 时改用下一个密钥，并记住成功的密钥
+  #[serde(rename = "on_429")]
+  On429,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredKey {
+  pub label: String,
+  pub key: String,
+}
+
+/// credentials.json 中的密钥池；选中密钥的值同时写入节点的 env_key 字段，未使用密钥池的节点保持原有格式
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct KeyPool {
+  pub keys: Vec<StoredKey>,
+  pub active: Option<String>,
+  pub rotation: KeyRotation,
+}
+
+impl KeyPool {
+  fn position(&self, label: &str) -> Option<usize> {
+    self.keys.iter().position(|k| k.label == label)
+  }
+
+  fn active_index(&self) -> Option<usize> {
+    self.active.as_deref().and_then(|l| self.position(l))
+  }
+}
+
+/// 列表中展示的密钥信息，不含密钥本身
+#[derive(Serialize, Clone, Debug)]
+pub struct KeyInfo {
+  pub label: String,
+  pub active: bool,
+  pub encrypted: bool,
+  /// 加密且未解锁时为空
+  pub fingerprint: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct KeyPoolInfo {
+  pub provider: String,
+  pub rotation: KeyRotation,
+  pub active: Option<String>,
+  pub keys: Vec<KeyInfo>,
+}
+
+pub fn read_pool(fields: &serde_json::Map<String, serde_json::Value>) -> KeyPool {
+  fields.get(KEY_POOL_FIELD).and_then(|v| serde_json::from_value(v.clone()).ok()).unwrap_or_default()
+}
+
+fn write_pool(fields: &mut serde_json::Map<String, serde_json::Value>, pool: &KeyPool) -> AppResult<()> {
+  if pool.keys.is_empty() && pool.rotation == KeyRotation::Manual {
+    fields.remove(KEY_POOL_FIELD);
+  } else {
+    fields.insert(KEY_POOL_FIELD.into(), serde_json::to_value(pool)?);
+  }
+  Ok(())
+}
+
+fn provider_fields<'a>(
+  map: &'a mut serde_json::Map<String, serde_json::Value>,
+  name: &str,
+) -> AppResult<&'a mut serde_json::Map<String, serde_json::Value>> {
+  if name == CHATGPT_NODE || name == RELAY_NODE {
+    return Err(AppError::ReservedName { name: name.to_string() });
+  }
+  let entry = map.entry(name.to_string()).or_insert_with(|| serde_json::json!({}));
+  if !entry.is_object() {
+    *entry = serde_json::json!({});
+  }
+  entry.as_object_mut().ok_or_else(|| AppError::Internal { message: "credential entry is not an object".into() })
+}
+
+/// 选中密钥池中的某个密钥：同步写入 env_key 字段
+fn activate(fields: &mut serde_json::Map<String, serde_json::Value>, env_key: &str, pool: &mut KeyPool, index: usize) {
+  let key = &pool.keys[index];
+  pool.active = Some(key.label.clone());
+  put_key(fields, env_key, key.key.clone());
+}
+
+/// 写入 auth.json 的 OPENAI_API_KEY：Codex 只从 auth.json 读取这一个变量，
+/// 使用其他 env_key 的节点需通过环境变量提供密钥（见 `codex-mate-cli credentials env`），此时清空该字段，
+/// 由调用方提示用户导出（见 nodes::env_key_warning）
+pub(crate) fn auth_api_key(env_key: &str, stored: Option<&str>) -> AppResult<serde_json::Value> {
+  match stored {
+    Some(k) if env_key == DEFAULT_ENV_KEY => Ok(serde_json::Value::String(reveal(k)?)),
+    _ => Ok(serde_json::Value::Null),
+  }
+}
+
+/// 设置节点当前使用的密钥；节点使用密钥池时同时更新被选中的那一项
+pub fn set_current_key(map: &mut serde_json::Map<String, serde_json::Value>, name: &str, key: String) -> AppResult<()> {
+  let env_key = credential_field(name)?;
+  let fields = provider_fields(map, name)?;
+  let mut pool = read_pool(fields);
+  if let Some(i) = pool.active_index() {
+    pool.keys[i].key = key.clone();
+    write_pool(fields, &pool)?;
+  }
+  put_key(fields, &env_key, key);
+  Ok(())
+}
+
+/// 切换节点前调用：轮询策略下换用下一个密钥，返回是否修改了凭据
+pub fn rotate_for_switch(map: &mut serde_json::Map<String, serde_json::Value>, name: &str, env_key: &str) -> AppResult<bool> {
+  let Some(fields) = map.get_mut(name).and_then(|v| v.as_object_mut()) else {
+    return Ok(false);
+  };
+  let mut pool = read_pool(fields);
+  if pool.rotation != KeyRotation::RoundRobin || pool.keys.len() < 2 {
+    return Ok(false);
+  }
+  let next = pool.active_index().map(|i| (i + 1) % pool.keys.len()).unwrap_or(0);
+  activate(fields, env_key, &mut pool, next);
+  write_pool(fields, &pool)?;
+  log::info!("rotated '{}' to key '{}'", name, pool.keys[next].label);
+  Ok(true)
+}
+
+/// 节点正在被 Codex 直接使用时，准备写入新当前密钥的 auth.json；
+/// 在写入 credentials.json 之前调用，解密等失败时两个文件都不会被修改
+fn staged_auth(
+  map: &serde_json::Map<String, serde_json::Value>,
+  name: &str,
+) -> AppResult<Option<serde_json::Map<String, serde_json::Value>>> {
+  let cfg = get_full_config()?;
+  if cfg.get("model_provider").and_then(|v| v.as_str()) != Some(name) {
+    return Ok(None);
+  }
+  let env_key = credential_field(name)?;
+  let stored = map.get(name).and_then(|v| v.as_object()).and_then(|f| stored_key(f, &env_key));
+  let key = auth_api_key(&env_key, stored)?;
+  let mut auth = read_auth_value()?;
+  auth.insert(DEFAULT_ENV_KEY.into(), key);
+  Ok(Some(auth))
+}
+
+/// 写入修改后的凭据，并同步已准备好的 auth.json
+fn commit(map: &serde_json::Map<String, serde_json::Value>, name: &str) -> AppResult<()> {
+  let auth = staged_auth(map, name)?;
+  write_credentials_value(map)?;
+  match auth {
+    Some(auth) => write_auth_value(&auth),
+    None => Ok(()),
+  }
+}
+
+pub fn list_provider_keys(name: String) -> AppResult<KeyPoolInfo> {
+  let map = read_credentials_value()?;
+  let fields = map.get(&name).and_then(|v| v.as_object()).cloned().unwrap_or_default();
+  let pool = read_pool(&fields);
+  let keys = pool
+    .keys
+    .iter()
+    .map(|k| KeyInfo {
+      label: k.label.clone(),
+      active: pool.active.as_deref() == Some(k.label.as_str()),
+      encrypted: is_encrypted(&k.key),
+      fingerprint: reveal(&k.key).ok().map(|plain| key_fingerprint(&plain)),
+    })
+    .collect();
+  Ok(KeyPoolInfo { provider: name, rotation: pool.rotation, active: pool.active, keys })
+}
+
+/// 向密钥池添加带标签的密钥；首次添加时已有的单个密钥以 "default" 标签并入密钥池
+pub fn add_provider_key(name: String, label: String, key: String) -> AppResult<()> {
+  let label = label.trim().to_string();
+  let key = key.trim().to_string();
+  if label.is_empty() || key.is_empty() {
+    return Err(AppError::invalid("key label and value must not be empty"));
+  }
+  ensure_unlocked()?;
+  let env_key = credential_field(&name)?;
+  let mut map = read_credentials_value()?;
+  let fields = provider_fields(&mut map, &name)?;
+  let mut pool = read_pool(fields);
+  if pool.keys.is_empty() {
+    if let Some(existing) = stored_key(fields, &env_key) {
+      pool.keys.push(StoredKey { label: "default".into(), key: existing.to_string() });
+      pool.active = Some("default".into());
+    }
+  }
+  if pool.position(&label).is_some() {
+    return Err(AppError::invalid(format!("key '{}' already exists for '{}'", label, name)));
+  }
+  pool.keys.push(StoredKey { label: label.clone(), key });
+  if pool.active_index().is_none() {
+    let last = pool.keys.len() - 1;
+    activate(fields, &env_key, &mut pool, last);
+  }
+  write_pool(fields, &pool)?;
+  commit(&map, &name)?;
+  log::info!("added key '{}' to '{}'", label, name);
+  Ok(())
+}
+
+/// 删除密钥；删除的是当前密钥时改用第一个剩余密钥，密钥池为空时节点不再有凭据
+pub fn remove_provider_key(name: String, label: String) -> AppResult<()> {
+  ensure_unlocked()?;
+  let env_key = credential_field(&name)?;
+  let mut map = read_credentials_value()?;
+  let fields = provider_fields(&mut map, &name)?;
+  let mut pool = read_pool(fields);
+  let index = pool.position(&label).ok_or_else(|| AppError::not_found("key", &label))?;
+  pool.keys.remove(index);
+  if pool.active.as_deref() == Some(label.as_str()) {
+    pool.active = None;
+    match pool.keys.is_empty() {
+      true => remove_key(fields, &env_key),
+      false => activate(fields, &env_key, &mut pool, 0),
+    }
+  }
+  write_pool(fields, &pool)?;
+  commit(&map, &name)?;
+  log::info!("removed key '{}' from '{}'", label, name);
+  Ok(())
+}
+
+/// 手动选择密钥；该节点正在使用时同步更新 auth.json
+pub fn select_provider_key(name: String, label: String) -> AppResult<()> {
+  ensure_unlocked()?;
+  let env_key = credential_field(&name)?;
+  let mut map = read_credentials_value()?;
+  let fields = provider_fields(&mut map, &name)?;
+  let mut pool = read_pool(fields);
+  let index = pool.position(&label).ok_or_else(|| AppError::not_found("key", &label))?;
+  activate(fields, &env_key, &mut pool, index);
+  write_pool(fields, &pool)?;
+  commit(&map, &name)?;
+  log::info!("selected key '{}' for '{}'", label, name);
+  Ok(())
+}
+
+pub fn set_key_rotation(name: String, rotation: KeyRotation) -> AppResult<()> {
+  let mut map = read_credentials_value()?;
+  let fields = provider_fields(&mut map, &name)?;
+  let mut pool = read_pool(fields);
+  pool.rotation = rotation;
+  write_pool(fields, &pool)?;
+  write_credentials_value(&map)?;
+  log::info!("key rotation for '{}' set to {:?}", name, rotation);
+  Ok(())
+}
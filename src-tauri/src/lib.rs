@@ -0,0 +1,29 @@
+//! Codex Mate 核心逻辑
+//! 图形界面（Tauri 命令）与命令行工具 codex-mate-cli 共用同一套实现
+
+pub mod accounts;
+pub mod auth;
+pub mod backup;
+pub mod config;
+pub mod credentials;
+pub mod effective;
+pub mod error;
+pub mod health;
+pub mod homes;
+pub mod http;
+pub mod keys;
+pub mod logging;
+pub mod mcp;
+pub mod migrate;
+pub mod models;
+pub mod nodes;
+pub mod permissions;
+pub mod presets;
+pub mod profiles;
+pub mod projects;
+pub mod relay;
+pub mod schema;
+pub mod settings;
+pub mod storage;
+pub mod vault;
+pub mod version;
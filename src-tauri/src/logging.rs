@@ -0,0 +1,302 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::backup::now_millis;
+use crate::error::{AppError, AppResult};
+use crate::permissions;
+use crate::settings::{read_settings, write_settings};
+use crate::storage::codex_dir;
+
+const LOG_FILE: &str = "codex-mate.log";
+
+/// 环境变量覆盖日志级别，便于临时排查（如 CODEX_MATE_LOG=debug）
+const LOG_LEVEL_ENV: &str = "CODEX_MATE_LOG";
+/// 设置为 1 时日志同时输出到 stderr，默认只写入日志文件，不混入 CLI 输出
+const LOG_STDERR_ENV: &str = "CODEX_MATE_LOG_STDERR";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LogSettings {
+  /// 最低记录级别：error / warn / info / debug / trace
+  pub level: String,
+  /// 单个日志文件上限（KB），超过后轮转
+  pub max_file_kb: u64,
+  /// 保留的历史日志文件数量
+  pub max_files: usize,
+}
+
+impl Default for LogSettings {
+  fn default() -> Self {
+    Self { level: "info".into(), max_file_kb: 1024, max_files: 5 }
+  }
+}
+
+/// 日志文件中的一行（JSON Lines 格式）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+  /// 毫秒时间戳
+  pub ts: u64,
+  pub level: String,
+  pub target: String,
+  pub message: String,
+}
+
+struct FileState {
+  file: Option<File>,
+  size: u64,
+  max_bytes: u64,
+  max_files: usize,
+}
+
+struct FileLogger {
+  dir: PathBuf,
+  /// 是否同时输出到 stderr，见 LOG_STDERR_ENV
+  stderr: bool,
+  state: Mutex<FileState>,
+}
+
+static LOGGER: OnceLock<FileLogger> = OnceLock::new();
+
+fn parse_level(s: &str) -> AppResult<LevelFilter> {
+  s.trim()
+    .parse::<LevelFilter>()
+    .map_err(|_| AppError::invalid(format!("unknown log level '{}'", s)))
+}
+
+/// 日志目录: ~/.codex/codex-mate/logs（进程启动时确定，切换 Codex 目录不影响）
+pub fn logs_dir() -> AppResult<PathBuf> {
+  match LOGGER.get() {
+    Some(logger) => Ok(logger.dir.clone()),
+    None => Ok(codex_dir()?.join("codex-mate").join("logs")),
+  }
+}
+
+/// 初始化全局日志，重复调用无副作用；日志目录不可用时静默降级为不记录
+pub fn init_logging() {
+  if LOGGER.get().is_some() {
+    return;
+  }
+  let Ok(dir) = logs_dir() else {
+    return;
+  };
+  let settings = read_settings().log;
+  let level = std::env::var(LOG_LEVEL_ENV)
+    .ok()
+    .and_then(|v| parse_level(&v).ok())
+    .or_else(|| parse_level(&settings.level).ok())
+    .unwrap_or(LevelFilter::Info);
+
+  let logger = LOGGER.get_or_init(|| FileLogger {
+    dir,
+    stderr: std::env::var(LOG_STDERR_ENV).is_ok_and(|v| v == "1"),
+    state: Mutex::new(FileState {
+      file: None,
+      size: 0,
+      max_bytes: settings.max_file_kb.max(1) * 1024,
+      max_files: settings.max_files,
+    }),
+  });
+  if log::set_logger(logger).is_ok() {
+    log::set_max_level(level);
+  }
+}
+
+/// 脱敏：API Key、Bearer token、JWT 以及 key/token/secret/password 类字段的值
+pub fn redact(message: &str) -> String {
+  static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+  let patterns = PATTERNS.get_or_init(|| {
+    [
+      (r"sk-[A-Za-z0-9_\-]{6,}", "sk-***"),
+      (r"(?i)(bearer\s+)[A-Za-z0-9_\-\.=]+", "${1}***"),
+      (r"eyJ[A-Za-z0-9_\-]+\.[A-Za-z0-9_\-]+\.[A-Za-z0-9_\-]*", "***"),
+      (
+        r#"(?i)("[^"]*(?:key|token|secret|password)[^"]*"\s*:\s*)"[^"]*""#,
+        r#"${1}"***""#,
+      ),
+      (
+        r"(?i)\b([A-Za-z_]*(?:key|token|secret|password)[A-Za-z_]*\s*=\s*)[^\s,;&]+",
+        "${1}***",
+      ),
+    ]
+    .into_iter()
+    .filter_map(|(p, r)| Regex::new(p).ok().map(|re| (re, r)))
+    .collect()
+  });
+  let mut out = message.to_string();
+  for (re, replacement) in patterns {
+    out = re.replace_all(&out, *replacement).into_owned();
+  }
+  out
+}
+
+fn rotated_path(dir: &Path, index: usize) -> PathBuf {
+  if index == 0 {
+    dir.join(LOG_FILE)
+  } else {
+    dir.join(format!("{}.{}", LOG_FILE, index))
+  }
+}
+
+impl FileLogger {
+  /// 当前文件超出上限时依次后移 .1 .. .N，丢弃最旧的一份
+  fn rotate(&self, state: &mut FileState) {
+    state.file = None;
+    let _ = fs::remove_file(rotated_path(&self.dir, state.max_files));
+    for i in (0..state.max_files).rev() {
+      let _ = fs::rename(rotated_path(&self.dir, i), rotated_path(&self.dir, i + 1));
+    }
+    if state.max_files == 0 {
+      let _ = fs::remove_file(rotated_path(&self.dir, 0));
+    }
+    state.size = 0;
+  }
+
+  fn write_line(&self, line: &str) {
+    let Ok(mut state) = self.state.lock() else {
+      return;
+    };
+    if state.size + line.len() as u64 > state.max_bytes && state.size > 0 {
+      self.rotate(&mut state);
+    }
+    if state.file.is_none() {
+      let _ = permissions::create_dir_all(&self.dir);
+      let path = rotated_path(&self.dir, 0);
+      match permissions::open_options(true).create(true).append(true).open(&path) {
+        Ok(f) => {
+          state.size = f.metadata().map(|m| m.len()).unwrap_or(0);
+          state.file = Some(f);
+        }
+        Err(_) => return,
+      }
+    }
+    if let Some(f) = state.file.as_mut() {
+      if f.write_all(line.as_bytes()).is_ok() {
+        state.size += line.len() as u64;
+      }
+    }
+  }
+}
+
+impl Log for FileLogger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.level() <= log::max_level()
+  }
+
+  fn log(&self, record: &Record) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+    let entry = LogEntry {
+      ts: now_millis(),
+      level: record.level().to_string(),
+      target: record.target().to_string(),
+      message: redact(&record.args().to_string()),
+    };
+    if self.stderr {
+      eprintln!("[{}] {}: {}", entry.level, entry.target, entry.message);
+    }
+    if let Ok(mut line) = serde_json::to_string(&entry) {
+      line.push('\n');
+      self.write_line(&line);
+    }
+  }
+
+  fn flush(&self) {
+    if let Ok(mut state) = self.state.lock() {
+      if let Some(f) = state.file.as_mut() {
+        let _ = f.flush();
+      }
+    }
+  }
+}
+
+/// 读取最近的日志（新的在后），可按最低级别过滤，用于应用内日志查看器
+pub fn tail_logs(limit: Option<usize>, level: Option<String>) -> AppResult<Vec<LogEntry>> {
+  let limit = limit.unwrap_or(200);
+  let min_level = level.as_deref().map(parse_level).transpose()?.unwrap_or(LevelFilter::Trace);
+  let dir = logs_dir()?;
+  let max_files = LOGGER
+    .get()
+    .and_then(|l| l.state.lock().ok().map(|s| s.max_files))
+    .unwrap_or_else(|| read_settings().log.max_files);
+
+  // 从当前文件往更旧的轮转文件读取，直到凑够 limit 条
+  let mut entries: Vec<LogEntry> = Vec::new();
+  for i in 0..=max_files {
+    let path = rotated_path(&dir, i);
+    let content = match fs::read_to_string(&path) {
+      Ok(c) => c,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+      Err(e) => return Err(AppError::io("read", &path, e)),
+    };
+    let mut chunk: Vec<LogEntry> = content
+      .lines()
+      .filter_map(|l| serde_json::from_str::<LogEntry>(l).ok())
+      .filter(|e| e.level.parse::<Level>().map(|lv| lv <= min_level).unwrap_or(true))
+      .collect();
+    chunk.append(&mut entries);
+    entries = chunk;
+    if entries.len() >= limit {
+      break;
+    }
+  }
+  let skip = entries.len().saturating_sub(limit);
+  Ok(entries.split_off(skip))
+}
+
+pub fn get_log_settings() -> LogSettings {
+  read_settings().log
+}
+
+/// 保存日志设置并立即应用到当前进程
+pub fn set_log_settings(settings: LogSettings) -> AppResult<()> {
+  let level = parse_level(&settings.level)?;
+  let mut all = read_settings();
+  all.log = settings.clone();
+  write_settings(&all)?;
+
+  log::set_max_level(level);
+  if let Some(logger) = LOGGER.get() {
+    if let Ok(mut state) = logger.state.lock() {
+      state.max_bytes = settings.max_file_kb.max(1) * 1024;
+      state.max_files = settings.max_files;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn redacts_api_keys_and_tokens() {
+    assert_eq!(redact("using key sk-proj-abcdef123456 for 'a'"), "using key sk-*** for 'a'");
+    assert_eq!(redact("Authorization: Bearer abc.def-123"), "Authorization: Bearer ***");
+    assert_eq!(redact("id_token eyJhbGciOi.eyJzdWIiOi.c2lnbmF0dXJl"), "id_token ***");
+  }
+
+  #[test]
+  fn redacts_secret_fields() {
+    assert_eq!(redact(r#"{"OPENAI_API_KEY": "abc123", "name": "a"}"#), r#"{"OPENAI_API_KEY": "***", "name": "a"}"#);
+    assert_eq!(redact("url?api_key=abc123&x=1"), "url?api_key=***&x=1");
+    assert_eq!(redact("password = hunter2, user = bob"), "password = ***, user = bob");
+  }
+
+  #[test]
+  fn keeps_ordinary_messages() {
+    let message = "switched to node 'packycode' (key 'alice', 3 keys)";
+    assert_eq!(redact(message), message);
+  }
+
+  #[test]
+  fn parses_levels() {
+    assert_eq!(parse_level(" debug ").unwrap(), LevelFilter::Debug);
+    assert!(parse_level("loud").is_err());
+  }
+}
@@ -1,602 +1,438 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use serde::{Serialize, Deserialize};
-use std::process::Command;
-use std::fs;
-use std::path::PathBuf;
-use toml::Value as TomlValue;
-use std::io::Write;
+use std::collections::BTreeMap;
+
+use codex_mate::accounts::{self, AccountSnapshot};
+use codex_mate::backup::{self, BackupEntry, BackupSettings};
+use codex_mate::config;
+use codex_mate::credentials::{self, CredentialDiagnostics};
+use codex_mate::effective::{self, EffectiveConfig};
+use codex_mate::error::AppResult;
+use codex_mate::health::{self, ProviderHealth};
+use codex_mate::homes::{self, HomeList};
+use codex_mate::keys::{self, KeyPoolInfo, KeyRotation};
+use codex_mate::logging::{self, LogEntry, LogSettings};
+use codex_mate::mcp::{self, McpServer};
+use codex_mate::migrate::{self, MigrationPlan};
+use codex_mate::models::{self, ModelList};
+use codex_mate::nodes::{self, NodeList, ProviderConfig};
+use codex_mate::permissions::{self, PermissionReport};
+use codex_mate::presets::{self, PresetCatalog};
+use codex_mate::profiles::{self, ProfileConfig, ProfileList};
+use codex_mate::projects::{self, ProjectEntry};
+use codex_mate::relay::{self, RelayStatus};
+use codex_mate::schema::{self, Diagnostic};
+use codex_mate::vault::{self, VaultStatus};
+use codex_mate::version::{self, CodexVersion};
+
+// Tauri 命令层：仅做参数转发，具体逻辑位于 codex_mate 库中，与 CLI 共用
 
-#[derive(Serialize)]
-struct CodexVersion {
-  installed: bool,
-  version: Option<String>,
-  error: Option<String>,
+#[tauri::command]
+fn get_codex_version() -> CodexVersion {
+  version::refresh_codex_version()
 }
 
 #[tauri::command]
-fn get_codex_version() -> CodexVersion {
-  match Command::new("codex").arg("--version").output() {
-    Ok(out) => {
-      if out.status.success() {
-        let v = String::from_utf8_lossy(&out.stdout).trim().to_string();
-        CodexVersion { installed: true, version: Some(v), error: None }
-      } else {
-        CodexVersion { installed: false, version: None, error: Some(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
-      }
-    }
-    Err(e) => CodexVersion { installed: false, version: None, error: Some(e.to_string()) },
-  }
+fn get_full_config() -> AppResult<serde_json::Value> {
+  config::get_full_config()
 }
 
-/// 获取 Codex 配置目录路径 (~/.codex)
-fn codex_dir() -> PathBuf {
-  let path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("~")).join(".codex");
-  eprintln!("[DEBUG] codex_dir() = {}", path.display());
-  path
+#[tauri::command]
+fn list_nodes() -> AppResult<NodeList> {
+  nodes::list_nodes()
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Credentials(pub std::collections::BTreeMap<String, serde_json::Value>);
+#[tauri::command]
+fn switch_node(name: String) -> AppResult<Option<String>> {
+  nodes::switch_node(name)
+}
 
 #[tauri::command]
-fn get_full_config() -> Result<serde_json::Value, String> {
-  let path = codex_dir().join("config.toml");
-  let content = fs::read_to_string(&path).map_err(|e| format!("read {} failed: {}", path.display(), e))?;
-  let v: TomlValue = toml::from_str(&content).map_err(|e| format!("parse toml failed: {}", e))?;
-  serde_json::to_value(v).map_err(|e| e.to_string())
+fn upsert_node(name: String, provider_fields: ProviderConfig, credential: Option<String>) -> AppResult<()> {
+  nodes::upsert_node(name, provider_fields, credential)
 }
 
 #[tauri::command]
-fn get_credentials() -> Result<serde_json::Value, String> {
-  let path = codex_dir().join("codex-mate").join("credentials.json");
-  eprintln!("[DEBUG] get_credentials: path = {}", path.display());
-  if !path.exists() {
-    eprintln!("[DEBUG] get_credentials: file not found, returning empty object");
-    return Ok(serde_json::json!({}));
-  }
-  match fs::read_to_string(&path) {
-    Ok(content) => {
-      let trimmed = content.trim();
-      if trimmed.is_empty() {
-        // 文件存在但为空：返回 {}，避免前端功能受阻
-        eprintln!("[WARN] get_credentials: file exists but is empty, returning empty object");
-        return Ok(serde_json::json!({}));
-      }
-      match serde_json::from_str::<serde_json::Value>(trimmed) {
-        Ok(v) => {
-          eprintln!("[DEBUG] get_credentials: parsed successfully");
-          Ok(v)
-        }
-        Err(e) => {
-          // 解析失败也容错返回 {}，并打印警告日志，防止列表页崩溃
-          eprintln!("[WARN] get_credentials: parse failed ({}), returning empty object", e);
-          Ok(serde_json::json!({}))
-        }
-      }
-    }
-    Err(e) => {
-      eprintln!(
-        "[WARN] get_credentials: read {} failed: {}. Returning empty object",
-        path.display(),
-        e
-      );
-      Ok(serde_json::json!({}))
-    }
-  }
+fn rename_node(name: String, new_name: String) -> AppResult<()> {
+  nodes::rename_node(name, new_name)
 }
 
-#[derive(Serialize)]
-struct ProviderInfo {
-  name: String,
-  base_url: Option<String>,
-  wire_api: Option<String>,
-  requires_openai_auth: Option<bool>,
-  has_credential: bool,
-}
-
-#[derive(Serialize)]
-struct NodeList {
-  current_provider: Option<String>,
-  providers: Vec<ProviderInfo>,
-}
-
-/// Tauri 命令: 列出所有节点及其状态
-/// 返回: 当前激活的节点和所有节点列表
-#[tauri::command]
-fn list_nodes() -> Result<NodeList, String> {
-  eprintln!("[DEBUG] list_nodes called");
-  
-  // 1. 读取配置文件
-  let cfg = get_full_config()?;
-  
-  // 2. 读取凭据文件
-  let creds = get_credentials()?;
-
-  // 3. 获取当前激活的节点
-  let current_provider = cfg.get("model_provider").and_then(|v| v.as_str()).map(|s| s.to_string());
-  eprintln!("[DEBUG] list_nodes: current_provider = {:?}", current_provider);
-
-  // 4. 遍历所有配置的节点
-  let mut providers: Vec<ProviderInfo> = vec![];
-  if let Some(mps) = cfg.get("model_providers").and_then(|v| v.as_object()) {
-    eprintln!("[DEBUG] list_nodes: found {} model_providers", mps.len());
-    for (name, item) in mps.iter() {
-      let base_url = item.get("base_url").and_then(|v| v.as_str()).map(|s| s.to_string());
-      let wire_api = item.get("wire_api").and_then(|v| v.as_str()).map(|s| s.to_string());
-      let requires_openai_auth = item.get("requires_openai_auth").and_then(|v| v.as_bool());
-      
-      // 检查该节点是否有凭据
-      let has_credential = creds.get(name).and_then(|v| v.get("OPENAI_API_KEY")).and_then(|v| v.as_str()).map(|s| !s.is_empty()).unwrap_or(false);
-      eprintln!("[DEBUG] list_nodes: provider '{}' has_credential = {}", name, has_credential);
-      
-      providers.push(ProviderInfo { name: name.clone(), base_url, wire_api, requires_openai_auth, has_credential });
-    }
-  }
+#[tauri::command]
+fn duplicate_node(name: String, new_name: String) -> AppResult<()> {
+  nodes::duplicate_node(name, new_name)
+}
 
-  eprintln!("[DEBUG] list_nodes: returning {} providers", providers.len());
-  Ok(NodeList { current_provider, providers })
-}
-
-/// 原子写入文件
-/// 使用临时文件先写入，再重命名，确保数据不会因为写入中断而损坏
-fn atomic_write(path: &std::path::Path, content: &str) -> Result<(), String> {
-  eprintln!("[DEBUG] atomic_write: target path = {}", path.display());
-  
-  // 1. 获取父目录并创建
-  let parent = path.parent().ok_or_else(|| "invalid path".to_string())?;
-  eprintln!("[DEBUG] atomic_write: parent dir = {}", parent.display());
-  
-  fs::create_dir_all(parent).map_err(|e| format!("create dir {} failed: {}", parent.display(), e))?;
-  eprintln!("[DEBUG] atomic_write: parent dir created/verified");
-  
-  // 2. 创建临时文件
-  let mut tmp = parent.to_path_buf();
-  tmp.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
-  eprintln!("[DEBUG] atomic_write: temp file = {}", tmp.display());
-  
-  {
-    let mut f = fs::File::create(&tmp).map_err(|e| format!("create temp file failed: {}", e))?;
-    eprintln!("[DEBUG] atomic_write: temp file created, writing {} bytes", content.len());
-    
-    f.write_all(content.as_bytes()).map_err(|e| format!("write temp file failed: {}", e))?;
-    f.sync_all().ok();
-    eprintln!("[DEBUG] atomic_write: content written and synced");
-  }
-  
-  // 3. 重命名临时文件为目标文件（失败则尝试降级写入）
-  match fs::rename(&tmp, path) {
-    Ok(_) => {
-      eprintln!("[DEBUG] atomic_write: file renamed to target path successfully");
-    }
-    Err(e) => {
-      eprintln!("[WARN] atomic_write: rename failed ({}), trying fallback write", e);
-      // fallback: 读取临时文件内容，直接写入目标文件
-      let data = fs::read(&tmp).map_err(|e| format!("fallback read temp failed: {}", e))?;
-      let mut f2 = fs::File::create(path).map_err(|e| format!("fallback create target failed: {}", e))?;
-      f2.write_all(&data).map_err(|e| format!("fallback write target failed: {}", e))?;
-      f2.sync_all().ok();
-      // 删除临时文件
-      let _ = fs::remove_file(&tmp);
-      eprintln!("[DEBUG] atomic_write: fallback write completed ({} bytes)", data.len());
-    }
-  }
+#[tauri::command]
+fn list_presets() -> AppResult<PresetCatalog> {
+  presets::list_presets()
+}
 
-  // 4. 目录级别 fsync，确保重命名对文件系统可见（macOS/Unix 推荐）
-  if let Some(parent_dir) = path.parent() {
-    if let Ok(dir_file) = fs::File::open(parent_dir) {
-      let _ = dir_file.sync_all();
-      eprintln!("[DEBUG] atomic_write: parent directory fsync completed");
-    } else {
-      eprintln!("[WARN] atomic_write: failed to open parent directory for fsync");
-    }
-  }
-  
-  Ok(())
-}
-
-#[tauri::command]
-fn switch_node(name: String) -> Result<(), String> {
-  eprintln!("[DEBUG] switch_node called: name = '{}'", name);
-  // 读取凭据（容错：空文件/损坏文件均返回空 Map）
-  let creds_map = read_credentials_value()?;
-  let key = creds_map
-    .get(&name)
-    .and_then(|v| v.get("OPENAI_API_KEY"))
-    .and_then(|v| v.as_str())
-    .ok_or_else(|| format!("credential not found for provider '{}'", name))?;
-  eprintln!("[DEBUG] switch_node: credential found, key_length = {}", key.len());
-
-  // write auth.json
-  let auth_path = codex_dir().join("auth.json");
-  let auth_content = serde_json::json!({ "OPENAI_API_KEY": key });
-  atomic_write(&auth_path, &serde_json::to_string_pretty(&auth_content).unwrap())?;
-
-  // update config.toml model_provider
-  let cfg_path = codex_dir().join("config.toml");
-  let cfg_str = fs::read_to_string(&cfg_path).map_err(|e| format!("read {} failed: {}", cfg_path.display(), e))?;
-  let mut v: TomlValue = toml::from_str(&cfg_str).map_err(|e| format!("parse toml failed: {}", e))?;
-  if let Some(tbl) = v.as_table_mut() {
-    tbl.insert("model_provider".into(), TomlValue::String(name));
-  }
-  let out = toml::to_string_pretty(&v).map_err(|e| e.to_string())?;
-  atomic_write(&cfg_path, &out)?;
+#[tauri::command]
+fn apply_preset(
+  id: String,
+  name: Option<String>,
+  values: BTreeMap<String, String>,
+  credential: Option<String>,
+) -> AppResult<String> {
+  presets::apply_preset(id, name, values, credential)
+}
 
-  Ok(())
+#[tauri::command]
+fn set_preset_catalogs(paths: Vec<String>) -> AppResult<PresetCatalog> {
+  presets::set_preset_catalogs(paths)
 }
 
-fn read_config_value() -> Result<TomlValue, String> {
-  let cfg_path = codex_dir().join("config.toml");
-  let cfg_str = fs::read_to_string(&cfg_path).map_err(|e| format!("read {} failed: {}", cfg_path.display(), e))?;
-  toml::from_str(&cfg_str).map_err(|e| format!("parse toml failed: {}", e))
+#[tauri::command]
+async fn check_providers(names: Option<Vec<String>>, timeout_ms: Option<u64>) -> AppResult<Vec<ProviderHealth>> {
+  health::check_providers(names, timeout_ms).await
 }
 
-fn write_config_value(v: &TomlValue) -> Result<(), String> {
-  let cfg_path = codex_dir().join("config.toml");
-  let out = toml::to_string_pretty(&v).map_err(|e| e.to_string())?;
-  atomic_write(&cfg_path, &out)
+#[tauri::command]
+async fn list_provider_models(name: String, refresh: bool, timeout_ms: Option<u64>) -> AppResult<ModelList> {
+  models::list_provider_models(name, refresh, timeout_ms).await
 }
 
-/// 读取凭据文件，返回凭据的 Map 结构
-/// 文件路径: ~/.codex/codex-mate/credentials.json
-fn read_credentials_value() -> Result<serde_json::Map<String, serde_json::Value>, String> {
-  let path = codex_dir().join("codex-mate").join("credentials.json");
-  eprintln!("[DEBUG] read_credentials_value: path = {}", path.display());
-  
-  if !path.exists() {
-    eprintln!("[DEBUG] read_credentials_value: file does not exist, returning empty map");
-    return Ok(serde_json::Map::new());
-  }
+#[tauri::command]
+async fn set_model(model: String, provider: Option<String>, profile: Option<String>) -> AppResult<Vec<String>> {
+  models::set_model(model, provider, profile).await
+}
 
-  let content = fs::read_to_string(&path).map_err(|e| format!("read {} failed: {}", path.display(), e))?;
-  eprintln!("[DEBUG] read_credentials_value: file content length = {}", content.len());
+#[tauri::command]
+fn get_relay_status() -> AppResult<RelayStatus> {
+  relay::get_relay_status()
+}
 
-  let trimmed = content.trim();
-  if trimmed.is_empty() {
-    // 文件为空时，容错返回空 Map，避免后续更新失败
-    eprintln!("[WARN] read_credentials_value: file is empty, returning empty map");
-    return Ok(serde_json::Map::new());
-  }
+/// 启用中转并在本进程内启动服务
+#[tauri::command]
+async fn enable_relay(port: Option<u16>) -> AppResult<RelayStatus> {
+  relay::enable_relay(port)?;
+  relay::start_relay().await
+}
 
-  match serde_json::from_str::<serde_json::Value>(trimmed) {
-    Ok(v) => {
-      let result = v.as_object().cloned().unwrap_or_default();
-      eprintln!("[DEBUG] read_credentials_value: parsed map has {} entries", result.len());
-      Ok(result)
-    }
-    Err(e) => {
-      // 当文件损坏/格式不正确时，打印警告并返回空 Map，让调用方可以继续写入修复
-      eprintln!("[WARN] read_credentials_value: parse failed ({}), returning empty map", e);
-      Ok(serde_json::Map::new())
-    }
-  }
+#[tauri::command]
+fn set_relay_failover(nodes: Vec<String>, cooldown_secs: Option<u64>) -> AppResult<RelayStatus> {
+  relay::set_relay_failover(nodes, cooldown_secs)
 }
 
-/// 写入凭据文件，使用原子写入保证数据安全
-/// 文件路径: ~/.codex/codex-mate/credentials.json
-fn write_credentials_value(map: &serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
-  let path = codex_dir().join("codex-mate").join("credentials.json");
-  eprintln!("[DEBUG] write_credentials_value: path = {}", path.display());
-  eprintln!("[DEBUG] write_credentials_value: writing {} entries", map.len());
-  
-  let s = serde_json::to_string_pretty(&serde_json::Value::Object(map.clone())).map_err(|e| e.to_string())?;
-  eprintln!("[DEBUG] write_credentials_value: json content = {}", s);
-  
-  let byte_len = s.as_bytes().len();
-  atomic_write(&path, &s)?;
-  eprintln!("[DEBUG] write_credentials_value: file written successfully ({} bytes)", byte_len);
-  
-  Ok(())
-}
-
-#[tauri::command]
-fn upsert_node(name: String, provider_fields: serde_json::Value, credential: Option<String>) -> Result<(), String> {
-  let mut cfg = read_config_value()?;
-  let tbl = cfg.as_table_mut().ok_or_else(|| "invalid config root".to_string())?;
-  // ensure model_providers table exists
-  if !tbl.contains_key("model_providers") {
-    tbl.insert("model_providers".into(), TomlValue::Table(toml::map::Map::new()));
-  }
-  let mps = tbl.get_mut("model_providers").and_then(|v| v.as_table_mut()).ok_or_else(|| "invalid model_providers".to_string())?;
-
-  let mut provider_tbl = if let Some(existing) = mps.get(&name).and_then(|v| v.as_table()) {
-    existing.clone()
-  } else {
-    toml::map::Map::new()
-  };
-
-  // merge fields from provider_fields (JSON) into provider_tbl (TOML)
-  fn json_to_toml(v: &serde_json::Value) -> TomlValue {
-    match v {
-      serde_json::Value::Null => TomlValue::String(String::new()),
-      serde_json::Value::Bool(b) => TomlValue::Boolean(*b),
-      serde_json::Value::Number(n) => {
-        if let Some(i) = n.as_i64() { TomlValue::Integer(i) }
-        else if let Some(f) = n.as_f64() { TomlValue::Float(f) }
-        else { TomlValue::String(n.to_string()) }
-      }
-      serde_json::Value::String(s) => TomlValue::String(s.clone()),
-      serde_json::Value::Array(arr) => TomlValue::Array(arr.iter().map(json_to_toml).collect()),
-      serde_json::Value::Object(obj) => {
-        let mut m = toml::map::Map::new();
-        for (k, v) in obj.iter() { m.insert(k.clone(), json_to_toml(v)); }
-        TomlValue::Table(m)
-      }
-    }
-  }
+#[tauri::command]
+fn disable_relay() -> AppResult<Option<String>> {
+  relay::disable_relay()
+}
 
-  if let Some(obj) = provider_fields.as_object() {
-    for (k, v) in obj {
-      provider_tbl.insert(k.clone(), json_to_toml(v));
-    }
-  }
+#[tauri::command]
+fn delete_node(name: String, force: bool) -> AppResult<()> {
+  nodes::delete_node(name, force)
+}
 
-  // enforce required fields
-  provider_tbl.insert("name".into(), TomlValue::String(name.clone()));
-  if !provider_tbl.contains_key("wire_api") {
-    provider_tbl.insert("wire_api".into(), TomlValue::String("responses".into()));
-  }
+#[tauri::command]
+fn update_node_credential(name: String, openai_api_key: String) -> AppResult<()> {
+  nodes::update_node_credential(name, openai_api_key)
+}
 
-  // ensure base_url exists when creating new
-  if !mps.contains_key(&name) {
-    if !provider_tbl.contains_key("base_url") {
-      return Err("base_url is required for new provider".into());
-    }
-  }
+#[tauri::command]
+fn credentials_diagnostics() -> AppResult<CredentialDiagnostics> {
+  credentials::credentials_diagnostics()
+}
 
-  mps.insert(name.clone(), TomlValue::Table(provider_tbl));
-  write_config_value(&cfg)?;
-
-  if let Some(key) = credential {
-    let trimmed_key = key.trim().to_string();
-    if !trimmed_key.is_empty() {
-      eprintln!("[DEBUG] upsert_node: writing credential for provider '{}', key_length = {}", name, trimmed_key.len());
-      let mut map = read_credentials_value()?;
-      map.insert(name.clone(), serde_json::json!({"OPENAI_API_KEY": trimmed_key}));
-      write_credentials_value(&map)?;
-      // 回读校验
-      let verify = read_credentials_value()?;
-      let ok = verify.get(&name).and_then(|v| v.get("OPENAI_API_KEY")).and_then(|v| v.as_str()).is_some();
-      eprintln!("[DEBUG] upsert_node: credential persisted = {}", ok);
-    } else {
-      eprintln!("[WARN] upsert_node: provided credential is empty, skipping write");
-    }
-  }
+#[tauri::command]
+fn reveal_credential(name: String) -> AppResult<String> {
+  credentials::reveal_credential(name)
+}
 
-  Ok(())
+#[tauri::command]
+fn list_provider_keys(name: String) -> AppResult<KeyPoolInfo> {
+  keys::list_provider_keys(name)
 }
 
 #[tauri::command]
-fn delete_node(name: String, force: bool) -> Result<(), String> {
-  let mut cfg = read_config_value()?;
-  let current = cfg.get("model_provider").and_then(|v| v.as_str()).map(|s| s.to_string());
-  if !force {
-    if let Some(cur) = current {
-      if cur == name { return Err("cannot delete active provider without force".into()); }
-    }
-  }
+fn add_provider_key(name: String, label: String, key: String) -> AppResult<()> {
+  keys::add_provider_key(name, label, key)
+}
 
-  let tbl = cfg.as_table_mut().ok_or_else(|| "invalid config root".to_string())?;
-  if let Some(mps) = tbl.get_mut("model_providers").and_then(|v| v.as_table_mut()) {
-    mps.remove(&name);
-  }
-  write_config_value(&cfg)?;
+#[tauri::command]
+fn remove_provider_key(name: String, label: String) -> AppResult<()> {
+  keys::remove_provider_key(name, label)
+}
 
-  let mut map = read_credentials_value()?;
-  map.remove(&name);
-  write_credentials_value(&map)?;
-  Ok(())
+#[tauri::command]
+fn select_provider_key(name: String, label: String) -> AppResult<()> {
+  keys::select_provider_key(name, label)
 }
 
 #[tauri::command]
-fn read_config_raw() -> Result<String, String> {
-  let cfg_path = codex_dir().join("config.toml");
-  fs::read_to_string(&cfg_path).map_err(|e| format!("read {} failed: {}", cfg_path.display(), e))
+fn set_key_rotation(name: String, rotation: KeyRotation) -> AppResult<()> {
+  keys::set_key_rotation(name, rotation)
 }
 
 #[tauri::command]
-fn write_config_raw(content: String) -> Result<(), String> {
-  // validate TOML first
-  let _: TomlValue = toml::from_str(&content).map_err(|e| format!("TOML parse error: {}", e))?;
-  let cfg_path = codex_dir().join("config.toml");
-  atomic_write(&cfg_path, &content)
+fn list_profiles() -> AppResult<ProfileList> {
+  profiles::list_profiles()
 }
 
-/// Tauri 命令: 更新指定节点的凭据
-/// 参数:
-///   - name: 节点名称 (例如: "packycode", "openai-chat-completions")
-///   - openai_api_key: OpenAI API 密钥
 #[tauri::command]
-fn update_node_credential(name: String, openai_api_key: String) -> Result<(), String> {
-  let trimmed_name = name.trim().to_string();
-  let trimmed_key = openai_api_key.trim().to_string();
-  eprintln!(
-    "[DEBUG] update_node_credential called: name='{}'(len {}), key_length = {}",
-    trimmed_name,
-    trimmed_name.len(),
-    trimmed_key.len()
-  );
+fn upsert_profile(name: String, fields: ProfileConfig) -> AppResult<Vec<String>> {
+  profiles::upsert_profile(name, fields)
+}
 
-  if trimmed_name.is_empty() {
-    return Err("provider name is empty".into());
-  }
-  if trimmed_key.is_empty() {
-    return Err("credential is empty".into());
-  }
+#[tauri::command]
+fn delete_profile(name: String, force: bool) -> AppResult<()> {
+  profiles::delete_profile(name, force)
+}
 
-  // 1. 读取现有凭据
-  let mut map = read_credentials_value()?;
-  eprintln!("[DEBUG] update_node_credential: loaded {} existing credentials", map.len());
-
-  // 2. 插入或更新指定节点的凭据
-  map.insert(trimmed_name.clone(), serde_json::json!({"OPENAI_API_KEY": trimmed_key}));
-  eprintln!("[DEBUG] update_node_credential: after insert, map has {} entries", map.len());
-
-  // 3. 写入文件
-  write_credentials_value(&map)?;
-  eprintln!("[DEBUG] update_node_credential: write_credentials_value finished");
-
-  // 4. 回读校验，确保落盘成功
-  let verify = read_credentials_value()?;
-  let ok = verify
-    .get(&trimmed_name)
-    .and_then(|v| v.get("OPENAI_API_KEY"))
-    .and_then(|v| v.as_str())
-    .is_some();
-  eprintln!("[DEBUG] update_node_credential: verify persisted = {}", ok);
-  if !ok {
-    eprintln!("[WARN] update_node_credential: credential not found after write");
-  }
+#[tauri::command]
+fn activate_profile(name: Option<String>) -> AppResult<()> {
+  profiles::activate_profile(name)
+}
 
-  Ok(())
+#[tauri::command]
+fn list_mcp_servers() -> AppResult<Vec<McpServer>> {
+  mcp::list_mcp_servers()
 }
 
-fn main() {
-  tauri::Builder::default()
-    .invoke_handler(tauri::generate_handler![
-      get_codex_version,
-      get_full_config,
-      get_credentials,
-      list_nodes,
-      switch_node,
-      upsert_node,
-      delete_node,
-      update_node_credential,
-      debug_credentials_info,
-      list_mcp_servers,
-      upsert_mcp_server,
-      delete_mcp_server,
-      read_config_raw,
-      write_config_raw,
-      list_projects,
-      upsert_project,
-      delete_project,
-    ])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+#[tauri::command]
+fn upsert_mcp_server(name: String, command: String, args: Vec<String>) -> AppResult<()> {
+  mcp::upsert_mcp_server(name, command, args)
 }
 
-#[derive(Serialize, Deserialize)]
-struct McpServer {
-  name: String,
-  command: Option<String>,
-  args: Option<Vec<String>>,
+#[tauri::command]
+fn delete_mcp_server(name: String) -> AppResult<()> {
+  mcp::delete_mcp_server(name)
 }
 
 #[tauri::command]
-fn list_mcp_servers() -> Result<Vec<McpServer>, String> {
-  let cfg = read_config_value()?;
-  let mut res = vec![];
-  if let Some(mcp) = cfg.get("mcp_servers").and_then(|v| v.as_table()) {
-    for (name, item) in mcp.iter() {
-      let command = item.get("command").and_then(|v| v.as_str()).map(|s| s.to_string());
-      let args = item.get("args").and_then(|v| v.as_array()).map(|arr| {
-        arr.iter().filter_map(|x| x.as_str().map(|s| s.to_string())).collect::<Vec<_>>()
-      });
-      res.push(McpServer { name: name.clone(), command, args });
-    }
-  }
-  Ok(res)
+fn read_config_raw() -> AppResult<String> {
+  config::read_config_raw()
 }
 
 #[tauri::command]
-fn upsert_mcp_server(name: String, command: String, args: Vec<String>) -> Result<(), String> {
-  let mut cfg = read_config_value()?;
-  let tbl = cfg.as_table_mut().ok_or_else(|| "invalid config root".to_string())?;
-  if !tbl.contains_key("mcp_servers") {
-    tbl.insert("mcp_servers".into(), TomlValue::Table(toml::map::Map::new()));
-  }
-  let mcp = tbl.get_mut("mcp_servers").and_then(|v| v.as_table_mut()).ok_or_else(|| "invalid mcp_servers".to_string())?;
-  let mut entry = toml::map::Map::new();
-  entry.insert("command".into(), TomlValue::String(command));
-  let args_t = TomlValue::Array(args.into_iter().map(|s| TomlValue::String(s)).collect());
-  entry.insert("args".into(), args_t);
-  mcp.insert(name, TomlValue::Table(entry));
-  write_config_value(&cfg)
+fn write_config_raw(content: String) -> AppResult<Option<String>> {
+  config::write_config_raw(content)
 }
 
 #[tauri::command]
-fn delete_mcp_server(name: String) -> Result<(), String> {
-  let mut cfg = read_config_value()?;
-  let tbl = cfg.as_table_mut().ok_or_else(|| "invalid config root".to_string())?;
-  if let Some(mcp) = tbl.get_mut("mcp_servers").and_then(|v| v.as_table_mut()) {
-    mcp.remove(&name);
-  }
-  write_config_value(&cfg)
+fn validate_config(content: String) -> Vec<Diagnostic> {
+  schema::validate_config(&content, version::installed_version().as_ref())
+}
+
+#[tauri::command]
+fn plan_migrations() -> AppResult<MigrationPlan> {
+  migrate::plan_migrations()
 }
 
-#[derive(Serialize, Deserialize)]
-struct ProjectEntry { path: String, trust_level: String }
+#[tauri::command]
+fn apply_migrations() -> AppResult<MigrationPlan> {
+  migrate::apply_migrations()
+}
 
 #[tauri::command]
-fn list_projects() -> Result<Vec<ProjectEntry>, String> {
-  let cfg = read_config_value()?;
-  let mut res = vec![];
-  if let Some(projects) = cfg.get("projects").and_then(|v| v.as_table()) {
-    for (path, item) in projects.iter() {
-      let trust = item.get("trust_level").and_then(|v| v.as_str()).unwrap_or("").to_string();
-      res.push(ProjectEntry { path: path.clone(), trust_level: trust });
-    }
-  }
-  Ok(res)
+fn resolve_config(profile: Option<String>, overrides: Vec<String>) -> AppResult<EffectiveConfig> {
+  effective::resolve_config(profile, overrides)
 }
 
 #[tauri::command]
-fn upsert_project(path: String, trust_level: String) -> Result<(), String> {
-  let mut cfg = read_config_value()?;
-  let tbl = cfg.as_table_mut().ok_or_else(|| "invalid config root".to_string())?;
-  if !tbl.contains_key("projects") {
-    tbl.insert("projects".into(), TomlValue::Table(toml::map::Map::new()));
-  }
-  let projects = tbl.get_mut("projects").and_then(|v| v.as_table_mut()).ok_or_else(|| "invalid projects".to_string())?;
-  let mut entry = toml::map::Map::new();
-  entry.insert("trust_level".into(), TomlValue::String(trust_level));
-  projects.insert(path, TomlValue::Table(entry));
-  write_config_value(&cfg)
+fn list_projects() -> AppResult<Vec<ProjectEntry>> {
+  projects::list_projects()
 }
 
 #[tauri::command]
-fn delete_project(path: String) -> Result<(), String> {
-  let mut cfg = read_config_value()?;
-  if let Some(projects) = cfg.as_table_mut().and_then(|t| t.get_mut("projects")).and_then(|v| v.as_table_mut()) {
-    projects.remove(&path);
-  }
-  write_config_value(&cfg)
+fn upsert_project(path: String, trust_level: String) -> AppResult<()> {
+  projects::upsert_project(path, trust_level)
 }
 
-/// 调试命令：返回 credentials.json 的路径、是否存在、长度、以及文件内容（用于排查写入问题）
 #[tauri::command]
-fn debug_credentials_info() -> Result<serde_json::Value, String> {
-  let path = codex_dir().join("codex-mate").join("credentials.json");
-  let path_str = path.to_string_lossy().to_string();
-  let exists = path.exists();
-  if !exists {
-    eprintln!("[DEBUG] debug_credentials_info: file not exists: {}", path_str);
-    return Ok(serde_json::json!({
-      "path": path_str,
-      "exists": false
-    }));
-  }
-  match fs::read_to_string(&path) {
-    Ok(content) => {
-      let len = content.len();
-      let trimmed_empty = content.trim().is_empty();
-      eprintln!("[DEBUG] debug_credentials_info: exists={}, len={}, trimmed_empty={}", exists, len, trimmed_empty);
-      Ok(serde_json::json!({
-        "path": path_str,
-        "exists": true,
-        "len": len,
-        "trimmed_empty": trimmed_empty,
-        "content": content
-      }))
-    }
-    Err(e) => {
-      eprintln!("[WARN] debug_credentials_info: read failed: {}", e);
-      Ok(serde_json::json!({
-        "path": path_str,
-        "exists": true,
-        "read_error": e.to_string()
-      }))
-    }
+fn delete_project(path: String) -> AppResult<()> {
+  projects::delete_project(path)
+}
+
+#[tauri::command]
+fn list_account_snapshots() -> AppResult<Vec<AccountSnapshot>> {
+  accounts::list_account_snapshots()
+}
+
+#[tauri::command]
+fn save_account_snapshot(name: String) -> AppResult<()> {
+  accounts::save_account_snapshot(name)
+}
+
+#[tauri::command]
+fn restore_account_snapshot(name: String) -> AppResult<()> {
+  accounts::restore_account_snapshot(name)
+}
+
+#[tauri::command]
+fn delete_account_snapshot(name: String) -> AppResult<()> {
+  accounts::delete_account_snapshot(name)
+}
+
+#[tauri::command]
+fn list_backups(file: Option<String>) -> AppResult<Vec<BackupEntry>> {
+  backup::list_backups(file)
+}
+
+#[tauri::command]
+fn diff_backup(id: String) -> AppResult<String> {
+  backup::diff_backup(id)
+}
+
+#[tauri::command]
+fn restore_backup(id: String) -> AppResult<()> {
+  backup::restore_backup(id)
+}
+
+#[tauri::command]
+fn get_backup_settings() -> BackupSettings {
+  backup::get_backup_settings()
+}
+
+#[tauri::command]
+fn set_backup_settings(settings: BackupSettings) -> AppResult<()> {
+  backup::set_backup_settings(settings)
+}
+
+#[tauri::command]
+fn list_codex_homes() -> AppResult<HomeList> {
+  homes::list_codex_homes()
+}
+
+#[tauri::command]
+fn add_codex_home(name: String, path: String) -> AppResult<()> {
+  homes::add_codex_home(name, path)
+}
+
+#[tauri::command]
+fn remove_codex_home(name: String) -> AppResult<()> {
+  homes::remove_codex_home(name)
+}
+
+#[tauri::command]
+fn set_active_codex_home(name: String) -> AppResult<()> {
+  homes::set_active_codex_home(name)
+}
+
+#[tauri::command]
+fn tail_logs(limit: Option<usize>, level: Option<String>) -> AppResult<Vec<LogEntry>> {
+  logging::tail_logs(limit, level)
+}
+
+#[tauri::command]
+fn get_log_settings() -> LogSettings {
+  logging::get_log_settings()
+}
+
+#[tauri::command]
+fn set_log_settings(settings: LogSettings) -> AppResult<()> {
+  logging::set_log_settings(settings)
+}
+
+#[tauri::command]
+fn get_vault_status() -> AppResult<VaultStatus> {
+  vault::get_vault_status()
+}
+
+#[tauri::command]
+fn enable_vault(passphrase: String) -> AppResult<()> {
+  vault::enable_vault(passphrase)
+}
+
+#[tauri::command]
+fn disable_vault(passphrase: String) -> AppResult<()> {
+  vault::disable_vault(passphrase)
+}
+
+#[tauri::command]
+fn unlock_vault(passphrase: String) -> AppResult<()> {
+  vault::unlock_vault(passphrase)
+}
+
+#[tauri::command]
+fn lock_vault() -> AppResult<()> {
+  vault::lock_vault()
+}
+
+#[tauri::command]
+fn audit_permissions(fix: bool) -> AppResult<PermissionReport> {
+  permissions::audit_permissions(fix)
+}
+
+fn main() {
+  homes::init_active_codex_home();
+  logging::init_logging();
+  // config.toml 指向中转时随应用启动中转服务
+  if relay::relay_enabled().unwrap_or(false) {
+    tauri::async_runtime::spawn(async {
+      if let Err(e) = relay::start_relay().await {
+        log::error!("start relay failed: {}", e);
+      }
+    });
   }
+
+  tauri::Builder::default()
+    .invoke_handler(tauri::generate_handler![
+      get_codex_version,
+      get_full_config,
+      list_nodes,
+      switch_node,
+      upsert_node,
+      delete_node,
+      rename_node,
+      duplicate_node,
+      list_presets,
+      apply_preset,
+      set_preset_catalogs,
+      check_providers,
+      list_provider_models,
+      set_model,
+      get_relay_status,
+      enable_relay,
+      disable_relay,
+      set_relay_failover,
+      update_node_credential,
+      credentials_diagnostics,
+      reveal_credential,
+      list_provider_keys,
+      add_provider_key,
+      remove_provider_key,
+      select_provider_key,
+      set_key_rotation,
+      list_profiles,
+      upsert_profile,
+      delete_profile,
+      activate_profile,
+      list_mcp_servers,
+      upsert_mcp_server,
+      delete_mcp_server,
+      read_config_raw,
+      write_config_raw,
+      validate_config,
+      plan_migrations,
+      apply_migrations,
+      resolve_config,
+      list_projects,
+      upsert_project,
+      delete_project,
+      list_account_snapshots,
+      save_account_snapshot,
+      restore_account_snapshot,
+      delete_account_snapshot,
+      list_backups,
+      diff_backup,
+      restore_backup,
+      get_backup_settings,
+      set_backup_settings,
+      list_codex_homes,
+      add_codex_home,
+      remove_codex_home,
+      set_active_codex_home,
+      tail_logs,
+      get_log_settings,
+      set_log_settings,
+      get_vault_status,
+      enable_vault,
+      disable_vault,
+      unlock_vault,
+      lock_vault,
+      audit_permissions,
+    ])
+    .run(tauri::generate_context!())
+    .expect("error while running tauri application");
 }
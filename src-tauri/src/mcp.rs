@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ensure_entry_table, ensure_root_table, read_config_doc, read_config_value, set_value_preserving_decor, write_config_doc};
+use crate::error::AppResult;
+
+#[derive(Serialize, Deserialize)]
+pub struct McpServer {
+  pub name: String,
+  pub command: Option<String>,
+  pub args: Option<Vec<String>>,
+}
+
+pub fn list_mcp_servers() -> AppResult<Vec<McpServer>> {
+  let cfg = read_config_value()?;
+  let mut res = vec![];
+  if let Some(mcp) = cfg.get("mcp_servers").and_then(|v| v.as_table()) {
+    for (name, item) in mcp.iter() {
+      let command = item.get("command").and_then(|v| v.as_str()).map(|s| s.to_string());
+      let args = item.get("args").and_then(|v| v.as_array()).map(|arr| {
+        arr.iter().filter_map(|x| x.as_str().map(|s| s.to_string())).collect::<Vec<_>>()
+      });
+      res.push(McpServer { name: name.clone(), command, args });
+    }
+  }
+  Ok(res)
+}
+
+pub fn upsert_mcp_server(name: String, command: String, args: Vec<String>) -> AppResult<()> {
+  let mut doc = read_config_doc()?;
+  let mcp = ensure_root_table(&mut doc, "mcp_servers")?;
+  // 仅更新 command/args，保留 env 等其他已有字段
+  let entry = ensure_entry_table(mcp, &name)?;
+  set_value_preserving_decor(entry, "command", command.into());
+  set_value_preserving_decor(entry, "args", toml_edit::Value::Array(args.iter().collect()));
+  write_config_doc(&doc)
+}
+
+pub fn delete_mcp_server(name: String) -> AppResult<()> {
+  let mut doc = read_config_doc()?;
+  if let Some(mcp) = doc.get_mut("mcp_servers").and_then(|v| v.as_table_like_mut()) {
+    mcp.remove(&name);
+  }
+  write_config_doc(&doc)
+}
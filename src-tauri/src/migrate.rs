@@ -0,0 +1,305 @@
+use serde::Serialize;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+use crate::config::{display_key_path, ensure_table_path, read_config_raw, write_config_raw};
+use crate::error::{AppError, AppResult};
+use crate::version::{at_least, get_codex_version, parse_version, version_string};
+
+/// 迁移时对取值的转换
+#[derive(Clone, Copy)]
+enum Transform {
+  Same,
+  /// 毫秒整数转换为秒（整除时为整数，否则为小数）
+  MillisToSecs,
+}
+
+/// 一条迁移规则；路径中的 `*` 匹配任意键名，并在新键路径中按顺序代入
+struct Rule {
+  from: &'static str,
+  /// None 表示该键已被移除且没有替代
+  to: Option<&'static str>,
+  /// 开始支持新写法的 Codex 版本；已安装版本更低时不迁移，以免新写法不被识别
+  since: (u64, u64, u64),
+  transform: Transform,
+  note: &'static str,
+}
+
+const fn rule(from: &'static str, to: Option<&'static str>, since: (u64, u64, u64), note: &'static str) -> Rule {
+  Rule { from, to, since, transform: Transform::Same, note }
+}
+
+/// 按 Codex 发布说明整理的已改名 / 移除的配置键
+const RULES: &[Rule] = &[
+  rule("experimental_use_exec_command_tool", Some("features.unified_exec"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("experimental_use_unified_exec_tool", Some("features.unified_exec"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("experimental_use_rmcp_client", Some("features.rmcp_client"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("experimental_use_freeform_apply_patch", Some("features.apply_patch_freeform"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("use_experimental_streamable_shell_tool", Some("features.streamable_shell"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("include_apply_patch_tool", Some("features.apply_patch_freeform"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("tools.web_search", Some("features.web_search_request"), (0, 48, 0), "[tools] toggles moved to [features]"),
+  rule("tools.view_image", Some("features.view_image_tool"), (0, 48, 0), "[tools] toggles moved to [features]"),
+  rule("profiles.*.include_apply_patch_tool", Some("profiles.*.features.apply_patch_freeform"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("profiles.*.experimental_use_unified_exec_tool", Some("profiles.*.features.unified_exec"), (0, 48, 0), "experimental toggles moved to [features]"),
+  rule("profiles.*.experimental_use_rmcp_client", Some("profiles.*.features.rmcp_client"), (0, 48, 0), "experimental toggles moved to [features]"),
+  Rule {
+    from: "mcp_servers.*.startup_timeout_ms",
+    to: Some("mcp_servers.*.startup_timeout_sec"),
+    since: (0, 40, 0),
+    transform: Transform::MillisToSecs,
+    note: "startup timeout is now in seconds",
+  },
+  rule("disable_response_storage", None, (0, 31, 0), "removed, Codex no longer reads this key"),
+  rule("preferred_auth_method", None, (0, 45, 0), "removed; use forced_login_method to restrict the login method"),
+];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct KeyMigration {
+  /// 旧键的点分路径
+  pub from: String,
+  /// 新键的点分路径，None 表示直接删除
+  pub to: Option<String>,
+  /// 开始支持新写法的 Codex 版本
+  pub since: String,
+  pub note: String,
+  /// 已安装的 Codex 是否支持新写法；不支持（或无法获取版本）时不会迁移
+  pub supported: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MigrationPlan {
+  /// codex --version 的输出，未安装时为 None
+  pub codex_version: Option<String>,
+  pub migrations: Vec<KeyMigration>,
+  /// 迁移前后 config.toml 的 unified diff，仅包含 supported 的迁移
+  pub diff: String,
+  /// 应用迁移前的 config.toml 备份 ID（仅 apply_migrations 返回）
+  pub backup: Option<String>,
+}
+
+/// 找出文件中与规则路径匹配的全部键
+fn find_matches(tbl: &dyn TableLike, pattern: &[&str], prefix: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+  let Some((head, rest)) = pattern.split_first() else { return };
+  let keys: Vec<String> = match *head {
+    "*" => tbl.iter().map(|(k, _)| k.to_string()).collect(),
+    k => vec![k.to_string()],
+  };
+  for k in keys {
+    let Some(item) = tbl.get(&k) else { continue };
+    prefix.push(k);
+    if rest.is_empty() {
+      if item.is_value() {
+        out.push(prefix.clone());
+      }
+    } else if let Some(sub) = item.as_table_like() {
+      find_matches(sub, rest, prefix, out);
+    }
+    prefix.pop();
+  }
+}
+
+/// 将新键路径中的 `*` 依次替换为旧键中对应位置匹配到的键名
+fn target_path(from_pattern: &[&str], from: &[String], to_pattern: &str) -> Vec<String> {
+  let mut captured = from_pattern.iter().zip(from).filter(|(p, _)| **p == "*").map(|(_, k)| k.clone());
+  to_pattern.split('.').map(|p| if p == "*" { captured.next().unwrap_or_default() } else { p.to_string() }).collect()
+}
+
+/// 键路径对应已改名或移除的键时返回提示文本，供 config lint 标记并引导用户执行迁移
+pub fn deprecation(path: &[String]) -> Option<String> {
+  RULES.iter().find_map(|rule| {
+    let pattern: Vec<&str> = rule.from.split('.').collect();
+    if pattern.len() != path.len() || !pattern.iter().zip(path).all(|(p, k)| *p == "*" || p == k) {
+      return None;
+    }
+    let key = display_key_path(path);
+    Some(match rule.to {
+      Some(to) => format!(
+        "'{}' is deprecated, use '{}' instead (run `config migrate` to update it)",
+        key,
+        display_key_path(&target_path(&pattern, path, to))
+      ),
+      None => format!("'{}' is no longer read by Codex (run `config migrate` to remove it)", key),
+    })
+  })
+}
+
+fn get_table<'a>(root: &'a dyn TableLike, path: &[String]) -> Option<&'a dyn TableLike> {
+  path.iter().try_fold(root, |tbl, k| tbl.get(k)?.as_table_like())
+}
+
+fn get_table_mut<'a>(root: &'a mut dyn TableLike, path: &[String]) -> Option<&'a mut dyn TableLike> {
+  path.iter().try_fold(root, |tbl, k| tbl.get_mut(k)?.as_table_like_mut())
+}
+
+fn transform_value(item: Item, transform: Transform) -> Item {
+  let Item::Value(mut value) = item else { return item };
+  value.decor_mut().clear();
+  if let (Transform::MillisToSecs, Some(ms)) = (transform, value.as_integer()) {
+    value = if ms % 1000 == 0 { (ms / 1000).into() } else { (ms as f64 / 1000.0).into() };
+  }
+  Item::Value(value)
+}
+
+/// 在文档上执行一条迁移；新键已存在时保留新键的值，只删除旧键
+fn apply_one(doc: &mut DocumentMut, from: &[String], to: Option<&[String]>, transform: Transform) -> AppResult<()> {
+  let (last, parents) = from.split_last().ok_or_else(|| AppError::invalid("empty key path"))?;
+  let Some(old) = get_table_mut(doc.as_table_mut(), parents).and_then(|t| t.remove(last)) else { return Ok(()) };
+
+  if let Some((to_last, to_parents)) = to.and_then(|t| t.split_last()) {
+    let tbl = ensure_table_path(doc.as_table_mut(), to_parents)?;
+    if tbl.get(to_last).is_none() {
+      tbl.insert(to_last, transform_value(old, transform));
+    }
+  }
+
+  // 旧键所在的表（如 [tools]）被清空时一并删除
+  if let Some((parent_last, grand)) = parents.split_last() {
+    let empty = get_table(doc.as_table(), parents).is_some_and(|t| t.is_empty());
+    if empty {
+      if let Some(tbl) = get_table_mut(doc.as_table_mut(), grand) {
+        tbl.remove(parent_last);
+      }
+    }
+  }
+  Ok(())
+}
+
+/// 对 content 执行迁移，返回迁移计划与迁移后的文本；codex_version 为 codex --version 的输出
+fn migrate(content: &str, codex_version: Option<String>) -> AppResult<(MigrationPlan, String)> {
+  let installed = codex_version.as_deref().and_then(parse_version);
+
+  let mut doc = content.parse::<DocumentMut>().map_err(|e| AppError::toml(e.message(), e.span(), content))?;
+  let mut migrations = Vec::new();
+  for rule in RULES {
+    let pattern: Vec<&str> = rule.from.split('.').collect();
+    let mut matches = Vec::new();
+    find_matches(doc.as_table(), &pattern, &mut Vec::new(), &mut matches);
+    for from in matches {
+      let to = rule.to.map(|t| target_path(&pattern, &from, t));
+      let supported = installed.as_ref().is_some_and(|v| at_least(v, rule.since));
+      let mut note = rule.note.to_string();
+      let exists = to
+        .as_deref()
+        .and_then(|t| t.split_last())
+        .is_some_and(|(last, parents)| get_table(doc.as_table(), parents).is_some_and(|t| t.contains_key(last)));
+      if exists {
+        note.push_str(" (new key already set, only the old key is removed)");
+      }
+      if supported {
+        apply_one(&mut doc, &from, to.as_deref(), rule.transform)?;
+      }
+      migrations.push(KeyMigration {
+        from: display_key_path(&from),
+        to: to.as_deref().map(display_key_path),
+        since: version_string(rule.since),
+        note,
+        supported,
+      });
+    }
+  }
+
+  let migrated = doc.to_string();
+  let diff = similar::TextDiff::from_lines(content, &migrated)
+    .unified_diff()
+    .context_radius(3)
+    .header("config.toml", "config.toml (migrated)")
+    .to_string();
+  Ok((MigrationPlan { codex_version, migrations, diff, backup: None }, migrated))
+}
+
+/// 检查 config.toml 中已改名或移除的键，返回迁移计划与预览 diff，不修改文件
+pub fn plan_migrations() -> AppResult<MigrationPlan> {
+  Ok(migrate(&read_config_raw()?, get_codex_version().version)?.0)
+}
+
+/// 应用受支持的迁移；与配置编辑器保存相同，经 write_config_raw 校验后写入，返回的 backup 为本次写入前生成的备份
+pub fn apply_migrations() -> AppResult<MigrationPlan> {
+  let (mut plan, migrated) = migrate(&read_config_raw()?, get_codex_version().version)?;
+  if !plan.migrations.iter().any(|m| m.supported) {
+    return Ok(plan);
+  }
+  plan.backup = write_config_raw(migrated)?;
+  let applied: Vec<&str> = plan.migrations.iter().filter(|m| m.supported).map(|m| m.from.as_str()).collect();
+  log::info!("migrated deprecated config keys: {}", applied.join(", "));
+  Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CONFIG: &str = r#"# my config
+model = "gpt-5"
+experimental_use_rmcp_client = true
+disable_response_storage = true
+
+[tools]
+web_search = true
+
+[mcp_servers.docs]
+command = "npx"
+startup_timeout_ms = 15000
+
+[mcp_servers.slow]
+command = "slow"
+startup_timeout_ms = 2500
+
+[profiles.fast]
+include_apply_patch_tool = true
+"#;
+
+  fn plan(version: &str) -> (MigrationPlan, String) {
+    migrate(CONFIG, Some(format!("codex-cli {}", version))).unwrap()
+  }
+
+  #[test]
+  fn migrates_supported_keys() {
+    let (plan, migrated) = plan("0.50.0");
+    assert!(plan.migrations.iter().all(|m| m.supported));
+    let cfg: toml::Value = toml::from_str(&migrated).unwrap();
+    assert_eq!(cfg["features"]["rmcp_client"].as_bool(), Some(true));
+    assert_eq!(cfg["features"]["web_search_request"].as_bool(), Some(true));
+    assert_eq!(cfg["mcp_servers"]["docs"]["startup_timeout_sec"].as_integer(), Some(15));
+    assert_eq!(cfg["mcp_servers"]["slow"]["startup_timeout_sec"].as_float(), Some(2.5));
+    assert_eq!(cfg["profiles"]["fast"]["features"]["apply_patch_freeform"].as_bool(), Some(true));
+    for removed in ["experimental_use_rmcp_client", "disable_response_storage", "tools"] {
+      assert!(cfg.get(removed).is_none(), "{} should be removed", removed);
+    }
+    assert!(migrated.starts_with("# my config\n"));
+    assert!(plan.diff.contains("-disable_response_storage = true"));
+  }
+
+  #[test]
+  fn skips_keys_the_installed_version_does_not_support() {
+    let (plan, migrated) = plan("0.45.0");
+    let supported: Vec<&str> = plan.migrations.iter().filter(|m| m.supported).map(|m| m.from.as_str()).collect();
+    assert_eq!(supported, ["mcp_servers.docs.startup_timeout_ms", "mcp_servers.slow.startup_timeout_ms", "disable_response_storage"]);
+    assert!(migrated.contains("experimental_use_rmcp_client = true"));
+    assert!(!migrated.contains("[features]"));
+  }
+
+  #[test]
+  fn unknown_version_migrates_nothing() {
+    let (plan, migrated) = migrate(CONFIG, None).unwrap();
+    assert_eq!(plan.migrations.len(), 6);
+    assert!(plan.migrations.iter().all(|m| !m.supported));
+    assert_eq!(migrated, CONFIG);
+    assert!(plan.diff.is_empty());
+  }
+
+  #[test]
+  fn existing_target_keeps_its_value() {
+    let content = "experimental_use_rmcp_client = true\n[features]\nrmcp_client = false\n";
+    let (plan, migrated) = migrate(content, Some("0.50.0".into())).unwrap();
+    assert!(plan.migrations[0].note.contains("new key already set"));
+    assert_eq!(migrated, "[features]\nrmcp_client = false\n");
+  }
+
+  #[test]
+  fn deprecation_messages() {
+    let path = |p: &str| p.split('.').map(str::to_string).collect::<Vec<_>>();
+    assert!(deprecation(&path("tools.view_image")).unwrap().contains("use 'features.view_image_tool' instead"));
+    assert!(deprecation(&path("preferred_auth_method")).unwrap().contains("no longer read by Codex"));
+    assert_eq!(deprecation(&path("model")), None);
+    assert_eq!(deprecation(&path("tools")), None);
+  }
+}
@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::backup::now_millis;
+use crate::config::{ensure_root_table, get_full_config, read_config_doc, set_value_preserving_decor, write_config_doc};
+use crate::error::{AppError, AppResult};
+use crate::health::{error_chain, response_message};
+use crate::http::{client, resolve_endpoint, ProviderEndpoint};
+use crate::nodes::CHATGPT_NODE;
+use crate::storage::{atomic_write, codex_dir};
+use crate::version::path_warning;
+
+/// 节点模型列表缓存: ~/.codex/codex-mate/cache/models.json
+fn cache_path() -> AppResult<PathBuf> {
+  Ok(codex_dir()?.join("codex-mate").join("cache").join("models.json"))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedModels {
+  fetched_at: u64,
+  models: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ModelList {
+  pub provider: String,
+  pub models: Vec<String>,
+  /// 拉取时间（毫秒时间戳）
+  pub fetched_at: u64,
+  /// 是否来自本地缓存
+  pub cached: bool,
+}
+
+/// 读取缓存，文件不存在或损坏时视为空
+fn read_cache() -> BTreeMap<String, CachedModels> {
+  let Ok(path) = cache_path() else {
+    return BTreeMap::new();
+  };
+  match fs::read_to_string(&path) {
+    Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+      log::warn!("parse {} failed ({}), ignoring model cache", path.display(), e);
+      BTreeMap::new()
+    }),
+    Err(_) => BTreeMap::new(),
+  }
+}
+
+fn write_cache(cache: &BTreeMap<String, CachedModels>) -> AppResult<()> {
+  atomic_write(&cache_path()?, &serde_json::to_string_pretty(cache)?)
+}
+
+/// 解析模型列表：兼容 OpenAI 风格的 data[].id 与 Ollama 等使用的 models[].name
+fn parse_models(body: &serde_json::Value) -> Option<Vec<String>> {
+  let items = body
+    .get("data")
+    .or_else(|| body.get("models"))
+    .or(Some(body))
+    .and_then(|v| v.as_array())?;
+  let mut models: Vec<String> = items
+    .iter()
+    .filter_map(|m| match m {
+      serde_json::Value::String(s) => Some(s.as_str()),
+      _ => m.get("id").or_else(|| m.get("name")).and_then(|v| v.as_str()),
+    })
+    .map(str::to_string)
+    .collect();
+  models.sort();
+  models.dedup();
+  Some(models)
+}
+
+async fn fetch_models(name: &str, timeout_ms: Option<u64>) -> AppResult<Vec<String>> {
+  request_models(&resolve_endpoint(name)?, &client(timeout_ms)?).await
+}
+
+async fn request_models(endpoint: &ProviderEndpoint, client: &reqwest::Client) -> AppResult<Vec<String>> {
+  let upstream = |status: Option<u16>, message: String| AppError::Upstream { provider: endpoint.name.clone(), status, message };
+
+  let resp = endpoint
+    .request(client, reqwest::Method::GET, "models")
+    .send()
+    .await
+    .map_err(|e| upstream(None, error_chain(&e)))?;
+  let status = resp.status();
+  let text = resp.text().await.map_err(|e| upstream(Some(status.as_u16()), e.to_string()))?;
+  if !status.is_success() {
+    return Err(upstream(Some(status.as_u16()), response_message(&text)));
+  }
+  let body: serde_json::Value =
+    serde_json::from_str(&text).map_err(|e| upstream(Some(status.as_u16()), format!("invalid JSON from /models: {}", e)))?;
+  parse_models(&body).ok_or_else(|| upstream(Some(status.as_u16()), "unexpected /models response, expected a `data` array".into()))
+}
+
+/// 列出节点可用的模型；默认使用缓存，refresh 为 true 或无缓存时请求 GET {base_url}/models 并更新缓存
+pub async fn list_provider_models(name: String, refresh: bool, timeout_ms: Option<u64>) -> AppResult<ModelList> {
+  if !refresh {
+    if let Some(c) = read_cache().remove(&name) {
+      return Ok(ModelList { provider: name, models: c.models, fetched_at: c.fetched_at, cached: true });
+    }
+  }
+
+  let models = fetch_models(&name, timeout_ms).await?;
+  let fetched_at = now_millis();
+  let mut cache = read_cache();
+  cache.insert(name.clone(), CachedModels { fetched_at, models: models.clone() });
+  write_cache(&cache)?;
+  log::info!("fetched {} models from provider '{}'", models.len(), name);
+  Ok(ModelList { provider: name, models, fetched_at, cached: false })
+}
+
+/// 节点重命名后迁移其模型缓存
+pub(crate) fn rename_cached_models(old: &str, new: &str) -> AppResult<()> {
+  let mut cache = read_cache();
+  match cache.remove(old) {
+    Some(entry) => {
+      cache.insert(new.to_string(), entry);
+      write_cache(&cache)
+    }
+    None => Ok(()),
+  }
+}
+
+/// 设置根级 `model`（或 profiles.<profile>.model），并校验模型名存在于节点的模型列表中
+/// provider 缺省时依次取 profile 的 model_provider、根级 model_provider；
+/// 返回值同 upsert_profile，为已安装的 Codex 版本过低时的提示
+pub async fn set_model(model: String, provider: Option<String>, profile: Option<String>) -> AppResult<Vec<String>> {
+  let model = model.trim().to_string();
+  if model.is_empty() {
+    return Err(AppError::invalid("model name is empty"));
+  }
+
+  let cfg = get_full_config()?;
+  let profile_cfg = match &profile {
+    Some(p) => Some(
+      cfg
+        .get("profiles")
+        .and_then(|v| v.get(p))
+        .ok_or_else(|| AppError::not_found("profile", p))?,
+    ),
+    None => None,
+  };
+  let provider = provider
+    .filter(|s| !s.trim().is_empty())
+    .or_else(|| profile_cfg.and_then(|p| p.get("model_provider")).and_then(|v| v.as_str()).map(str::to_string))
+    .or_else(|| cfg.get("model_provider").and_then(|v| v.as_str()).map(str::to_string))
+    .ok_or_else(|| AppError::invalid("no model_provider is configured, specify the provider explicitly"))?;
+  if provider == CHATGPT_NODE {
+    return Err(AppError::invalid("models of the ChatGPT account node cannot be listed, edit `model` directly"));
+  }
+
+  let mut list = list_provider_models(provider.clone(), false, None).await?;
+  if list.cached && !list.models.contains(&model) {
+    // 缓存可能已过期，重新拉取一次再判断
+    list = list_provider_models(provider.clone(), true, None).await?;
+  }
+  if !list.models.contains(&model) {
+    return Err(AppError::invalid(format!("model '{}' is not offered by provider '{}'", model, provider)));
+  }
+
+  let mut doc = read_config_doc()?;
+  let path: Vec<String> = match &profile {
+    Some(p) => vec!["profiles".into(), p.clone(), "model".into()],
+    None => vec!["model".into()],
+  };
+  match &profile {
+    Some(p) => {
+      let profiles = ensure_root_table(&mut doc, "profiles")?;
+      let tbl = profiles
+        .get_mut(p)
+        .and_then(|v| v.as_table_like_mut())
+        .ok_or_else(|| AppError::config(format!("profile '{}' is not a table", p)))?;
+      set_value_preserving_decor(tbl, "model", model.as_str().into());
+    }
+    None => set_value_preserving_decor(doc.as_table_mut(), "model", model.as_str().into()),
+  }
+  write_config_doc(&doc)?;
+  log::info!("model set to '{}' (provider '{}', profile {:?})", model, provider, profile);
+  Ok(path_warning(&path, Some(&model)).into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock;
+
+  #[test]
+  fn parses_openai_and_ollama_lists() {
+    let openai = serde_json::json!({ "data": [{ "id": "b" }, { "id": "a" }, { "id": "a" }] });
+    assert_eq!(parse_models(&openai), Some(vec!["a".to_string(), "b".to_string()]));
+    let ollama = serde_json::json!({ "models": [{ "name": "llama3" }] });
+    assert_eq!(parse_models(&ollama), Some(vec!["llama3".to_string()]));
+    assert_eq!(parse_models(&serde_json::json!(["x"])), Some(vec!["x".to_string()]));
+    assert_eq!(parse_models(&serde_json::json!({ "object": "list" })), None);
+  }
+
+  #[tokio::test]
+  async fn lists_models_from_mock_server() {
+    let server = mock::serve().await;
+    let models = request_models(&mock::endpoint(&server, "ok", Some(mock::KEY)), &client(None).unwrap()).await.unwrap();
+    assert_eq!(models, ["gpt-a", "gpt-b"]);
+  }
+
+  #[tokio::test]
+  async fn unauthorized_models_request_is_upstream_error() {
+    let server = mock::serve().await;
+    let err = request_models(&mock::endpoint(&server, "ok", Some("sk-wrong")), &client(None).unwrap()).await.unwrap_err();
+    assert!(matches!(err, AppError::Upstream { status: Some(401), ref message, .. } if message == "Incorrect API key provided"));
+  }
+
+  #[tokio::test]
+  async fn malformed_models_response_is_rejected() {
+    let server = mock::serve().await;
+    let client = client(None).unwrap();
+    let err = request_models(&mock::endpoint(&server, "malformed", Some(mock::KEY)), &client).await.unwrap_err();
+    assert!(matches!(err, AppError::Upstream { status: Some(200), ref message, .. } if message.starts_with("invalid JSON from /models")));
+    let err = request_models(&mock::endpoint(&server, "unexpected", Some(mock::KEY)), &client).await.unwrap_err();
+    assert!(matches!(err, AppError::Upstream { status: Some(200), ref message, .. } if message.contains("expected a `data` array")));
+  }
+
+  #[tokio::test]
+  async fn models_request_times_out() {
+    let server = mock::serve().await;
+    let err = request_models(&mock::endpoint(&server, "slow", Some(mock::KEY)), &client(Some(200)).unwrap()).await.unwrap_err();
+    assert!(matches!(err, AppError::Upstream { status: None, .. }));
+  }
+}
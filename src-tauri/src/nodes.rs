@@ -0,0 +1,763 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::auth::{has_chatgpt_tokens, read_auth_value, write_auth_value};
+use crate::config::{
+  ensure_entry_table, ensure_root_table, get_full_config, read_config_doc, read_config_raw, set_value_preserving_decor, write_config_doc,
+  write_config_raw,
+};
+use crate::credentials::{credential_field, env_key_of, get_credentials, put_key, read_credentials_value, stored_key, write_credentials_value, DEFAULT_ENV_KEY};
+use crate::error::{AppError, AppResult};
+use crate::keys::{auth_api_key, read_pool, rotate_for_switch, set_current_key};
+use crate::models::rename_cached_models;
+use crate::relay::{active_node, relay_enabled, rename_relay_refs, set_relay_target, RELAY_NODE};
+use crate::vault::ensure_unlocked;
+
+/// Codex 内置的 OpenAI 提供方 ID，作为 ChatGPT 账号登录节点使用
+pub const CHATGPT_NODE: &str = "openai";
+
+/// 节点类型：API Key 节点（model_providers 中配置）或 ChatGPT 账号登录节点
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+  ApiKey,
+  Chatgpt,
+}
+
+/// Codex 支持的 wire_api 取值
+pub const WIRE_APIS: [&str; 2] = ["responses", "chat"];
+/// Codex 对两类重试次数的上限
+pub const MAX_RETRIES: u64 = 100;
+
+/// config.toml 中 [model_providers.<name>] 的字段（name 固定为节点键名，不在此列）
+///
+/// 用于 upsert_node 时：为 None 的字段保持原值；字符串为空或表为空时删除该字段
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProviderConfig {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub base_url: Option<String>,
+  /// responses 或 chat
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub wire_api: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub requires_openai_auth: Option<bool>,
+  /// 读取 API Key 的环境变量，默认 OPENAI_API_KEY
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub env_key: Option<String>,
+  /// 缺少 env_key 时 Codex 展示给用户的提示
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub env_key_instructions: Option<String>,
+  /// 直接写在 config.toml 中的 Bearer token（Codex 实验字段）
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub experimental_bearer_token: Option<String>,
+  /// 附加到每个请求的查询参数，如 Azure 的 api-version
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub query_params: Option<BTreeMap<String, String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub http_headers: Option<BTreeMap<String, String>>,
+  /// 请求头名 -> 环境变量名，变量存在时才发送
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub env_http_headers: Option<BTreeMap<String, String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub request_max_retries: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub stream_max_retries: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub stream_idle_timeout_ms: Option<u64>,
+  /// 未识别的字段：读取时忽略，写入时拒绝（name 除外）
+  #[serde(flatten, skip_serializing)]
+  pub unknown: BTreeMap<String, serde_json::Value>,
+}
+
+fn is_env_name(s: &str) -> bool {
+  let mut chars = s.chars();
+  chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl ProviderConfig {
+  /// 读取 config.toml 中某个节点的字段；类型不符的字段记录警告后跳过，其余字段照常读取
+  pub fn from_config(cfg: &serde_json::Value, name: &str) -> Self {
+    let (config, errors) = Self::parse(cfg, name);
+    for e in errors {
+      log::warn!("model_providers.{}: {}", name, e);
+    }
+    config
+  }
+
+  /// 同 from_config，同时返回被跳过的字段及原因，用于在节点列表中逐个节点展示
+  pub fn parse(cfg: &serde_json::Value, name: &str) -> (Self, Vec<String>) {
+    let Some(item) = cfg.get("model_providers").and_then(|v| v.get(name)) else {
+      return (Self::default(), Vec::new());
+    };
+    let Some(fields) = item.as_object() else {
+      return (Self::default(), vec!["expected a table".to_string()]);
+    };
+    // 逐个字段试解析，定位出错的字段，避免一个字段出错导致整个节点的配置被丢弃
+    let mut errors = Vec::new();
+    let mut valid = fields.clone();
+    valid.retain(|k, v| {
+      let single = serde_json::Value::Object([(k.clone(), v.clone())].into_iter().collect());
+      match serde_json::from_value::<Self>(single) {
+        Ok(_) => true,
+        Err(e) => {
+          errors.push(format!("{}: {}", k, e));
+          false
+        }
+      }
+    });
+    (serde_json::from_value(serde_json::Value::Object(valid)).unwrap_or_default(), errors)
+  }
+
+  /// 校验字段取值，错误信息指出具体字段
+  pub fn validate(&self) -> AppResult<()> {
+    let invalid = |field: &str, why: String| Err(AppError::invalid(format!("{}: {}", field, why)));
+    if let Some(field) = self.unknown.keys().find(|k| k.as_str() != "name") {
+      return invalid(field, "unknown provider field".into());
+    }
+    if let Some(url) = self.base_url.as_deref().map(str::trim) {
+      if !(url.starts_with("http://") || url.starts_with("https://")) || reqwest::Url::parse(url).is_err() {
+        return invalid("base_url", format!("'{}' is not an http(s) URL", url));
+      }
+    }
+    if let Some(wire) = self.wire_api.as_deref().filter(|w| !WIRE_APIS.contains(w)) {
+      return invalid("wire_api", format!("'{}' is not one of {}", wire, WIRE_APIS.join(", ")));
+    }
+    if let Some(env_key) = self.env_key.as_deref().filter(|k| !k.is_empty() && !is_env_name(k)) {
+      return invalid("env_key", format!("'{}' is not a valid environment variable name", env_key));
+    }
+    if let Some(params) = &self.query_params {
+      if params.keys().any(|k| k.trim().is_empty()) {
+        return invalid("query_params", "parameter names must not be empty".into());
+      }
+    }
+    for (field, headers) in [("http_headers", &self.http_headers), ("env_http_headers", &self.env_http_headers)] {
+      for (k, v) in headers.iter().flatten() {
+        if reqwest::header::HeaderName::from_bytes(k.as_bytes()).is_err() {
+          return invalid(field, format!("'{}' is not a valid header name", k));
+        }
+        let ok = match field {
+          "env_http_headers" => is_env_name(v),
+          _ => reqwest::header::HeaderValue::from_str(v).is_ok(),
+        };
+        if !ok {
+          return invalid(field, format!("invalid value for header '{}'", k));
+        }
+      }
+    }
+    for (field, value) in [("request_max_retries", self.request_max_retries), ("stream_max_retries", self.stream_max_retries)] {
+      if value.is_some_and(|n| n > MAX_RETRIES) {
+        return invalid(field, format!("must be at most {}", MAX_RETRIES));
+      }
+    }
+    if self.stream_idle_timeout_ms == Some(0) {
+      return invalid("stream_idle_timeout_ms", "must be greater than 0".into());
+    }
+    Ok(())
+  }
+
+  /// 将非空字段写入节点表，保留未修改字段的注释与格式
+  fn apply(&self, tbl: &mut dyn toml_edit::TableLike) {
+    fn put(tbl: &mut dyn toml_edit::TableLike, key: &str, value: Option<toml_edit::Value>) {
+      match value {
+        Some(v) => set_value_preserving_decor(tbl, key, v),
+        None => {
+          tbl.remove(key);
+        }
+      }
+    }
+    fn text(s: &str) -> Option<toml_edit::Value> {
+      Some(s.trim()).filter(|s| !s.is_empty()).map(Into::into)
+    }
+    fn table(m: &BTreeMap<String, String>) -> Option<toml_edit::Value> {
+      (!m.is_empty()).then(|| toml_edit::Value::InlineTable(m.iter().map(|(k, v)| (k.as_str(), toml_edit::Value::from(v.as_str()))).collect()))
+    }
+    fn int(n: u64) -> Option<toml_edit::Value> {
+      Some(toml_edit::Value::from(n.min(i64::MAX as u64) as i64))
+    }
+    let strings = [
+      ("base_url", &self.base_url),
+      ("wire_api", &self.wire_api),
+      ("env_key", &self.env_key),
+      ("env_key_instructions", &self.env_key_instructions),
+      ("experimental_bearer_token", &self.experimental_bearer_token),
+    ];
+    for (key, value) in strings {
+      if let Some(v) = value {
+        put(tbl, key, text(v));
+      }
+    }
+    if let Some(v) = self.requires_openai_auth {
+      put(tbl, "requires_openai_auth", Some(v.into()));
+    }
+    for (key, value) in [("query_params", &self.query_params), ("http_headers", &self.http_headers), ("env_http_headers", &self.env_http_headers)] {
+      if let Some(m) = value {
+        put(tbl, key, table(m));
+      }
+    }
+    let numbers = [
+      ("request_max_retries", self.request_max_retries),
+      ("stream_max_retries", self.stream_max_retries),
+      ("stream_idle_timeout_ms", self.stream_idle_timeout_ms),
+    ];
+    for (key, value) in numbers {
+      if let Some(n) = value {
+        put(tbl, key, int(n));
+      }
+    }
+  }
+}
+
+#[derive(Serialize)]
+pub struct ProviderInfo {
+  pub name: String,
+  pub kind: NodeKind,
+  pub base_url: Option<String>,
+  pub wire_api: Option<String>,
+  pub requires_openai_auth: Option<bool>,
+  /// 凭据对应的环境变量名，未配置 env_key 时为 OPENAI_API_KEY
+  pub env_key: String,
+  pub has_credential: bool,
+  /// 密钥池中的密钥数量，未使用密钥池时为 0
+  pub key_count: usize,
+  /// 节点的完整配置（ChatGPT 账号节点为空）
+  pub config: ProviderConfig,
+  /// config.toml 中无法解析而被跳过的字段，如 "request_max_retries: invalid type: string \"3\", expected u64"
+  pub errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct NodeList {
+  /// 当前生效的节点；中转模式下为中转目标
+  pub current_provider: Option<String>,
+  /// config.toml 是否指向本地中转
+  pub relay: bool,
+  pub providers: Vec<ProviderInfo>,
+}
+
+/// 列出所有节点及其状态
+/// 返回: 当前激活的节点和所有节点列表
+pub fn list_nodes() -> AppResult<NodeList> {
+  // 1. 读取配置文件
+  let cfg = get_full_config()?;
+  
+  // 2. 读取凭据文件
+  let creds = get_credentials()?;
+
+  // 3. 获取当前激活的节点（中转模式下为中转目标）
+  let model_provider = cfg.get("model_provider").and_then(|v| v.as_str()).map(|s| s.to_string());
+  let relay = model_provider.as_deref() == Some(RELAY_NODE);
+  let current_provider = active_node(model_provider);
+
+  // 4. ChatGPT 账号节点：auth.json 中存在登录 tokens 即视为有凭据
+  let auth = read_auth_value()?;
+  let mut providers: Vec<ProviderInfo> = vec![ProviderInfo {
+    name: CHATGPT_NODE.to_string(),
+    kind: NodeKind::Chatgpt,
+    base_url: None,
+    wire_api: None,
+    requires_openai_auth: Some(true),
+    env_key: DEFAULT_ENV_KEY.to_string(),
+    has_credential: has_chatgpt_tokens(&auth),
+    key_count: 0,
+    config: ProviderConfig::default(),
+    errors: Vec::new(),
+  }];
+
+  // 5. 遍历所有配置的节点
+  if let Some(mps) = cfg.get("model_providers").and_then(|v| v.as_object()) {
+    for name in mps.keys().filter(|name| name.as_str() != CHATGPT_NODE && name.as_str() != RELAY_NODE) {
+      let (config, errors) = ProviderConfig::parse(&cfg, name);
+      let base_url = config.base_url.clone();
+      let wire_api = config.wire_api.clone();
+      let requires_openai_auth = config.requires_openai_auth;
+      
+      // 检查该节点是否有凭据（按节点的 env_key 查找）
+      let env_key = env_key_of(&cfg, name);
+      let fields = creds.get(name).and_then(|v| v.as_object());
+      let has_credential = fields.and_then(|f| stored_key(f, &env_key)).is_some();
+      let key_count = fields.map(|f| read_pool(f).keys.len()).unwrap_or(0);
+
+      providers.push(ProviderInfo {
+        name: name.clone(),
+        kind: NodeKind::ApiKey,
+        base_url,
+        wire_api,
+        requires_openai_auth,
+        env_key,
+        has_credential,
+        key_count,
+        config,
+        errors,
+      });
+    }
+  }
+
+  log::debug!("list_nodes: {} providers, current = {:?}", providers.len(), current_provider);
+  Ok(NodeList { current_provider, relay, providers })
+}
+
+/// 切换节点；中转模式下切换 API Key 节点只改变中转目标，无需重启 Codex。
+/// 返回需要用户处理的提示：节点的 env_key 不是 OPENAI_API_KEY 时，Codex 需从环境变量读取密钥
+/// （中转模式下密钥由中转携带，不需要导出环境变量）
+pub fn switch_node(name: String) -> AppResult<Option<String>> {
+  if name != CHATGPT_NODE && relay_enabled()? {
+    ensure_relay_credential(&name)?;
+    set_relay_target(name)?;
+    return Ok(None);
+  }
+  let warning = activate_node(&name)?;
+  log::info!("switched to node '{}'", name);
+  Ok(warning)
+}
+
+/// 中转转发需要节点已保存的密钥（或配置的 experimental_bearer_token），缺失时与直接切换一样报错，
+/// 避免切换成功后请求全部以 401 失败
+fn ensure_relay_credential(name: &str) -> AppResult<()> {
+  let cfg = get_full_config()?;
+  if cfg.get("model_providers").and_then(|v| v.get(name)).is_none() {
+    return Err(AppError::not_found("provider", name));
+  }
+  let env_key = credential_field(name)?;
+  let creds = read_credentials_value()?;
+  let stored = creds.get(name).and_then(|v| v.as_object()).and_then(|f| stored_key(f, &env_key)).is_some();
+  let bearer = ProviderConfig::from_config(&cfg, name).experimental_bearer_token.is_some_and(|t| !t.trim().is_empty());
+  if !stored && !bearer {
+    return Err(AppError::CredentialMissing { provider: name.to_string() });
+  }
+  Ok(())
+}
+
+/// 密钥无法经 auth.json 提供给 Codex 时的提示，给出需要导出的环境变量
+pub(crate) fn env_key_warning(name: &str, env_key: &str) -> String {
+  format!(
+    "node '{}' reads its key from ${}, which Codex does not load from auth.json; export it before starting Codex \
+     (eval \"$(codex-mate-cli credentials env {})\") or enable the relay",
+    name, env_key, name
+  )
+}
+
+/// 将节点写入 auth.json 与 config.toml 的 model_provider；
+/// 节点使用其他 env_key 时 auth.json 中不保留密钥，返回提示用户导出该环境变量的警告
+pub(crate) fn activate_node(name: &str) -> AppResult<Option<String>> {
+  if name == RELAY_NODE {
+    return Err(AppError::ReservedName { name: name.to_string() });
+  }
+  // 合并写入 auth.json：保留 ChatGPT 登录的 tokens / last_refresh 等字段
+  let mut auth = read_auth_value()?;
+  let mut warning = None;
+  if name == CHATGPT_NODE {
+    if !has_chatgpt_tokens(&auth) {
+      return Err(AppError::ChatgptLoginMissing);
+    }
+    // 清空 API Key，Codex 将使用 tokens 登录态
+    auth.insert(DEFAULT_ENV_KEY.into(), serde_json::Value::Null);
+  } else {
+    // 读取凭据（容错：空文件/损坏文件均返回空 Map）；轮询策略下先换用下一个密钥
+    let env_key = credential_field(name)?;
+    let mut creds_map = read_credentials_value()?;
+    if rotate_for_switch(&mut creds_map, name, &env_key)? {
+      write_credentials_value(&creds_map)?;
+    }
+    let key = creds_map
+      .get(name)
+      .and_then(|v| v.as_object())
+      .and_then(|f| stored_key(f, &env_key))
+      .ok_or_else(|| AppError::CredentialMissing { provider: name.to_string() })?;
+    // 启用加密时仅在此处解密，明文只写入 Codex 读取的 auth.json；其他 env_key 由环境变量提供
+    auth.insert(DEFAULT_ENV_KEY.into(), auth_api_key(&env_key, Some(key))?);
+    if env_key != DEFAULT_ENV_KEY {
+      let text = env_key_warning(name, &env_key);
+      log::warn!("{}", text);
+      warning = Some(text);
+    }
+  }
+  write_auth_value(&auth)?;
+
+  // update config.toml model_provider（只改写这一行，保留其余内容原样）
+  let mut doc = read_config_doc()?;
+  set_value_preserving_decor(doc.as_table_mut(), "model_provider", name.into());
+  write_config_doc(&doc)?;
+  Ok(warning)
+}
+
+/// 新增或更新节点：provider_fields 中给出的字段逐项写入，其余字段保持原样
+pub fn upsert_node(name: String, provider_fields: ProviderConfig, credential: Option<String>) -> AppResult<()> {
+  if name == CHATGPT_NODE || name == RELAY_NODE {
+    return Err(AppError::ReservedName { name });
+  }
+  provider_fields.validate()?;
+  if credential.as_deref().is_some_and(|k| !k.trim().is_empty()) {
+    ensure_unlocked()?;
+  }
+  let mut doc = read_config_doc()?;
+  let mps = ensure_root_table(&mut doc, "model_providers")?;
+
+  // ensure base_url exists when creating new
+  let is_new = mps.get(&name).is_none();
+  let old_env_key = if is_new { None } else { Some(credential_field(&name)?) };
+  if is_new && provider_fields.base_url.as_deref().is_none_or(|s| s.trim().is_empty()) {
+    return Err(AppError::invalid("base_url is required for new provider"));
+  }
+
+  let provider_tbl = ensure_entry_table(mps, &name)?;
+
+  // enforce required fields（name 固定为节点键名）
+  set_value_preserving_decor(provider_tbl, "name", name.as_str().into());
+
+  // 将 provider_fields 逐键就地更新到 provider_tbl
+  provider_fields.apply(provider_tbl);
+
+  // 仅新建节点时补默认 wire_api，编辑已有节点不增加原本没有的行
+  if is_new && provider_tbl.get("wire_api").is_none() {
+    provider_tbl.insert("wire_api", toml_edit::value("responses"));
+  }
+
+  write_config_doc(&doc)?;
+
+  // 修改 env_key 时将已保存的密钥移到新字段，旧字段下的凭据不会失联
+  let env_key = credential_field(&name)?;
+  if let Some(old_env_key) = old_env_key.filter(|k| *k != env_key) {
+    let mut map = read_credentials_value()?;
+    let moved = map.get_mut(&name).and_then(|v| v.as_object_mut()).and_then(|fields| {
+      let key = stored_key(fields, &old_env_key)?.to_string();
+      put_key(fields, &env_key, key);
+      Some(())
+    });
+    if moved.is_some() {
+      write_credentials_value(&map)?;
+      log::info!("moved credential of '{}' from {} to {}", name, old_env_key, env_key);
+    }
+  }
+
+  if let Some(key) = credential {
+    let trimmed_key = key.trim().to_string();
+    if !trimmed_key.is_empty() {
+      let mut map = read_credentials_value()?;
+      set_current_key(&mut map, &name, trimmed_key)?;
+      write_credentials_value(&map)?;
+      // 回读校验
+      let verify = read_credentials_value()?;
+      let ok = verify.get(&name).and_then(|v| v.as_object()).and_then(|f| stored_key(f, &env_key)).is_some();
+      if !ok {
+        log::warn!("upsert_node: credential for '{}' not found after write", name);
+      }
+    } else {
+      log::warn!("upsert_node: provided credential for '{}' is empty, skipping write", name);
+    }
+  }
+
+  log::info!("saved node '{}'", name);
+  Ok(())
+}
+
+/// 校验 rename / duplicate 的源节点与新节点名，返回去除空白后的新名称
+fn check_copy_target(doc: &toml_edit::DocumentMut, name: &str, new_name: &str) -> AppResult<String> {
+  let new_name = new_name.trim().to_string();
+  for n in [name, new_name.as_str()] {
+    if n == CHATGPT_NODE || n == RELAY_NODE {
+      return Err(AppError::ReservedName { name: n.to_string() });
+    }
+  }
+  if new_name.is_empty() {
+    return Err(AppError::invalid("new node name is empty"));
+  }
+  let mps = doc.get("model_providers").and_then(|v| v.as_table_like());
+  if mps.and_then(|m| m.get(name)).is_none() {
+    return Err(AppError::not_found("provider", name));
+  }
+  if mps.and_then(|m| m.get(&new_name)).is_some() {
+    return Err(AppError::invalid(format!("node '{}' already exists", new_name)));
+  }
+  Ok(new_name)
+}
+
+/// 先写 config.toml 再写 credentials.json；后者失败时恢复 config.toml，两个文件保持一致
+fn commit_node_change(original_config: String, doc: &toml_edit::DocumentMut, creds: &serde_json::Map<String, serde_json::Value>) -> AppResult<()> {
+  write_config_doc(doc)?;
+  if let Err(e) = write_credentials_value(creds) {
+    log::error!("write credentials failed ({}), restoring config.toml", e);
+    write_config_raw(original_config)?;
+    return Err(e);
+  }
+  Ok(())
+}
+
+/// 重命名节点：移动 model_providers 表与凭据，并更新根级与各 profile 的 model_provider 引用
+pub fn rename_node(name: String, new_name: String) -> AppResult<()> {
+  let original = read_config_raw()?;
+  let mut doc = read_config_doc()?;
+  let new_name = check_copy_target(&doc, &name, &new_name)?;
+
+  // 原位替换键名：Table 保留自身的位置信息，重命名后仍在原处
+  let mps = ensure_root_table(&mut doc, "model_providers")?;
+  let mut item = mps.remove(&name).ok_or_else(|| AppError::not_found("provider", &name))?;
+  if let Some(tbl) = item.as_table_like_mut() {
+    set_value_preserving_decor(tbl, "name", new_name.as_str().into());
+  }
+  mps.insert(&new_name, item);
+
+  if doc.get("model_provider").and_then(|v| v.as_str()) == Some(name.as_str()) {
+    set_value_preserving_decor(doc.as_table_mut(), "model_provider", new_name.as_str().into());
+  }
+  let mut profiles_updated = 0;
+  if let Some(profiles) = doc.get_mut("profiles").and_then(|v| v.as_table_like_mut()) {
+    for (_, profile) in profiles.iter_mut() {
+      let Some(tbl) = profile.as_table_like_mut() else { continue };
+      if tbl.get("model_provider").and_then(|v| v.as_str()) == Some(name.as_str()) {
+        set_value_preserving_decor(tbl, "model_provider", new_name.as_str().into());
+        profiles_updated += 1;
+      }
+    }
+  }
+
+  let mut creds = read_credentials_value()?;
+  if let Some(entry) = creds.remove(&name) {
+    creds.insert(new_name.clone(), entry);
+  }
+  commit_node_change(original, &doc, &creds)?;
+
+  // 附属状态：失败不影响重命名结果
+  if let Err(e) = rename_relay_refs(&name, &new_name) {
+    log::warn!("update relay settings after rename failed: {}", e);
+  }
+  if let Err(e) = rename_cached_models(&name, &new_name) {
+    log::warn!("move model cache after rename failed: {}", e);
+  }
+  log::info!("renamed node '{}' to '{}' ({} profiles updated)", name, new_name, profiles_updated);
+  Ok(())
+}
+
+/// 复制节点：复制 model_providers 表（含注释）与凭据、密钥池，不修改任何引用
+pub fn duplicate_node(name: String, new_name: String) -> AppResult<()> {
+  let original = read_config_raw()?;
+  let mut doc = read_config_doc()?;
+  let new_name = check_copy_target(&doc, &name, &new_name)?;
+
+  let mps = ensure_root_table(&mut doc, "model_providers")?;
+  let mut item = mps.get(&name).cloned().ok_or_else(|| AppError::not_found("provider", &name))?;
+  if let Some(tbl) = item.as_table_like_mut() {
+    set_value_preserving_decor(tbl, "name", new_name.as_str().into());
+  }
+  mps.insert(&new_name, item);
+
+  let mut creds = read_credentials_value()?;
+  if let Some(entry) = creds.get(&name).cloned() {
+    creds.insert(new_name.clone(), entry);
+  }
+  commit_node_change(original, &doc, &creds)?;
+  log::info!("duplicated node '{}' as '{}'", name, new_name);
+  Ok(())
+}
+
+pub fn delete_node(name: String, force: bool) -> AppResult<()> {
+  if name == CHATGPT_NODE || name == RELAY_NODE {
+    return Err(AppError::ReservedName { name });
+  }
+  let mut doc = read_config_doc()?;
+  let current = active_node(doc.get("model_provider").and_then(|v| v.as_str()).map(|s| s.to_string()));
+  if !force {
+    if let Some(cur) = current {
+      if cur == name { return Err(AppError::ActiveProviderDelete { provider: name }); }
+    }
+  }
+
+  if let Some(mps) = doc.get_mut("model_providers").and_then(|v| v.as_table_like_mut()) {
+    mps.remove(&name);
+  }
+  write_config_doc(&doc)?;
+
+  let mut map = read_credentials_value()?;
+  map.remove(&name);
+  write_credentials_value(&map)?;
+  log::info!("deleted node '{}'", name);
+  Ok(())
+}
+
+/// 更新指定节点的凭据
+/// 参数:
+///   - name: 节点名称 (例如: "packycode", "openai-chat-completions")
+///   - openai_api_key: API 密钥，保存在节点 env_key 对应的字段下
+pub fn update_node_credential(name: String, openai_api_key: String) -> AppResult<()> {
+  let trimmed_name = name.trim().to_string();
+  let trimmed_key = openai_api_key.trim().to_string();
+
+  if trimmed_name.is_empty() {
+    return Err(AppError::invalid("provider name is empty"));
+  }
+  if trimmed_name == CHATGPT_NODE || trimmed_name == RELAY_NODE {
+    return Err(AppError::ReservedName { name: trimmed_name });
+  }
+  if trimmed_key.is_empty() {
+    return Err(AppError::invalid("credential is empty"));
+  }
+
+  // 1. 读取现有凭据
+  let mut map = read_credentials_value()?;
+
+  // 2. 插入或更新指定节点的凭据（保留密钥池等其他字段）
+  set_current_key(&mut map, &trimmed_name, trimmed_key)?;
+
+  // 3. 写入文件
+  write_credentials_value(&map)?;
+
+  // 4. 回读校验，确保落盘成功
+  let verify = read_credentials_value()?;
+  let env_key = credential_field(&trimmed_name)?;
+  let ok = verify
+    .get(&trimmed_name)
+    .and_then(|v| v.as_object())
+    .and_then(|f| stored_key(f, &env_key))
+    .is_some();
+  if ok {
+    log::info!("updated credential for node '{}'", trimmed_name);
+  } else {
+    log::warn!("update_node_credential: credential for '{}' not found after write", trimmed_name);
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::test_support::with_codex_home;
+
+  const FORMATTED: &str = r#"# Codex 配置，手工排版
+model = 'gpt-5'   # 单引号
+model_provider = "a"
+model_reasoning_effort = "high"
+
+[model_providers.a]
+name = 'a'
+base_url = 'https://a.example/v1'  # 主节点
+wire_api   =   "responses"
+request_max_retries = 0x10
+
+# 备用节点
+[model_providers.b]
+name = "b"
+base_url = """https://b.example/v1"""
+stream_idle_timeout_ms = 300_000
+"#;
+
+  const CREDENTIALS: &str = r#"{ "a": { "OPENAI_API_KEY": "sk-a" }, "b": { "OPENAI_API_KEY": "sk-b" } }"#;
+
+  fn config_text(home: &std::path::Path) -> String {
+    std::fs::read_to_string(home.join("config.toml")).unwrap()
+  }
+
+  /// before 与 after 逐行比较，返回发生变化的 (原行, 新行)
+  fn changed_lines(before: &str, after: &str) -> Vec<(String, String)> {
+    assert_eq!(before.lines().count(), after.lines().count(), "{}", after);
+    before.lines().zip(after.lines()).filter(|(a, b)| a != b).map(|(a, b)| (a.to_string(), b.to_string())).collect()
+  }
+
+  #[test]
+  fn invalid_fields_are_skipped_per_node() {
+    let cfg = serde_json::json!({
+      "model_providers": {
+        "a": { "name": "a", "base_url": "https://a.example/v1", "request_max_retries": "3", "wire_api": 1 },
+        "b": { "base_url": "https://b.example/v1", "experimental_bearer_token": "t" },
+        "c": "oops",
+      }
+    });
+    let (a, errors) = ProviderConfig::parse(&cfg, "a");
+    assert_eq!(a.base_url.as_deref(), Some("https://a.example/v1"));
+    assert_eq!(a.request_max_retries, None);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("request_max_retries: "));
+    assert!(errors[1].starts_with("wire_api: "));
+
+    let (b, errors) = ProviderConfig::parse(&cfg, "b");
+    assert!(errors.is_empty());
+    assert_eq!(b.experimental_bearer_token.as_deref(), Some("t"));
+    assert!(b.validate().is_ok());
+
+    assert_eq!(ProviderConfig::parse(&cfg, "c").1, ["expected a table"]);
+    assert!(ProviderConfig::parse(&cfg, "missing").1.is_empty());
+  }
+
+  #[test]
+  fn validate_rejects_unknown_fields() {
+    let config: ProviderConfig = serde_json::from_value(serde_json::json!({ "name": "a", "base_urll": "x" })).unwrap();
+    assert!(matches!(config.validate(), Err(AppError::InvalidInput { ref message }) if message.starts_with("base_urll:")));
+  }
+
+  #[test]
+  fn edits_leave_untouched_lines_byte_identical() {
+    with_codex_home(&[("config.toml", FORMATTED), ("codex-mate/credentials.json", CREDENTIALS)], |home| {
+      switch_node("b".into()).unwrap();
+      let after_switch = config_text(home);
+      assert_eq!(changed_lines(FORMATTED, &after_switch), [("model_provider = \"a\"".to_string(), "model_provider = \"b\"".to_string())]);
+
+      // 重新写入相同的取值不改变原有写法
+      let same = ProviderConfig { base_url: Some("https://a.example/v1".into()), wire_api: Some("responses".into()), request_max_retries: Some(16), ..Default::default() };
+      upsert_node("a".into(), same, None).unwrap();
+      assert_eq!(config_text(home), after_switch);
+
+      let moved = ProviderConfig { base_url: Some("https://b2.example/v1".into()), ..Default::default() };
+      upsert_node("b".into(), moved, None).unwrap();
+      assert_eq!(
+        changed_lines(&after_switch, &config_text(home)),
+        [("base_url = \"\"\"https://b.example/v1\"\"\"".to_string(), "base_url = \"https://b2.example/v1\"".to_string())]
+      );
+    });
+  }
+
+  const PROFILES: &str = r#"
+[profiles.fast]
+model_provider = "a"
+model = "gpt-5-mini"
+
+[profiles.other]
+model_provider = "b"
+"#;
+
+  #[test]
+  fn rename_updates_every_reference_and_moves_credentials() {
+    let config = format!("{}{}", FORMATTED, PROFILES);
+    with_codex_home(&[("config.toml", &config), ("codex-mate/credentials.json", CREDENTIALS)], |home| {
+      rename_node("a".into(), "main".into()).unwrap();
+
+      let doc = read_config_doc().unwrap();
+      assert_eq!(doc["model_provider"].as_str(), Some("main"));
+      assert_eq!(doc["profiles"]["fast"]["model_provider"].as_str(), Some("main"));
+      assert_eq!(doc["profiles"]["other"]["model_provider"].as_str(), Some("b"));
+      assert!(doc["model_providers"].get("a").is_none());
+      assert_eq!(doc["model_providers"]["main"]["name"].as_str(), Some("main"));
+      assert_eq!(doc["model_providers"]["main"]["base_url"].as_str(), Some("https://a.example/v1"));
+      // 表保持原位，注释随之保留
+      let text = config_text(home);
+      assert!(text.find("[model_providers.main]").unwrap() < text.find("[model_providers.b]").unwrap());
+      assert!(text.contains("base_url = 'https://a.example/v1'  # 主节点"));
+
+      let creds = read_credentials_value().unwrap();
+      assert!(creds.get("a").is_none());
+      assert_eq!(creds["main"]["OPENAI_API_KEY"], "sk-a");
+      assert_eq!(creds["b"]["OPENAI_API_KEY"], "sk-b");
+
+      assert!(matches!(rename_node("main".into(), "b".into()), Err(AppError::InvalidInput { .. })));
+      assert!(matches!(rename_node("missing".into(), "c".into()), Err(AppError::NotFound { .. })));
+    });
+  }
+
+  #[test]
+  fn rename_restores_config_when_credentials_write_fails() {
+    let config = format!("{}{}", FORMATTED, PROFILES);
+    // 在 credentials.json 的备份目录位置放一个普通文件，使凭据写入在备份阶段失败
+    let files = [
+      ("config.toml", config.as_str()),
+      ("codex-mate/credentials.json", CREDENTIALS),
+      ("codex-mate/backups/codex-mate/credentials.json", "not a directory"),
+    ];
+    with_codex_home(&files, |home| {
+      assert!(rename_node("a".into(), "main".into()).is_err());
+      assert_eq!(config_text(home), config);
+      assert_eq!(std::fs::read_to_string(home.join("codex-mate/credentials.json")).unwrap(), CREDENTIALS);
+
+      assert!(duplicate_node("a".into(), "copy".into()).is_err());
+      assert_eq!(config_text(home), config);
+    });
+  }
+}
@@ -0,0 +1,192 @@
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+use crate::error::AppResult;
+use crate::storage::codex_dir;
+
+/// 含密钥文件的权限：仅所有者可读写
+pub const SECRET_FILE_MODE: u32 = 0o600;
+/// codex-mate 目录权限：仅所有者可访问
+pub const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// 是否为含密钥的文件：auth.json、config.toml（可能含中转访问令牌、节点的 experimental_bearer_token 与请求头）
+/// 以及 codex-mate 目录下的所有文件（凭据、账号快照、备份、日志等）
+pub fn is_secret_path(path: &Path) -> bool {
+  let Ok(root) = codex_dir() else {
+    return false;
+  };
+  match path.strip_prefix(&root) {
+    Ok(rel) => rel == Path::new("auth.json") || rel == Path::new("config.toml") || rel.starts_with("codex-mate"),
+    Err(_) => false,
+  }
+}
+
+/// 是否为需要仅所有者访问的目录：codex-mate 目录及其子目录；Codex 目录本身由用户管理，只检查组/其他用户写权限
+fn is_private_dir(path: &Path) -> bool {
+  let Ok(root) = codex_dir() else {
+    return false;
+  };
+  path.starts_with(root.join("codex-mate"))
+}
+
+/// 创建目录；位于 codex-mate 下的目录以 0700 创建，并收紧已存在的 codex-mate 目录
+pub fn create_dir_all(path: &Path) -> io::Result<()> {
+  if !is_secret_path(path) {
+    return fs::create_dir_all(path);
+  }
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    fs::DirBuilder::new().recursive(true).mode(PRIVATE_DIR_MODE).create(path)?;
+    if let Ok(dir) = codex_dir().map(|root| root.join("codex-mate")) {
+      let mode = fs::metadata(&dir)?.permissions().mode() & 0o777;
+      if mode & 0o077 != 0 {
+        fs::set_permissions(&dir, fs::Permissions::from_mode(PRIVATE_DIR_MODE))?;
+      }
+    }
+    Ok(())
+  }
+  #[cfg(not(unix))]
+  {
+    fs::create_dir_all(path)
+  }
+}
+
+/// 以指定权限打开（必要时创建）文件，secret 为 true 时新文件权限为 0600
+pub fn open_options(secret: bool) -> OpenOptions {
+  let mut options = OpenOptions::new();
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    if secret {
+      options.mode(SECRET_FILE_MODE);
+    }
+  }
+  #[cfg(not(unix))]
+  let _ = (&mut options, secret);
+  options
+}
+
+/// 创建新文件：秘密文件为 0600，其余文件沿用 like 指定的已有文件权限（保持替换前后一致）
+pub fn create_file(path: &Path, secret: bool, like: Option<&Path>) -> io::Result<File> {
+  let file = open_options(secret).write(true).create(true).truncate(true).open(path)?;
+  if secret {
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      file.set_permissions(fs::Permissions::from_mode(SECRET_FILE_MODE))?;
+    }
+  } else if let Some(perm) = like.and_then(|p| fs::metadata(p).ok()).map(|m| m.permissions()) {
+    file.set_permissions(perm)?;
+  }
+  Ok(file)
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PermissionIssue {
+  pub path: String,
+  pub is_dir: bool,
+  /// 当前权限，如 "0644"；无法读取该路径时为 None
+  pub mode: Option<String>,
+  /// 建议权限
+  pub expected: Option<String>,
+  pub fixed: bool,
+  /// 读取或修复失败的原因
+  pub error: Option<String>,
+}
+
+impl PermissionIssue {
+  /// 无法读取的路径：记为一条问题，不影响其余路径的检查
+  fn unreadable(path: &Path, is_dir: bool, e: io::Error) -> Self {
+    PermissionIssue { path: path.display().to_string(), is_dir, mode: None, expected: None, fixed: false, error: Some(e.to_string()) }
+  }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PermissionReport {
+  /// 当前平台是否支持 Unix 权限检查
+  pub supported: bool,
+  pub checked: usize,
+  pub issues: Vec<PermissionIssue>,
+}
+
+/// 审计 Codex 目录下的文件权限，fix 为 true 时同时修复
+/// 规则：密钥文件（含 auth.json、config.toml）应为 0600，codex-mate 目录应为 0700，其余文件和目录（含 Codex 目录本身）不应允许组/其他用户写入
+#[cfg(unix)]
+pub fn audit_permissions(fix: bool) -> AppResult<PermissionReport> {
+  use std::os::unix::fs::PermissionsExt;
+
+  let root = codex_dir()?;
+  let mut report = PermissionReport { supported: true, checked: 0, issues: Vec::new() };
+  if !root.exists() {
+    return Ok(report);
+  }
+
+  let mut stack = vec![root];
+  while let Some(path) = stack.pop() {
+    // 不跟随符号链接，避免修改 Codex 目录之外的文件
+    let meta = match fs::symlink_metadata(&path) {
+      Ok(meta) => meta,
+      Err(e) => {
+        report.issues.push(PermissionIssue::unreadable(&path, false, e));
+        continue;
+      }
+    };
+    if meta.file_type().is_symlink() {
+      continue;
+    }
+    let is_dir = meta.is_dir();
+    if is_dir {
+      match fs::read_dir(&path) {
+        Ok(entries) => stack.extend(entries.flatten().map(|e| e.path())),
+        Err(e) => {
+          report.issues.push(PermissionIssue::unreadable(&path, true, e));
+          continue;
+        }
+      }
+    }
+    report.checked += 1;
+
+    let mode = meta.permissions().mode() & 0o777;
+    let expected = if is_dir && is_private_dir(&path) {
+      mode & !0o077 | PRIVATE_DIR_MODE
+    } else if !is_dir && is_secret_path(&path) {
+      mode & !0o077 | SECRET_FILE_MODE
+    } else {
+      mode & !0o022
+    };
+    if mode == expected {
+      continue;
+    }
+
+    let mut issue = PermissionIssue {
+      path: path.display().to_string(),
+      is_dir,
+      mode: Some(format!("{:04o}", mode)),
+      expected: Some(format!("{:04o}", expected)),
+      fixed: false,
+      error: None,
+    };
+    if fix {
+      match fs::set_permissions(&path, fs::Permissions::from_mode(expected)) {
+        Ok(()) => issue.fixed = true,
+        Err(e) => issue.error = Some(e.to_string()),
+      }
+    }
+    report.issues.push(issue);
+  }
+
+  report.issues.sort_by(|a, b| a.path.cmp(&b.path));
+  if fix {
+    let fixed = report.issues.iter().filter(|i| i.fixed).count();
+    log::info!("permissions audit fixed {} of {} issues", fixed, report.issues.len());
+  }
+  Ok(report)
+}
+
+#[cfg(not(unix))]
+pub fn audit_permissions(_fix: bool) -> AppResult<PermissionReport> {
+  Ok(PermissionReport { supported: false, checked: 0, issues: Vec::new() })
+}
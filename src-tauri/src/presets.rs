@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult};
+use crate::nodes::{upsert_node, ProviderConfig};
+use crate::settings::{read_settings, write_settings};
+use crate::storage::codex_dir;
+
+/// 内置模板，格式与团队模板文件相同
+const BUILTIN_CATALOG: &str = include_str!("presets.toml");
+const BUILTIN_SOURCE: &str = "builtin";
+
+/// 默认的本地模板文件: ~/.codex/codex-mate/presets.toml，存在时自动加载
+pub fn local_catalog_path() -> AppResult<PathBuf> {
+  Ok(codex_dir()?.join("codex-mate").join("presets.toml"))
+}
+
+/// 额外加载的团队模板文件（如放在共享仓库中的 presets.toml），按顺序覆盖同 id 的模板
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct PresetSettings {
+  pub catalogs: Vec<String>,
+}
+
+/// 模板中需要填写的变量，在字段中以 {name} 引用
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PresetVariable {
+  pub name: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  /// 有默认值的变量无需填写
+  #[serde(default)]
+  pub default: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProviderPreset {
+  pub id: String,
+  pub label: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  /// 实例化时的默认节点名，未设置时使用 id
+  #[serde(default)]
+  pub node_name: Option<String>,
+  /// 未提供密钥时写入的占位密钥，用于 Ollama 等无需鉴权的本地服务
+  #[serde(default)]
+  pub default_key: Option<String>,
+  pub fields: ProviderConfig,
+  #[serde(default)]
+  pub variables: Vec<PresetVariable>,
+  /// 模板来源：builtin 或模板文件路径
+  #[serde(default, skip_deserializing)]
+  pub source: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct CatalogFile {
+  presets: Vec<ProviderPreset>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PresetCatalog {
+  pub presets: Vec<ProviderPreset>,
+  /// 已加载的模板文件（不含内置模板）
+  pub catalogs: Vec<String>,
+  /// 无法加载的模板文件或模板，不影响其余模板
+  pub errors: Vec<String>,
+}
+
+fn parse_catalog(content: &str, source: &str) -> AppResult<Vec<ProviderPreset>> {
+  let file: CatalogFile = toml::from_str(content).map_err(|e| AppError::toml(e.message(), e.span(), content))?;
+  let mut presets = Vec::new();
+  for mut preset in file.presets {
+    if preset.id.trim().is_empty() {
+      return Err(AppError::config("preset id must not be empty"));
+    }
+    if let Some(field) = preset.fields.unknown.keys().next() {
+      return Err(AppError::config(format!("preset '{}': unknown provider field '{}'", preset.id, field)));
+    }
+    preset.source = source.to_string();
+    presets.push(preset);
+  }
+  Ok(presets)
+}
+
+fn read_catalog(path: &Path) -> AppResult<Vec<ProviderPreset>> {
+  let content = fs::read_to_string(path).map_err(|e| AppError::io("read", path, e))?;
+  parse_catalog(&content, &path.display().to_string())
+}
+
+/// 列出全部模板：内置模板、本地模板文件、设置中的团队模板文件，后加载的覆盖同 id 的模板
+pub fn list_presets() -> AppResult<PresetCatalog> {
+  let mut presets = parse_catalog(BUILTIN_CATALOG, BUILTIN_SOURCE)?;
+  let mut catalogs = Vec::new();
+  let mut errors = Vec::new();
+
+  let local = local_catalog_path()?;
+  let files = local.exists().then_some(local).into_iter().chain(read_settings().presets.catalogs.into_iter().map(PathBuf::from));
+  for path in files {
+    match read_catalog(&path) {
+      Ok(loaded) => {
+        for preset in loaded {
+          match presets.iter_mut().find(|p| p.id == preset.id) {
+            Some(existing) => *existing = preset,
+            None => presets.push(preset),
+          }
+        }
+        catalogs.push(path.display().to_string());
+      }
+      Err(e) => {
+        log::warn!("load preset catalog {} failed: {}", path.display(), e);
+        errors.push(format!("{}: {}", path.display(), e));
+      }
+    }
+  }
+  Ok(PresetCatalog { presets, catalogs, errors })
+}
+
+/// 设置团队模板文件列表；保存前逐个解析，有错误时不保存
+pub fn set_preset_catalogs(paths: Vec<String>) -> AppResult<PresetCatalog> {
+  let mut catalogs: Vec<String> = Vec::new();
+  for path in paths.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+    let path = fs::canonicalize(path).map_err(|e| AppError::io("read", Path::new(path), e))?;
+    read_catalog(&path).map_err(|e| AppError::config(format!("{}: {}", path.display(), e)))?;
+    let path = path.display().to_string();
+    if !catalogs.contains(&path) {
+      catalogs.push(path);
+    }
+  }
+  let mut settings = read_settings();
+  settings.presets.catalogs = catalogs;
+  write_settings(&settings)?;
+  log::info!("preset catalogs set to {:?}", settings.presets.catalogs);
+  list_presets()
+}
+
+impl ProviderPreset {
+  /// 需要用户填写的变量：没有默认值且未在 values 中给出
+  pub fn missing_variables(&self, values: &BTreeMap<String, String>) -> Vec<&PresetVariable> {
+    self
+      .variables
+      .iter()
+      .filter(|v| v.default.is_none() && values.get(&v.name).is_none_or(|s| s.trim().is_empty()))
+      .collect()
+  }
+
+  /// 代入变量得到节点字段
+  fn render(&self, values: &BTreeMap<String, String>) -> AppResult<ProviderConfig> {
+    let missing: Vec<&str> = self.missing_variables(values).iter().map(|v| v.name.as_str()).collect();
+    if !missing.is_empty() {
+      return Err(AppError::invalid(format!("preset '{}' needs values for: {}", self.id, missing.join(", "))));
+    }
+    if let Some(unknown) = values.keys().find(|k| !self.variables.iter().any(|v| &v.name == *k)) {
+      return Err(AppError::invalid(format!("preset '{}' has no variable '{}'", self.id, unknown)));
+    }
+    let vars: Vec<(String, String)> = self
+      .variables
+      .iter()
+      .map(|v| {
+        let value = values.get(&v.name).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        (format!("{{{}}}", v.name), value.or_else(|| v.default.clone()).unwrap_or_default())
+      })
+      .collect();
+
+    fn substitute(value: &mut serde_json::Value, vars: &[(String, String)]) {
+      match value {
+        serde_json::Value::String(s) => {
+          for (placeholder, v) in vars {
+            *s = s.replace(placeholder, v);
+          }
+        }
+        serde_json::Value::Object(obj) => obj.values_mut().for_each(|v| substitute(v, vars)),
+        _ => {}
+      }
+    }
+    let mut fields = serde_json::to_value(&self.fields)?;
+    substitute(&mut fields, &vars);
+    Ok(serde_json::from_value(fields)?)
+  }
+}
+
+/// 用模板创建或更新节点，返回节点名
+///   - name: 节点名，默认为模板的 node_name 或 id
+///   - values: 变量取值，有默认值的变量可省略
+///   - credential: API Key，未提供时使用模板的占位密钥（如有）
+pub fn apply_preset(id: String, name: Option<String>, values: BTreeMap<String, String>, credential: Option<String>) -> AppResult<String> {
+  let catalog = list_presets()?;
+  let preset = catalog.presets.iter().find(|p| p.id == id).ok_or_else(|| AppError::not_found("preset", &id))?;
+  let fields = preset.render(&values)?;
+  let name = name
+    .map(|n| n.trim().to_string())
+    .filter(|n| !n.is_empty())
+    .or_else(|| preset.node_name.clone())
+    .unwrap_or_else(|| preset.id.clone());
+  let credential = credential.filter(|k| !k.trim().is_empty()).or_else(|| preset.default_key.clone());
+  upsert_node(name.clone(), fields, credential)?;
+  log::info!("created node '{}' from preset '{}' ({})", name, preset.id, preset.source);
+  Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::test_support::with_codex_home;
+
+  fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+  }
+
+  fn builtin(id: &str) -> ProviderPreset {
+    parse_catalog(BUILTIN_CATALOG, BUILTIN_SOURCE).unwrap().into_iter().find(|p| p.id == id).unwrap()
+  }
+
+  #[test]
+  fn builtin_catalog_is_valid() {
+    let presets = parse_catalog(BUILTIN_CATALOG, BUILTIN_SOURCE).unwrap();
+    for id in ["openai-api", "azure", "openrouter", "ollama", "lmstudio", "packycode", "anyrouter", "aihubmix", "relay"] {
+      assert!(presets.iter().any(|p| p.id == id), "{}", id);
+    }
+    for preset in &presets {
+      let defaults: BTreeMap<String, String> =
+        preset.variables.iter().map(|v| (v.name.clone(), v.default.clone().unwrap_or_else(|| "https://x.example/v1".into()))).collect();
+      let fields = preset.render(&defaults).unwrap();
+      assert!(fields.validate().is_ok(), "{}", preset.id);
+    }
+  }
+
+  #[test]
+  fn substitutes_variables_and_azure_api_version() {
+    let azure = builtin("azure");
+    assert_eq!(azure.missing_variables(&BTreeMap::new()).iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), ["resource"]);
+    let fields = azure.render(&values(&[("resource", "myres")])).unwrap();
+    assert_eq!(fields.base_url.as_deref(), Some("https://myres.openai.azure.com/openai"));
+    assert_eq!(fields.query_params, Some(values(&[("api-version", "2025-04-01-preview")])));
+
+    let fields = azure.render(&values(&[("resource", "myres"), ("api_version", " 2024-10-21 ")])).unwrap();
+    assert_eq!(fields.query_params, Some(values(&[("api-version", "2024-10-21")])));
+
+    let err = azure.render(&BTreeMap::new()).unwrap_err();
+    assert_eq!(err.to_string(), "preset 'azure' needs values for: resource");
+    let err = azure.render(&values(&[("resource", "r"), ("region", "x")])).unwrap_err();
+    assert_eq!(err.to_string(), "preset 'azure' has no variable 'region'");
+  }
+
+  #[test]
+  fn rejects_invalid_catalogs() {
+    let err = parse_catalog("[[presets]]\nid = \" \"\nlabel = \"x\"\n[presets.fields]\n", "team").unwrap_err();
+    assert!(err.to_string().contains("preset id must not be empty"));
+    let err = parse_catalog("[[presets]]\nid = \"x\"\nlabel = \"x\"\n[presets.fields]\nbase_urll = \"y\"\n", "team").unwrap_err();
+    assert!(err.to_string().contains("unknown provider field 'base_urll'"));
+  }
+
+  const LOCAL: &str = r#"
+[[presets]]
+id = "openrouter"
+label = "OpenRouter (team)"
+[presets.fields]
+base_url = "https://openrouter.internal/v1"
+wire_api = "chat"
+"#;
+
+  const TEAM: &str = r#"
+[[presets]]
+id = "internal"
+label = "Internal gateway"
+node_name = "gateway"
+default_key = "unused"
+[presets.fields]
+base_url = "https://{host}/v1"
+[[presets.variables]]
+name = "host"
+
+[[presets]]
+id = "openrouter"
+label = "OpenRouter (shared repo)"
+[presets.fields]
+base_url = "https://openrouter.shared/v1"
+wire_api = "chat"
+"#;
+
+  #[test]
+  fn team_catalogs_override_by_id_in_load_order() {
+    with_codex_home(&[("config.toml", ""), ("codex-mate/presets.toml", LOCAL), ("team/presets.toml", TEAM)], |home| {
+      let local = list_presets().unwrap();
+      let openrouter = local.presets.iter().find(|p| p.id == "openrouter").unwrap();
+      assert_eq!(openrouter.label, "OpenRouter (team)");
+      assert_eq!(local.catalogs.len(), 1);
+
+      let team = home.join("team/presets.toml").display().to_string();
+      let catalog = set_preset_catalogs(vec![team.clone()]).unwrap();
+      let openrouter = catalog.presets.iter().find(|p| p.id == "openrouter").unwrap();
+      assert_eq!(openrouter.label, "OpenRouter (shared repo)");
+      assert!(catalog.presets.iter().any(|p| p.id == "internal" && p.source.ends_with("team/presets.toml")));
+      assert!(catalog.presets.iter().any(|p| p.id == "azure" && p.source == BUILTIN_SOURCE));
+      assert!(catalog.errors.is_empty());
+
+      // 无法解析的团队模板文件不会被保存
+      std::fs::write(home.join("team/broken.toml"), "[[presets]]\nid = 1\n").unwrap();
+      assert!(set_preset_catalogs(vec![home.join("team/broken.toml").display().to_string()]).is_err());
+      assert_eq!(read_settings().presets.catalogs.len(), 1);
+    });
+  }
+
+  #[test]
+  fn apply_preset_writes_the_node() {
+    with_codex_home(&[("config.toml", "")], |home| {
+      let name = apply_preset("azure".into(), Some("az".into()), values(&[("resource", "myres")]), Some("sk-az".into())).unwrap();
+      assert_eq!(name, "az");
+      let config = std::fs::read_to_string(home.join("config.toml")).unwrap();
+      assert!(config.contains("base_url = \"https://myres.openai.azure.com/openai\""), "{}", config);
+      assert!(config.contains("query_params = { api-version = \"2025-04-01-preview\" }"), "{}", config);
+
+      assert_eq!(apply_preset("ollama".into(), None, BTreeMap::new(), None).unwrap(), "ollama");
+      let creds = std::fs::read_to_string(home.join("codex-mate/credentials.json")).unwrap();
+      assert!(creds.contains("\"ollama\""));
+      assert!(matches!(apply_preset("missing".into(), None, BTreeMap::new(), None), Err(AppError::NotFound { .. })));
+    });
+  }
+}
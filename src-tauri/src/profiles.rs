@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use toml_edit::DocumentMut;
+
+use crate::config::{ensure_entry_table, ensure_root_table, get_full_config, read_config_doc, set_value_preserving_decor, write_config_doc};
+use crate::error::{AppError, AppResult};
+use crate::nodes::CHATGPT_NODE;
+use crate::version::path_warning;
+
+/// Codex 内置、无需在 model_providers 中声明的提供方
+pub const BUILTIN_PROVIDERS: [&str; 2] = [CHATGPT_NODE, "oss"];
+pub const APPROVAL_POLICIES: [&str; 4] = ["untrusted", "on-failure", "on-request", "never"];
+pub const SANDBOX_MODES: [&str; 3] = ["read-only", "workspace-write", "danger-full-access"];
+pub const REASONING_EFFORTS: [&str; 6] = ["none", "minimal", "low", "medium", "high", "xhigh"];
+pub const REASONING_SUMMARIES: [&str; 4] = ["auto", "concise", "detailed", "none"];
+pub const VERBOSITIES: [&str; 3] = ["low", "medium", "high"];
+
+/// config.toml 中 [profiles.<name>] 的常用字段
+///
+/// 用于 upsert_profile 时：为 None 的字段保持原值，空字符串删除该字段；其他未列出的字段原样保留
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProfileConfig {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub model: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub model_provider: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub approval_policy: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sandbox_mode: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub model_reasoning_effort: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub model_reasoning_summary: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub model_verbosity: Option<String>,
+  /// 其他字段：列出时原样返回，写入时拒绝（请在配置编辑器中修改）
+  #[serde(flatten)]
+  pub other: BTreeMap<String, serde_json::Value>,
+}
+
+impl ProfileConfig {
+  fn fields(&self) -> [(&'static str, &Option<String>); 7] {
+    [
+      ("model", &self.model),
+      ("model_provider", &self.model_provider),
+      ("approval_policy", &self.approval_policy),
+      ("sandbox_mode", &self.sandbox_mode),
+      ("model_reasoning_effort", &self.model_reasoning_effort),
+      ("model_reasoning_summary", &self.model_reasoning_summary),
+      ("model_verbosity", &self.model_verbosity),
+    ]
+  }
+
+  /// 解析 config.toml 中的一个 profile，类型不符的字段被跳过，同时返回这些字段及原因
+  pub fn parse(item: &serde_json::Value) -> (Self, Vec<String>) {
+    let Some(fields) = item.as_object() else {
+      return (Self::default(), vec!["expected a table".to_string()]);
+    };
+    // 逐个字段试解析，一个字段出错不影响其余字段的展示与后续编辑
+    let mut errors = Vec::new();
+    let mut valid = fields.clone();
+    valid.retain(|k, v| {
+      let single = serde_json::Value::Object([(k.clone(), v.clone())].into_iter().collect());
+      match serde_json::from_value::<Self>(single) {
+        Ok(_) => true,
+        Err(e) => {
+          errors.push(format!("{}: {}", k, e));
+          false
+        }
+      }
+    });
+    (serde_json::from_value(serde_json::Value::Object(valid)).unwrap_or_default(), errors)
+  }
+
+  /// 校验取值；model_provider 须为已配置的节点或 Codex 内置提供方
+  pub fn validate(&self, cfg: &serde_json::Value) -> AppResult<()> {
+    if let Some(field) = self.other.keys().next() {
+      return Err(AppError::invalid(format!("{}: not editable here, use the config editor", field)));
+    }
+    let allowed: [(&str, &[&str]); 5] = [
+      ("approval_policy", &APPROVAL_POLICIES),
+      ("sandbox_mode", &SANDBOX_MODES),
+      ("model_reasoning_effort", &REASONING_EFFORTS),
+      ("model_reasoning_summary", &REASONING_SUMMARIES),
+      ("model_verbosity", &VERBOSITIES),
+    ];
+    for (field, value) in self.fields() {
+      let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) else { continue };
+      if let Some((_, values)) = allowed.iter().find(|(f, _)| *f == field) {
+        if !values.contains(&value) {
+          return Err(AppError::invalid(format!("{}: '{}' is not one of {}", field, value, values.join(", "))));
+        }
+      }
+      if field == "model_provider"
+        && !BUILTIN_PROVIDERS.contains(&value)
+        && cfg.get("model_providers").and_then(|v| v.get(value)).is_none()
+      {
+        return Err(AppError::not_found("provider", value));
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileInfo {
+  pub name: String,
+  /// 是否为根级 `profile` 指定的默认 profile
+  pub active: bool,
+  pub config: ProfileConfig,
+  /// 类型不符、已被跳过的字段及原因
+  pub errors: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileList {
+  /// 根级 `profile` 键的值
+  pub default_profile: Option<String>,
+  pub profiles: Vec<ProfileInfo>,
+}
+
+pub fn list_profiles() -> AppResult<ProfileList> {
+  let cfg = get_full_config()?;
+  let default_profile = cfg.get("profile").and_then(|v| v.as_str()).map(str::to_string);
+  let profiles = cfg
+    .get("profiles")
+    .and_then(|v| v.as_object())
+    .map(|m| {
+      m.iter()
+        .map(|(name, item)| {
+          let (config, errors) = ProfileConfig::parse(item);
+          for e in &errors {
+            log::warn!("profiles.{}: {}", name, e);
+          }
+          ProfileInfo { name: name.clone(), active: default_profile.as_deref() == Some(name.as_str()), config, errors }
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  Ok(ProfileList { default_profile, profiles })
+}
+
+/// 新增或更新 profile：fields 中给出的字段逐项写入，其余字段保持原样；
+/// 返回已安装的 Codex 版本过低、无法识别所写字段时的提示
+pub fn upsert_profile(name: String, fields: ProfileConfig) -> AppResult<Vec<String>> {
+  let name = name.trim().to_string();
+  if name.is_empty() {
+    return Err(AppError::invalid("profile name is empty"));
+  }
+  fields.validate(&get_full_config()?)?;
+
+  let mut doc = read_config_doc()?;
+  let warnings = apply_profile(&mut doc, &name, &fields)?;
+  write_config_doc(&doc)?;
+  log::info!("saved profile '{}'", name);
+  Ok(warnings)
+}
+
+/// 将 fields 写入 doc 的 [profiles.<name>]：None 保持原值，空字符串删除该字段
+fn apply_profile(doc: &mut DocumentMut, name: &str, fields: &ProfileConfig) -> AppResult<Vec<String>> {
+  let profiles = ensure_root_table(doc, "profiles")?;
+  let tbl = ensure_entry_table(profiles, name)?;
+  let mut warnings = Vec::new();
+  for (key, value) in fields.fields() {
+    match value.as_deref().map(str::trim) {
+      Some("") => {
+        tbl.remove(key);
+      }
+      Some(v) => {
+        set_value_preserving_decor(tbl, key, v.into());
+        warnings.extend(path_warning(&["profiles".into(), name.to_string(), key.into()], Some(v)));
+      }
+      None => {}
+    }
+  }
+  warnings.dedup();
+  Ok(warnings)
+}
+
+/// 删除 profile；删除默认 profile 需 force，并同时移除根级 `profile` 键
+pub fn delete_profile(name: String, force: bool) -> AppResult<()> {
+  let mut doc = read_config_doc()?;
+  remove_profile(&mut doc, &name, force)?;
+  write_config_doc(&doc)?;
+  log::info!("deleted profile '{}'", name);
+  Ok(())
+}
+
+fn remove_profile(doc: &mut DocumentMut, name: &str, force: bool) -> AppResult<()> {
+  let is_default = doc.get("profile").and_then(|v| v.as_str()) == Some(name);
+  if is_default && !force {
+    return Err(AppError::invalid(format!("profile '{}' is the default profile, use force to delete it", name)));
+  }
+  let removed = doc
+    .get_mut("profiles")
+    .and_then(|v| v.as_table_like_mut())
+    .and_then(|p| p.remove(name))
+    .is_some();
+  if !removed {
+    return Err(AppError::not_found("profile", name));
+  }
+  if is_default {
+    doc.remove("profile");
+  }
+  Ok(())
+}
+
+/// 设置根级 `profile` 键，Codex 未指定 --profile 时使用该 profile；None 表示移除
+pub fn activate_profile(name: Option<String>) -> AppResult<()> {
+  let mut doc = read_config_doc()?;
+  match name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+    Some(name) => {
+      if doc.get("profiles").and_then(|v| v.get(&name)).is_none() {
+        return Err(AppError::not_found("profile", &name));
+      }
+      set_value_preserving_decor(doc.as_table_mut(), "profile", name.as_str().into());
+      log::info!("default profile set to '{}'", name);
+    }
+    None => {
+      doc.remove("profile");
+      log::info!("default profile cleared");
+    }
+  }
+  write_config_doc(&doc)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FIXTURE: &str = r#"profile = "fast"
+
+[profiles.fast]
+model = "gpt-5-codex" # 常用模型
+model_reasoning_effort = "low"
+approval_policy = "never"
+
+[profiles.slow]
+model = "gpt-5"
+"#;
+
+  fn doc() -> DocumentMut {
+    FIXTURE.parse().unwrap()
+  }
+
+  fn fields(pairs: &[(&str, &str)]) -> ProfileConfig {
+    let map: serde_json::Map<String, serde_json::Value> = pairs.iter().map(|(k, v)| (k.to_string(), (*v).into())).collect();
+    serde_json::from_value(serde_json::Value::Object(map)).unwrap()
+  }
+
+  #[test]
+  fn invalid_fields_are_skipped_per_profile() {
+    let item = serde_json::json!({ "model": "gpt-5", "model_reasoning_effort": 1, "sandbox_mode": true, "tools": { "web_search": true } });
+    let (config, errors) = ProfileConfig::parse(&item);
+    assert_eq!(config.model.as_deref(), Some("gpt-5"));
+    assert_eq!(config.model_reasoning_effort, None);
+    assert!(config.other.contains_key("tools"));
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("model_reasoning_effort: "));
+    assert!(errors[1].starts_with("sandbox_mode: "));
+    assert_eq!(ProfileConfig::parse(&serde_json::json!("oops")).1, ["expected a table"]);
+  }
+
+  #[test]
+  fn validates_enums_providers_and_other_keys() {
+    let cfg = serde_json::json!({ "model_providers": { "packy": {} } });
+    assert!(fields(&[("model_provider", "packy"), ("sandbox_mode", "read-only")]).validate(&cfg).is_ok());
+    assert!(fields(&[("model_provider", "oss")]).validate(&cfg).is_ok());
+    // 空字符串表示删除，不校验取值
+    assert!(fields(&[("approval_policy", "")]).validate(&cfg).is_ok());
+    let err = fields(&[("approval_policy", "sometimes")]).validate(&cfg).unwrap_err();
+    assert_eq!(err.to_string(), "approval_policy: 'sometimes' is not one of untrusted, on-failure, on-request, never");
+    assert!(matches!(fields(&[("model_provider", "missing")]).validate(&cfg), Err(AppError::NotFound { .. })));
+    let err = fields(&[("tools", "x")]).validate(&cfg).unwrap_err();
+    assert_eq!(err.to_string(), "tools: not editable here, use the config editor");
+  }
+
+  #[test]
+  fn upsert_sets_removes_and_keeps_fields() {
+    let mut doc = doc();
+    apply_profile(&mut doc, "fast", &fields(&[("model", "gpt-5"), ("model_reasoning_effort", ""), ("sandbox_mode", "read-only")])).unwrap();
+    let out = doc.to_string();
+    assert!(out.contains("model = \"gpt-5\" # 常用模型\n"), "{}", out);
+    assert!(!out.contains("model_reasoning_effort"));
+    assert!(out.contains("approval_policy = \"never\"\n"));
+    assert!(out.contains("sandbox_mode = \"read-only\"\n"));
+    assert!(out.ends_with("[profiles.slow]\nmodel = \"gpt-5\"\n"));
+
+    apply_profile(&mut doc, "new", &fields(&[("model", "o3")])).unwrap();
+    assert!(doc.to_string().ends_with("[profiles.new]\nmodel = \"o3\"\n"));
+  }
+
+  #[test]
+  fn deleting_the_default_profile_needs_force() {
+    let mut doc = doc();
+    let err = remove_profile(&mut doc, "fast", false).unwrap_err();
+    assert_eq!(err.to_string(), "profile 'fast' is the default profile, use force to delete it");
+    assert_eq!(doc.to_string(), FIXTURE);
+
+    remove_profile(&mut doc, "slow", false).unwrap();
+    assert!(!doc.to_string().contains("slow"));
+    remove_profile(&mut doc, "fast", true).unwrap();
+    assert!(doc.get("profile").is_none());
+    assert!(matches!(remove_profile(&mut doc, "fast", true), Err(AppError::NotFound { .. })));
+  }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ensure_entry_table, ensure_root_table, read_config_doc, read_config_value, set_value_preserving_decor, write_config_doc};
+use crate::error::{AppError, AppResult};
+
+#[derive(Serialize, Deserialize)]
+pub struct ProjectEntry { pub path: String, pub trust_level: String }
+
+pub const TRUST_LEVELS: [&str; 2] = ["trusted", "untrusted"];
+
+pub fn list_projects() -> AppResult<Vec<ProjectEntry>> {
+  let cfg = read_config_value()?;
+  let mut res = vec![];
+  if let Some(projects) = cfg.get("projects").and_then(|v| v.as_table()) {
+    for (path, item) in projects.iter() {
+      let trust = item.get("trust_level").and_then(|v| v.as_str()).unwrap_or("").to_string();
+      res.push(ProjectEntry { path: path.clone(), trust_level: trust });
+    }
+  }
+  Ok(res)
+}
+
+/// 新增或更新项目的信任级别，trust_level 必须是 TRUST_LEVELS 之一
+pub fn upsert_project(path: String, trust_level: String) -> AppResult<()> {
+  if path.trim().is_empty() {
+    return Err(AppError::invalid("project path is required"));
+  }
+  if !TRUST_LEVELS.contains(&trust_level.as_str()) {
+    return Err(AppError::invalid(format!("trust_level: '{}' is not one of {}", trust_level, TRUST_LEVELS.join(", "))));
+  }
+  let mut doc = read_config_doc()?;
+  let projects = ensure_root_table(&mut doc, "projects")?;
+  let entry = ensure_entry_table(projects, &path)?;
+  set_value_preserving_decor(entry, "trust_level", trust_level.into());
+  write_config_doc(&doc)
+}
+
+pub fn delete_project(path: String) -> AppResult<()> {
+  let mut doc = read_config_doc()?;
+  if let Some(projects) = doc.get_mut("projects").and_then(|v| v.as_table_like_mut()) {
+    projects.remove(&path);
+  }
+  write_config_doc(&doc)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rejects_unknown_trust_levels() {
+    let err = upsert_project("/tmp/project".into(), "trust".into()).unwrap_err();
+    assert_eq!(err.to_string(), "trust_level: 'trust' is not one of trusted, untrusted");
+    assert!(matches!(upsert_project(" ".into(), "trusted".into()), Err(AppError::InvalidInput { .. })));
+  }
+}
@@ -0,0 +1,685 @@
+use axum::body::{to_bytes, Body};
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderName, StatusCode};
+use axum::response::Response;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+use crate::config::{ensure_entry_table, ensure_root_table, get_full_config, read_config_doc, set_value_preserving_decor, write_config_doc};
+use crate::error::{AppError, AppResult};
+use crate::health::error_chain;
+use crate::http::{resolve_endpoint, streaming_client, ProviderEndpoint};
+use crate::keys::{select_provider_key, KeyRotation};
+use crate::nodes::{activate_node, CHATGPT_NODE};
+use crate::settings::{read_settings, write_settings};
+
+/// config.toml 中指向本地中转的节点名
+pub const RELAY_NODE: &str = "codex-mate";
+pub const DEFAULT_RELAY_PORT: u16 = 15721;
+/// 转发时缓存的请求体上限
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// 中转设置（保存在 settings.json）：监听端口、当前转发的目标节点与故障转移组
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RelaySettings {
+  pub port: u16,
+  pub target: Option<String>,
+  /// 按顺序排列的备用节点：目标节点返回 5xx / 429 或超时时依次重试
+  pub failover: Vec<String>,
+  /// 失败节点被标记为降级的时长，期间优先跳过
+  pub cooldown_secs: u64,
+  /// 等待上游返回响应头的超时时间（不限制流式响应的总时长）
+  pub response_timeout_ms: u64,
+  /// 访问令牌：每次启用中转时生成，写入 codex-mate 节点的 experimental_bearer_token，
+  /// 中转只接受携带该令牌的请求，避免本机其他进程借用节点的 API Key
+  pub token: Option<String>,
+}
+
+impl Default for RelaySettings {
+  fn default() -> Self {
+    RelaySettings {
+      port: DEFAULT_RELAY_PORT,
+      target: None,
+      failover: Vec::new(),
+      cooldown_secs: 60,
+      response_timeout_ms: 60_000,
+      token: None,
+    }
+  }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DegradedNode {
+  pub name: String,
+  pub reason: String,
+  /// 剩余冷却时间（秒）
+  pub remaining_secs: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RelayStatus {
+  /// config.toml 是否已指向中转（model_provider = "codex-mate"）
+  pub enabled: bool,
+  /// 端口上是否有中转服务在监听（本进程或其他 codex-mate 进程）
+  pub running: bool,
+  pub port: u16,
+  pub base_url: String,
+  pub target: Option<String>,
+  pub failover: Vec<String>,
+  pub cooldown_secs: u64,
+  /// 本进程中转服务记录的降级节点
+  pub degraded: Vec<DegradedNode>,
+}
+
+struct RunningRelay {
+  port: u16,
+  shutdown: oneshot::Sender<()>,
+}
+
+/// 本进程内运行的中转服务
+static RELAY: Mutex<Option<RunningRelay>> = Mutex::new(None);
+/// 轮询策略下每个节点的下一个密钥位置
+static KEY_CURSOR: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+/// 中转记住的密钥：(密钥标签, 记住时 credentials.json 中的当前密钥)
+type RememberedKey = (String, Option<String>);
+/// on_429 策略下中转换用后可用的密钥，按节点名索引；
+/// 先在内存中生效，再在后台写入 credentials.json，转发请求不等待磁盘读写
+static REMEMBERED_KEY: Mutex<Option<HashMap<String, RememberedKey>>> = Mutex::new(None);
+/// 串行化后台的密钥写入，避免并发请求交错读改写 credentials.json
+static PERSIST_KEY: Mutex<()> = Mutex::new(());
+/// 降级节点：节点名 -> (冷却结束时间, 失败原因)
+static DEGRADED: Mutex<Option<HashMap<String, (Instant, String)>>> = Mutex::new(None);
+
+fn mark_degraded(name: &str, reason: String, cooldown_secs: u64) {
+  log::warn!("relay marked node '{}' degraded for {}s: {}", name, cooldown_secs, reason);
+  if let Ok(mut guard) = DEGRADED.lock() {
+    let until = Instant::now() + Duration::from_secs(cooldown_secs);
+    guard.get_or_insert_with(HashMap::new).insert(name.to_string(), (until, reason));
+  }
+}
+
+fn clear_degraded(name: &str) {
+  if let Ok(mut guard) = DEGRADED.lock() {
+    if let Some(map) = guard.as_mut() {
+      map.remove(name);
+    }
+  }
+}
+
+/// 仍在冷却期内的降级节点（顺带清理已过期的记录）
+fn degraded_nodes() -> Vec<DegradedNode> {
+  let Ok(mut guard) = DEGRADED.lock() else {
+    return Vec::new();
+  };
+  let Some(map) = guard.as_mut() else {
+    return Vec::new();
+  };
+  let now = Instant::now();
+  map.retain(|_, (until, _)| *until > now);
+  let mut nodes: Vec<DegradedNode> = map
+    .iter()
+    .map(|(name, (until, reason))| DegradedNode {
+      name: name.clone(),
+      reason: reason.clone(),
+      remaining_secs: until.saturating_duration_since(now).as_secs(),
+    })
+    .collect();
+  nodes.sort_by(|a, b| a.name.cmp(&b.name));
+  nodes
+}
+
+/// 本次请求依次尝试的节点：目标节点在前，其后为故障转移组；降级节点排到最后，全部降级时仍会尝试
+fn candidates(settings: &RelaySettings, target: &str) -> Vec<String> {
+  let degraded: Vec<String> = degraded_nodes().into_iter().map(|d| d.name).collect();
+  let mut all = vec![target.to_string()];
+  for name in &settings.failover {
+    if !all.contains(name) {
+      all.push(name.clone());
+    }
+  }
+  let (healthy, cooling): (Vec<String>, Vec<String>) = all.into_iter().partition(|n| !degraded.contains(n));
+  healthy.into_iter().chain(cooling).collect()
+}
+
+fn relay_base_url(port: u16) -> String {
+  format!("http://127.0.0.1:{}/v1", port)
+}
+
+fn new_token() -> String {
+  format!("cm-{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+/// 将访问令牌写入 codex-mate 节点（节点不存在时不做任何修改）
+fn write_token_to_config(token: &str) -> AppResult<()> {
+  let mut doc = read_config_doc()?;
+  let Some(tbl) = doc.get_mut("model_providers").and_then(|v| v.get_mut(RELAY_NODE)).and_then(|v| v.as_table_like_mut()) else {
+    return Ok(());
+  };
+  if tbl.get("experimental_bearer_token").and_then(|v| v.as_str()) == Some(token) {
+    return Ok(());
+  }
+  tbl.insert("experimental_bearer_token", toml_edit::value(token));
+  write_config_doc(&doc)
+}
+
+/// 启动服务前确保已有访问令牌且与 config.toml 一致（如升级前启用、尚无令牌的中转）
+fn ensure_token() -> AppResult<()> {
+  let mut settings = read_settings();
+  let token = match settings.relay.token.clone() {
+    Some(token) => token,
+    None => {
+      let token = new_token();
+      settings.relay.token = Some(token.clone());
+      write_settings(&settings)?;
+      token
+    }
+  };
+  write_token_to_config(&token)
+}
+
+/// 请求是否携带了中转的访问令牌（Authorization: Bearer <token>）
+fn authorized(headers: &HeaderMap, token: Option<&str>) -> bool {
+  let Some(token) = token else {
+    return false;
+  };
+  let Some(given) = headers.get("authorization").and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("Bearer ")) else {
+    return false;
+  };
+  // 逐字节比较全部内容，耗时不随首个不同字节的位置变化
+  given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn running_port() -> Option<u16> {
+  RELAY.lock().ok().and_then(|g| g.as_ref().map(|r| r.port))
+}
+
+fn port_in_use(port: u16) -> bool {
+  TcpStream::connect_timeout(&SocketAddr::from(([127, 0, 0, 1], port)), Duration::from_millis(200)).is_ok()
+}
+
+fn wire_api_of(cfg: &serde_json::Value, name: &str) -> String {
+  cfg
+    .get("model_providers")
+    .and_then(|v| v.get(name))
+    .and_then(|v| v.get("wire_api"))
+    .and_then(|v| v.as_str())
+    .unwrap_or("responses")
+    .to_string()
+}
+
+/// 中转模式下 model_provider 为 codex-mate，实际生效的节点为中转目标
+pub fn active_node(model_provider: Option<String>) -> Option<String> {
+  match model_provider {
+    Some(p) if p == RELAY_NODE => read_settings().relay.target,
+    other => other,
+  }
+}
+
+pub fn relay_enabled() -> AppResult<bool> {
+  let cfg = get_full_config()?;
+  Ok(cfg.get("model_provider").and_then(|v| v.as_str()) == Some(RELAY_NODE))
+}
+
+pub fn get_relay_status() -> AppResult<RelayStatus> {
+  let settings = read_settings().relay;
+  let local = running_port();
+  let port = local.unwrap_or(settings.port);
+  Ok(RelayStatus {
+    enabled: relay_enabled()?,
+    running: local.is_some() || port_in_use(port),
+    port,
+    base_url: relay_base_url(port),
+    target: settings.target,
+    failover: settings.failover,
+    cooldown_secs: settings.cooldown_secs,
+    degraded: degraded_nodes(),
+  })
+}
+
+/// 切换中转目标：只修改 settings.json，正在运行的 Codex 会话的下一个请求即转发到新节点
+/// Codex 按 codex-mate 节点的 wire_api 发送请求，因此目标节点的 wire_api 必须与之一致
+pub fn set_relay_target(name: String) -> AppResult<()> {
+  let cfg = get_full_config()?;
+  if name == CHATGPT_NODE || name == RELAY_NODE {
+    return Err(AppError::invalid(format!("node '{}' cannot be a relay target", name)));
+  }
+  if cfg.get("model_providers").and_then(|v| v.get(&name)).is_none() {
+    return Err(AppError::not_found("provider", &name));
+  }
+  let (relay_wire, target_wire) = (wire_api_of(&cfg, RELAY_NODE), wire_api_of(&cfg, &name));
+  if relay_wire != target_wire {
+    return Err(AppError::invalid(format!(
+      "node '{}' uses wire_api '{}' but the relay is configured for '{}'; disable and re-enable the relay to change it",
+      name, target_wire, relay_wire
+    )));
+  }
+  let mut settings = read_settings();
+  settings.relay.target = Some(name.clone());
+  write_settings(&settings)?;
+  clear_degraded(&name);
+  log::info!("relay now forwards to node '{}'", name);
+  Ok(())
+}
+
+/// 设置故障转移组（按顺序）；节点必须存在且 wire_api 与中转一致，传入空列表即关闭故障转移
+pub fn set_relay_failover(nodes: Vec<String>, cooldown_secs: Option<u64>) -> AppResult<RelayStatus> {
+  let cfg = get_full_config()?;
+  let mut settings = read_settings();
+  let expected = match cfg.get("model_providers").and_then(|v| v.get(RELAY_NODE)) {
+    Some(_) => Some(wire_api_of(&cfg, RELAY_NODE)),
+    None => settings.relay.target.as_deref().map(|t| wire_api_of(&cfg, t)),
+  };
+  let mut group: Vec<String> = Vec::new();
+  for name in nodes.into_iter().map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+    if name == CHATGPT_NODE || name == RELAY_NODE {
+      return Err(AppError::invalid(format!("node '{}' cannot be in a failover group", name)));
+    }
+    if cfg.get("model_providers").and_then(|v| v.get(&name)).is_none() {
+      return Err(AppError::not_found("provider", &name));
+    }
+    let wire_api = wire_api_of(&cfg, &name);
+    if let Some(expected) = expected.as_deref().filter(|e| *e != wire_api) {
+      return Err(AppError::invalid(format!("node '{}' uses wire_api '{}', the relay expects '{}'", name, wire_api, expected)));
+    }
+    if !group.contains(&name) {
+      group.push(name);
+    }
+  }
+
+  settings.relay.failover = group;
+  if let Some(secs) = cooldown_secs {
+    settings.relay.cooldown_secs = secs;
+  }
+  write_settings(&settings)?;
+  log::info!("relay failover group set to {:?} (cooldown {}s)", settings.relay.failover, settings.relay.cooldown_secs);
+  get_relay_status()
+}
+
+/// 节点重命名后同步中转目标与故障转移组
+pub(crate) fn rename_relay_refs(old: &str, new: &str) -> AppResult<()> {
+  clear_degraded(old);
+  let mut settings = read_settings();
+  let mut changed = false;
+  for name in settings.relay.target.iter_mut().chain(settings.relay.failover.iter_mut()).filter(|n| n.as_str() == old) {
+    *name = new.to_string();
+    changed = true;
+  }
+  if changed {
+    write_settings(&settings)?;
+  }
+  Ok(())
+}
+
+/// 启用中转：写入 [model_providers.codex-mate] 并将 model_provider 指向它，当前节点成为中转目标
+/// 只修改配置；服务本身由 start_relay（桌面应用）或 serve_relay（CLI）运行
+pub fn enable_relay(port: Option<u16>) -> AppResult<RelayStatus> {
+  let cfg = get_full_config()?;
+  let mut settings = read_settings();
+  let port = port.unwrap_or(settings.relay.port);
+  let current = cfg.get("model_provider").and_then(|v| v.as_str()).map(str::to_string);
+  let target = active_node(current)
+    .filter(|t| t != CHATGPT_NODE && t != RELAY_NODE)
+    .ok_or_else(|| AppError::invalid("switch to an API key node before enabling the relay"))?;
+  if cfg.get("model_providers").and_then(|v| v.get(&target)).is_none() {
+    return Err(AppError::not_found("provider", &target));
+  }
+
+  let token = new_token();
+  let mut doc = read_config_doc()?;
+  let mps = ensure_root_table(&mut doc, "model_providers")?;
+  let tbl = ensure_entry_table(mps, RELAY_NODE)?;
+  set_value_preserving_decor(tbl, "name", RELAY_NODE.into());
+  set_value_preserving_decor(tbl, "base_url", relay_base_url(port).into());
+  set_value_preserving_decor(tbl, "wire_api", wire_api_of(&cfg, &target).into());
+  set_value_preserving_decor(tbl, "experimental_bearer_token", token.as_str().into());
+  set_value_preserving_decor(doc.as_table_mut(), "model_provider", RELAY_NODE.into());
+  write_config_doc(&doc)?;
+
+  settings.relay.port = port;
+  settings.relay.target = Some(target.clone());
+  settings.relay.token = Some(token);
+  write_settings(&settings)?;
+  log::info!("relay enabled on port {}, forwarding to '{}'", port, target);
+  get_relay_status()
+}
+
+/// 关闭中转：切回中转目标节点（写入其 API Key）并删除 codex-mate 节点；
+/// 返回值同 switch_node，目标节点的密钥需由环境变量提供时给出提示。
+/// 配置改写完成后才停止服务，切回失败时中转保持可用，Codex 会话不受影响
+pub fn disable_relay() -> AppResult<Option<String>> {
+  let mut warning = None;
+  if relay_enabled()? {
+    let target = read_settings().relay.target.ok_or_else(|| AppError::config("relay has no target node to switch back to"))?;
+    warning = activate_node(&target)?;
+  }
+  let mut doc = read_config_doc()?;
+  if let Some(mps) = doc.get_mut("model_providers").and_then(|v| v.as_table_like_mut()) {
+    mps.remove(RELAY_NODE);
+  }
+  write_config_doc(&doc)?;
+  let mut settings = read_settings();
+  if settings.relay.token.take().is_some() {
+    write_settings(&settings)?;
+  }
+  stop_relay();
+  log::info!("relay disabled");
+  Ok(warning)
+}
+
+/// 逐跳头与由中转重新生成的头，不原样转发
+fn skip_header(name: &HeaderName) -> bool {
+  matches!(
+    name.as_str(),
+    "host" | "authorization" | "content-length" | "connection" | "keep-alive" | "proxy-connection" | "transfer-encoding" | "te" | "trailer" | "upgrade"
+  )
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+  let body = serde_json::json!({ "error": { "message": message, "type": "codex_mate_relay" } });
+  Response::builder()
+    .status(status)
+    .header("content-type", "application/json")
+    .body(Body::from(body.to_string()))
+    .unwrap_or_default()
+}
+
+/// 中转记住的当前密钥；credentials.json 中的当前密钥已被手动改为其他密钥时不再生效
+fn remembered_key(endpoint: &ProviderEndpoint) -> Option<String> {
+  let guard = REMEMBERED_KEY.lock().ok()?;
+  let (label, base) = guard.as_ref()?.get(&endpoint.name)?;
+  (endpoint.active_key.as_ref() == Some(label) || endpoint.active_key == *base).then(|| label.clone())
+}
+
+/// 记住可用的密钥并在后台线程写入 credentials.json；写入时以内存中最新的记录为准，先后完成的请求不会写回旧值
+fn remember_key(endpoint: &ProviderEndpoint, label: &str) {
+  if let Ok(mut guard) = REMEMBERED_KEY.lock() {
+    let entry = (label.to_string(), endpoint.active_key.clone());
+    guard.get_or_insert_with(HashMap::new).insert(endpoint.name.clone(), entry);
+  }
+  let name = endpoint.name.clone();
+  tokio::task::spawn_blocking(move || {
+    let _lock = PERSIST_KEY.lock();
+    let latest = REMEMBERED_KEY.lock().ok().and_then(|g| g.as_ref().and_then(|m| m.get(&name)).map(|(l, _)| l.clone()));
+    if let Some(label) = latest {
+      if let Err(e) = select_provider_key(name.clone(), label.clone()) {
+        log::warn!("relay could not remember key '{}' for '{}': {}", label, name, e);
+      }
+    }
+  });
+}
+
+/// 本次请求依次尝试的密钥：未使用密钥池或手动策略时只用当前密钥；轮询策略从下一个密钥开始，429 策略从当前密钥开始
+fn request_keys(endpoint: &ProviderEndpoint) -> Vec<(Option<String>, Option<String>)> {
+  let pool = &endpoint.key_pool;
+  if pool.is_empty() || endpoint.rotation == KeyRotation::Manual {
+    return vec![(endpoint.active_key.clone(), endpoint.api_key.clone())];
+  }
+  let start = match endpoint.rotation {
+    KeyRotation::RoundRobin => KEY_CURSOR
+      .lock()
+      .ok()
+      .map(|mut g| {
+        let cursor = g.get_or_insert_with(HashMap::new).entry(endpoint.name.clone()).or_insert(0);
+        *cursor = cursor.wrapping_add(1);
+        *cursor
+      })
+      .unwrap_or(0),
+    _ => remembered_key(endpoint)
+      .or_else(|| endpoint.active_key.clone())
+      .and_then(|a| pool.iter().position(|(l, _)| *l == a))
+      .unwrap_or(0),
+  };
+  (0..pool.len())
+    .map(|i| &pool[(start + i) % pool.len()])
+    .map(|(label, key)| (Some(label.clone()), Some(key.clone())))
+    .collect()
+}
+
+/// 上游失败且可由其他节点重试的状态码
+fn retryable(status: reqwest::StatusCode) -> bool {
+  status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// 原样返回上游响应，响应体按流转发
+fn stream_response(resp: reqwest::Response) -> Response {
+  let mut builder = Response::builder().status(resp.status());
+  for (name, value) in resp.headers().iter().filter(|(name, _)| !skip_header(name) || name.as_str() == "content-length") {
+    builder = builder.header(name, value);
+  }
+  builder.body(Body::from_stream(resp.bytes_stream())).unwrap_or_default()
+}
+
+/// 将请求转发到当前目标节点：/v1/<path> -> {base_url}/<path>，替换为目标节点的 API Key，响应按流原样返回
+/// 目标节点返回 5xx / 429、连接失败或超时时，标记为降级并依次重试故障转移组中的节点；已开始的流式响应中断后无法重试
+async fn forward(State(client): State<reqwest::Client>, req: Request) -> Response {
+  let settings = read_settings().relay;
+  if !authorized(req.headers(), settings.token.as_deref()) {
+    log::warn!("relay request rejected: missing or wrong access token");
+    return error_response(StatusCode::UNAUTHORIZED, "missing or invalid relay access token".into());
+  }
+  let Some(target) = settings.target.clone() else {
+    log::warn!("relay request rejected: no target node");
+    return error_response(StatusCode::SERVICE_UNAVAILABLE, "relay has no target node".into());
+  };
+
+  let (parts, body) = req.into_parts();
+  let body = match to_bytes(body, MAX_BODY_BYTES).await {
+    Ok(b) => b,
+    Err(e) => return error_response(StatusCode::PAYLOAD_TOO_LARGE, format!("read request body failed: {}", e)),
+  };
+  let path = parts.uri.path();
+  let path = path.strip_prefix("/v1").unwrap_or(path);
+  let path = match parts.uri.query() {
+    Some(q) => format!("{}?{}", path, q),
+    None => path.to_string(),
+  };
+
+  // 最后一次失败的结果：全部节点失败时原样返回
+  let mut failure: Option<Response> = None;
+  for name in candidates(&settings, &target) {
+    let endpoint = match resolve_endpoint(&name) {
+      Ok(ep) => ep,
+      Err(e @ AppError::VaultLocked) => {
+        log::warn!("relay request rejected: {}", e);
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, e.to_string());
+      }
+      Err(e) => {
+        log::warn!("relay skipped node '{}': {}", name, e);
+        failure = Some(error_response(StatusCode::BAD_GATEWAY, e.to_string()));
+        continue;
+      }
+    };
+
+    // 节点配置了多个密钥时，429 先换用同一节点的下一个密钥，全部受限后才转移到其他节点
+    let keys = request_keys(&endpoint);
+    let started = Instant::now();
+    let mut sent = None;
+    for (i, (label, key)) in keys.iter().enumerate() {
+      let mut attempt = endpoint.clone();
+      attempt.api_key = key.clone();
+      let mut upstream = attempt.request(&client, parts.method.clone(), &path);
+      for (header, value) in parts.headers.iter() {
+        if !skip_header(header) && !endpoint.http_headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(header.as_str())) {
+          upstream = upstream.header(header, value);
+        }
+      }
+      let result = tokio::time::timeout(Duration::from_millis(settings.response_timeout_ms), upstream.body(body.clone()).send()).await;
+      let limited = matches!(&result, Ok(Ok(resp)) if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS);
+      if limited && i + 1 < keys.len() {
+        log::warn!("relay key '{}' of '{}' is rate limited, trying the next key", label.as_deref().unwrap_or("-"), name);
+        continue;
+      }
+      if let (Ok(Ok(resp)), Some(label)) = (&result, label) {
+        if !limited && resp.status().is_success() && endpoint.rotation == KeyRotation::On429 {
+          let current = remembered_key(&endpoint).or_else(|| endpoint.active_key.clone());
+          if current.as_ref() != Some(label) {
+            remember_key(&endpoint, label);
+          }
+        }
+      }
+      sent = Some(result);
+      break;
+    }
+    let Some(sent) = sent else {
+      continue;
+    };
+    match sent {
+      Ok(Ok(resp)) if !retryable(resp.status()) => {
+        log::debug!(
+          "relay {} {} -> '{}' {} ({}ms)",
+          parts.method,
+          parts.uri.path(),
+          name,
+          resp.status().as_u16(),
+          started.elapsed().as_millis()
+        );
+        if name != target {
+          log::warn!("relay request served by failover node '{}' instead of '{}'", name, target);
+        }
+        clear_degraded(&name);
+        return stream_response(resp);
+      }
+      Ok(Ok(resp)) => {
+        mark_degraded(&name, format!("HTTP {}", resp.status().as_u16()), settings.cooldown_secs);
+        failure = Some(stream_response(resp));
+      }
+      Ok(Err(e)) => {
+        let message = format!("forward to '{}' failed: {}", name, error_chain(&e));
+        mark_degraded(&name, message.clone(), settings.cooldown_secs);
+        let status = if e.is_timeout() { StatusCode::GATEWAY_TIMEOUT } else { StatusCode::BAD_GATEWAY };
+        failure = Some(error_response(status, message));
+      }
+      Err(_) => {
+        let message = format!("'{}' did not respond within {}ms", name, settings.response_timeout_ms);
+        mark_degraded(&name, message.clone(), settings.cooldown_secs);
+        failure = Some(error_response(StatusCode::GATEWAY_TIMEOUT, message));
+      }
+    }
+  }
+  log::warn!("relay {} {} failed on every node", parts.method, parts.uri.path());
+  failure.unwrap_or_else(|| error_response(StatusCode::SERVICE_UNAVAILABLE, "no relay node available".into()))
+}
+
+fn router() -> AppResult<Router> {
+  Ok(Router::new().fallback(forward).with_state(streaming_client()?))
+}
+
+async fn bind(port: u16) -> AppResult<tokio::net::TcpListener> {
+  tokio::net::TcpListener::bind(("127.0.0.1", port))
+    .await
+    .map_err(|e| AppError::invalid(format!("relay cannot listen on 127.0.0.1:{}: {}", port, e)))
+}
+
+/// 在后台启动中转服务（桌面应用），已在配置的端口上运行时直接返回状态；
+/// 端口已变更（重新启用时指定了新端口）时在新端口监听成功后再停止旧服务
+pub async fn start_relay() -> AppResult<RelayStatus> {
+  let port = read_settings().relay.port;
+  if running_port() == Some(port) {
+    return get_relay_status();
+  }
+  ensure_token()?;
+  let listener = bind(port).await?;
+  let app = router()?;
+  stop_relay();
+  let (tx, rx) = oneshot::channel::<()>();
+  tokio::spawn(async move {
+    let shutdown = async {
+      let _ = rx.await;
+    };
+    if let Err(e) = axum::serve(listener, app).with_graceful_shutdown(shutdown).await {
+      log::error!("relay server stopped: {}", e);
+    }
+  });
+  if let Ok(mut guard) = RELAY.lock() {
+    *guard = Some(RunningRelay { port, shutdown: tx });
+  }
+  log::info!("relay listening on {}", relay_base_url(port));
+  get_relay_status()
+}
+
+/// 停止本进程内的中转服务；进行中的流式响应会继续完成
+pub fn stop_relay() {
+  let running = RELAY.lock().ok().and_then(|mut g| g.take());
+  if let Some(r) = running {
+    let _ = r.shutdown.send(());
+    log::info!("relay on port {} stopped", r.port);
+  }
+}
+
+/// 在前台运行中转服务直到进程退出（CLI）
+pub async fn serve_relay() -> AppResult<()> {
+  ensure_token()?;
+  let port = read_settings().relay.port;
+  let listener = bind(port).await?;
+  log::info!("relay listening on {}", relay_base_url(port));
+  axum::serve(listener, router()?)
+    .await
+    .map_err(|e| AppError::Internal { message: format!("relay server failed: {}", e) })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn headers(authorization: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("authorization", authorization.parse().unwrap());
+    headers
+  }
+
+  #[test]
+  fn requires_the_relay_token() {
+    assert!(authorized(&headers("Bearer cm-secret"), Some("cm-secret")));
+    assert!(!authorized(&headers("Bearer cm-secreT"), Some("cm-secret")));
+    assert!(!authorized(&headers("Bearer cm-secret2"), Some("cm-secret")));
+    assert!(!authorized(&headers("cm-secret"), Some("cm-secret")));
+    assert!(!authorized(&HeaderMap::new(), Some("cm-secret")));
+    assert!(!authorized(&headers("Bearer "), None));
+  }
+
+  fn pooled(name: &str, active: Option<&str>) -> ProviderEndpoint {
+    ProviderEndpoint {
+      name: name.into(),
+      base_url: "http://127.0.0.1:1/v1".into(),
+      api_key: None,
+      key_pool: vec![("a".into(), "sk-a".into()), ("b".into(), "sk-b".into()), ("c".into(), "sk-c".into())],
+      active_key: active.map(str::to_string),
+      rotation: KeyRotation::On429,
+      query_params: vec![],
+      http_headers: vec![],
+    }
+  }
+
+  fn labels(endpoint: &ProviderEndpoint) -> Vec<String> {
+    request_keys(endpoint).into_iter().filter_map(|(label, _)| label).collect()
+  }
+
+  #[test]
+  fn remembered_key_applies_until_changed_on_disk() {
+    let endpoint = pooled("remembered-test", Some("a"));
+    assert_eq!(labels(&endpoint), ["a", "b", "c"]);
+    REMEMBERED_KEY
+      .lock()
+      .unwrap()
+      .get_or_insert_with(HashMap::new)
+      .insert("remembered-test".into(), ("b".into(), Some("a".into())));
+    // 尚未写入磁盘与已写入磁盘时都从记住的密钥开始
+    assert_eq!(labels(&endpoint), ["b", "c", "a"]);
+    assert_eq!(labels(&pooled("remembered-test", Some("b"))), ["b", "c", "a"]);
+    // 用户手动选择了其他密钥
+    assert_eq!(labels(&pooled("remembered-test", Some("c"))), ["c", "a", "b"]);
+  }
+
+  #[test]
+  fn tokens_are_unique() {
+    let (a, b) = (new_token(), new_token());
+    assert_ne!(a, b);
+    assert_eq!(a.len(), 67);
+  }
+}
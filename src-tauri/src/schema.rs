@@ -0,0 +1,517 @@
+use semver::Version;
+use serde::Serialize;
+use std::ops::Range;
+use toml_edit::{ImDocument, Item, TableLike};
+
+use crate::config::display_key_path;
+use crate::error::line_col;
+use crate::migrate::deprecation;
+use crate::nodes::WIRE_APIS;
+use crate::profiles::{APPROVAL_POLICIES, BUILTIN_PROVIDERS, REASONING_EFFORTS, REASONING_SUMMARIES, SANDBOX_MODES, VERBOSITIES};
+use crate::projects::TRUST_LEVELS;
+use crate::version::{requirement_message, unsupported};
+
+/// 配置项的取值类型
+pub enum Kind {
+  /// 不检查（自由格式的表，如 otel、tui）
+  Any,
+  Str,
+  Bool,
+  Int,
+  /// 整数或浮点数
+  Number,
+  StrArray,
+  Enum(&'static [&'static str]),
+  /// 键名固定的表
+  Table(&'static [Field]),
+  /// 键名任意、值类型相同的表，如 model_providers、env
+  Map(&'static Kind),
+}
+
+pub struct Field {
+  pub name: &'static str,
+  pub kind: Kind,
+}
+
+const fn field(name: &'static str, kind: Kind) -> Field {
+  Field { name, kind }
+}
+
+const STR_MAP: Kind = Kind::Map(&Kind::Str);
+
+const PROVIDER_FIELDS: &[Field] = &[
+  field("name", Kind::Str),
+  field("base_url", Kind::Str),
+  field("wire_api", Kind::Enum(&WIRE_APIS)),
+  field("env_key", Kind::Str),
+  field("env_key_instructions", Kind::Str),
+  field("experimental_bearer_token", Kind::Str),
+  field("requires_openai_auth", Kind::Bool),
+  field("query_params", STR_MAP),
+  field("http_headers", STR_MAP),
+  field("env_http_headers", STR_MAP),
+  field("request_max_retries", Kind::Int),
+  field("stream_max_retries", Kind::Int),
+  field("stream_idle_timeout_ms", Kind::Int),
+];
+
+const MCP_FIELDS: &[Field] = &[
+  field("command", Kind::Str),
+  field("args", Kind::StrArray),
+  field("env", STR_MAP),
+  field("env_vars", Kind::StrArray),
+  field("cwd", Kind::Str),
+  field("url", Kind::Str),
+  field("bearer_token_env_var", Kind::Str),
+  field("http_headers", STR_MAP),
+  field("env_http_headers", STR_MAP),
+  field("enabled", Kind::Bool),
+  field("enabled_tools", Kind::StrArray),
+  field("disabled_tools", Kind::StrArray),
+  field("startup_timeout_sec", Kind::Number),
+  field("startup_timeout_ms", Kind::Int),
+  field("tool_timeout_sec", Kind::Number),
+];
+
+const PROFILE_FIELDS: &[Field] = &[
+  field("model", Kind::Str),
+  field("model_provider", Kind::Str),
+  field("approval_policy", Kind::Enum(&APPROVAL_POLICIES)),
+  field("sandbox_mode", Kind::Enum(&SANDBOX_MODES)),
+  field("model_reasoning_effort", Kind::Enum(&REASONING_EFFORTS)),
+  field("model_reasoning_summary", Kind::Enum(&REASONING_SUMMARIES)),
+  field("model_verbosity", Kind::Enum(&VERBOSITIES)),
+  field("chatgpt_base_url", Kind::Str),
+  field("experimental_instructions_file", Kind::Str),
+  field("include_plan_tool", Kind::Bool),
+  field("include_apply_patch_tool", Kind::Bool),
+  field("experimental_use_unified_exec_tool", Kind::Bool),
+  field("experimental_use_rmcp_client", Kind::Bool),
+  field("features", Kind::Map(&Kind::Bool)),
+];
+
+const SANDBOX_WORKSPACE_WRITE_FIELDS: &[Field] = &[
+  field("writable_roots", Kind::StrArray),
+  field("network_access", Kind::Bool),
+  field("exclude_tmpdir_env_var", Kind::Bool),
+  field("exclude_slash_tmp", Kind::Bool),
+];
+
+const SHELL_ENVIRONMENT_POLICY_FIELDS: &[Field] = &[
+  field("inherit", Kind::Enum(&["all", "core", "none"])),
+  field("ignore_default_excludes", Kind::Bool),
+  field("exclude", Kind::StrArray),
+  field("include_only", Kind::StrArray),
+  field("set", STR_MAP),
+  field("experimental_use_profile", Kind::Bool),
+];
+
+const HISTORY_FIELDS: &[Field] = &[field("persistence", Kind::Enum(&["save-all", "none"])), field("max_bytes", Kind::Int)];
+
+const TOOLS_FIELDS: &[Field] = &[field("web_search", Kind::Bool), field("view_image", Kind::Bool)];
+
+const PROJECT_FIELDS: &[Field] = &[field("trust_level", Kind::Enum(&TRUST_LEVELS))];
+
+/// Codex config.toml 的已知结构
+pub const ROOT_FIELDS: &[Field] = &[
+  field("model", Kind::Str),
+  field("review_model", Kind::Str),
+  field("model_provider", Kind::Str),
+  field("model_context_window", Kind::Int),
+  field("model_auto_compact_token_limit", Kind::Int),
+  field("model_max_output_tokens", Kind::Int),
+  field("model_reasoning_effort", Kind::Enum(&REASONING_EFFORTS)),
+  field("model_reasoning_summary", Kind::Enum(&REASONING_SUMMARIES)),
+  field("model_reasoning_summary_format", Kind::Enum(&["none", "experimental"])),
+  field("model_supports_reasoning_summaries", Kind::Bool),
+  field("model_verbosity", Kind::Enum(&VERBOSITIES)),
+  field("approval_policy", Kind::Enum(&APPROVAL_POLICIES)),
+  field("sandbox_mode", Kind::Enum(&SANDBOX_MODES)),
+  field("sandbox_workspace_write", Kind::Table(SANDBOX_WORKSPACE_WRITE_FIELDS)),
+  field("shell_environment_policy", Kind::Table(SHELL_ENVIRONMENT_POLICY_FIELDS)),
+  field("notify", Kind::StrArray),
+  field("instructions", Kind::Str),
+  field("experimental_instructions_file", Kind::Str),
+  field("project_doc_max_bytes", Kind::Int),
+  field("project_doc_fallback_filenames", Kind::StrArray),
+  field("history", Kind::Table(HISTORY_FIELDS)),
+  field("file_opener", Kind::Enum(&["vscode", "vscode-insiders", "windsurf", "cursor", "none"])),
+  field("hide_agent_reasoning", Kind::Bool),
+  field("show_raw_agent_reasoning", Kind::Bool),
+  field("chatgpt_base_url", Kind::Str),
+  field("forced_login_method", Kind::Enum(&["chatgpt", "api"])),
+  field("forced_chatgpt_workspace_id", Kind::Str),
+  field("cli_auth_credentials_store", Kind::Enum(&["file", "keyring", "auto"])),
+  field("mcp_oauth_credentials_store", Kind::Enum(&["file", "keyring", "auto"])),
+  field("include_plan_tool", Kind::Bool),
+  field("include_apply_patch_tool", Kind::Bool),
+  field("experimental_use_exec_command_tool", Kind::Bool),
+  field("experimental_use_unified_exec_tool", Kind::Bool),
+  field("experimental_use_rmcp_client", Kind::Bool),
+  field("experimental_use_freeform_apply_patch", Kind::Bool),
+  field("use_experimental_streamable_shell_tool", Kind::Bool),
+  field("windows_wsl_setup_acknowledged", Kind::Bool),
+  field("check_for_update_on_startup", Kind::Bool),
+  field("tools", Kind::Table(TOOLS_FIELDS)),
+  field("features", Kind::Map(&Kind::Bool)),
+  field("tui", Kind::Any),
+  field("otel", Kind::Any),
+  field("notice", Kind::Any),
+  field("profile", Kind::Str),
+  field("profiles", Kind::Map(&Kind::Table(PROFILE_FIELDS))),
+  field("model_providers", Kind::Map(&Kind::Table(PROVIDER_FIELDS))),
+  field("mcp_servers", Kind::Map(&Kind::Table(MCP_FIELDS))),
+  field("projects", Kind::Map(&Kind::Table(PROJECT_FIELDS))),
+];
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+  Error,
+  Warning,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  /// 点分键路径，TOML 语法错误时为空
+  pub path: String,
+  pub message: String,
+  /// 1 起始的行号和列号
+  pub line: Option<usize>,
+  pub col: Option<usize>,
+  /// 拼写相近的键名或取值
+  pub suggestion: Option<String>,
+}
+
+struct Linter<'a> {
+  source: &'a str,
+  /// 已安装的 Codex 版本，用于提示需要更新版本的配置项；未知时不检查
+  installed: Option<&'a Version>,
+  diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+  fn push(&mut self, severity: Severity, path: &[String], span: Option<Range<usize>>, message: String, suggestion: Option<String>) {
+    let (line, col) = match span.map(|s| line_col(self.source, s.start)) {
+      Some((l, c)) => (Some(l), Some(c)),
+      None => (None, None),
+    };
+    let path = display_key_path(path);
+    let message = match &suggestion {
+      Some(s) => format!("{}, did you mean '{}'?", message, s),
+      None => message,
+    };
+    self.diagnostics.push(Diagnostic { severity, path, message, line, col, suggestion });
+  }
+
+  fn check_table(&mut self, tbl: &dyn TableLike, fields: &[Field], path: &mut Vec<String>) {
+    for (name, item) in tbl.iter() {
+      let span = tbl.get_key_value(name).and_then(|(k, _)| k.span()).or_else(|| first_span(item));
+      path.push(name.to_string());
+      let deprecated = deprecation(path);
+      if let Some(message) = &deprecated {
+        self.push(Severity::Warning, path, span.clone(), message.clone(), None);
+      }
+      match fields.iter().find(|f| f.name == name) {
+        Some(f) => {
+          self.check_version(item, path, span.clone());
+          self.check_item(item, &f.kind, path, span);
+        }
+        None if deprecated.is_some() => {}
+        None => {
+          let suggestion = closest(name, fields.iter().map(|f| f.name)).map(str::to_string);
+          self.push(Severity::Warning, path, span, format!("unknown key '{}'", name), suggestion);
+        }
+      }
+      path.pop();
+    }
+  }
+
+  fn check_item(&mut self, item: &Item, kind: &Kind, path: &mut Vec<String>, key_span: Option<Range<usize>>) {
+    let span = item.span().or(key_span);
+    match kind {
+      Kind::Any => {}
+      Kind::Table(fields) => match item.as_table_like() {
+        Some(tbl) => self.check_table(tbl, fields, path),
+        None => self.type_error(path, span, "a table", item.type_name()),
+      },
+      Kind::Map(inner) => match item.as_table_like() {
+        Some(tbl) => {
+          for (name, child) in tbl.iter() {
+            let child_span = tbl.get_key_value(name).and_then(|(k, _)| k.span()).or_else(|| first_span(child));
+            path.push(name.to_string());
+            self.check_item(child, inner, path, child_span);
+            path.pop();
+          }
+        }
+        None => self.type_error(path, span, "a table", item.type_name()),
+      },
+      _ => match item.as_value() {
+        Some(value) => self.check_value(value, kind, path, span),
+        None => self.type_error(path, span, kind.describe(), item.type_name()),
+      },
+    }
+  }
+
+  fn check_value(&mut self, value: &toml_edit::Value, kind: &Kind, path: &[String], span: Option<Range<usize>>) {
+    let ok = match kind {
+      Kind::Str => value.is_str(),
+      Kind::Bool => value.is_bool(),
+      Kind::Int => value.is_integer(),
+      Kind::Number => value.is_integer() || value.is_float(),
+      Kind::StrArray => value.as_array().is_some_and(|a| a.iter().all(|v| v.is_str())),
+      Kind::Enum(values) => match value.as_str() {
+        Some(s) if values.contains(&s) => true,
+        Some(s) => {
+          let suggestion = closest(s, values.iter().copied());
+          let message = format!("'{}' is not one of {}", s, values.join(", "));
+          self.push(Severity::Error, path, span, message, suggestion.map(str::to_string));
+          return;
+        }
+        None => false,
+      },
+      Kind::Any | Kind::Table(_) | Kind::Map(_) => true,
+    };
+    if !ok {
+      self.type_error(path, span, kind.describe(), value.type_name());
+    }
+  }
+
+  fn check_version(&mut self, item: &Item, path: &[String], span: Option<Range<usize>>) {
+    let Some(installed) = self.installed else { return };
+    if let Some(req) = unsupported(installed, path, item.as_str()) {
+      self.push(Severity::Warning, path, span, requirement_message(req, installed), None);
+    }
+  }
+
+  fn type_error(&mut self, path: &[String], span: Option<Range<usize>>, expected: &str, found: &str) {
+    self.push(Severity::Error, path, span, format!("expected {}, found {}", expected, found), None);
+  }
+
+  /// model_provider / profile 引用的节点和 profile 必须存在
+  fn check_references(&mut self, root: &toml_edit::Table) {
+    let names = |key: &str| -> Vec<String> {
+      root.get(key).and_then(|v| v.as_table_like()).map(|t| t.iter().map(|(k, _)| k.to_string()).collect()).unwrap_or_default()
+    };
+    let providers = names("model_providers");
+    let profiles = names("profiles");
+
+    let mut refs: Vec<(Vec<String>, &Item)> = Vec::new();
+    if let Some(item) = root.get("model_provider") {
+      refs.push((vec!["model_provider".into()], item));
+    }
+    if let Some(tbl) = root.get("profiles").and_then(|v| v.as_table_like()) {
+      for (name, profile) in tbl.iter() {
+        if let Some(item) = profile.get("model_provider") {
+          refs.push((vec!["profiles".into(), name.to_string(), "model_provider".into()], item));
+        }
+      }
+    }
+    for (path, item) in refs {
+      let Some(name) = item.as_str() else { continue };
+      if BUILTIN_PROVIDERS.contains(&name) || providers.iter().any(|p| p == name) {
+        continue;
+      }
+      let candidates = providers.iter().map(String::as_str).chain(BUILTIN_PROVIDERS);
+      let suggestion = closest(name, candidates).map(str::to_string);
+      self.push(Severity::Error, &path, item.span(), format!("provider '{}' is not defined in [model_providers]", name), suggestion);
+    }
+
+    if let Some(item) = root.get("profile") {
+      if let Some(name) = item.as_str().filter(|n| !profiles.iter().any(|p| p == n)) {
+        let suggestion = closest(name, profiles.iter().map(String::as_str)).map(str::to_string);
+        self.push(Severity::Error, &["profile".into()], item.span(), format!("profile '{}' is not defined in [profiles]", name), suggestion);
+      }
+    }
+  }
+}
+
+impl Kind {
+  fn describe(&self) -> &'static str {
+    match self {
+      Kind::Any => "any value",
+      Kind::Str | Kind::Enum(_) => "a string",
+      Kind::Bool => "a boolean",
+      Kind::Int => "an integer",
+      Kind::Number => "a number",
+      Kind::StrArray => "an array of strings",
+      Kind::Table(_) | Kind::Map(_) => "a table",
+    }
+  }
+}
+
+/// 表头隐式创建的父表（如 `[a.b]` 中的 a）自身没有位置，取其首个子项的位置
+fn first_span(item: &Item) -> Option<Range<usize>> {
+  item.span().or_else(|| {
+    let tbl = item.as_table_like()?;
+    tbl.iter().find_map(|(k, v)| tbl.get_key_value(k).and_then(|(key, _)| key.span()).or_else(|| first_span(v)))
+  })
+}
+
+/// 编辑距离，相邻字符互换计为一次（如 modle → model）
+fn edit_distance(a: &str, b: &str) -> usize {
+  let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+  let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+  for (i, row) in d.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  for (j, cell) in d[0].iter_mut().enumerate() {
+    *cell = j;
+  }
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = usize::from(a[i - 1] != b[j - 1]);
+      d[i][j] = (d[i - 1][j - 1] + cost).min(d[i - 1][j] + 1).min(d[i][j - 1] + 1);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+      }
+    }
+  }
+  d[a.len()][b.len()]
+}
+
+/// 在候选中找拼写最接近的一项；差异超过名称长度的三分之一时不给出建议
+fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+  let limit = (name.chars().count() / 3).max(1);
+  candidates
+    .into_iter()
+    .map(|c| (edit_distance(&name.to_lowercase(), &c.to_lowercase()), c))
+    .filter(|(d, _)| *d <= limit)
+    .min_by_key(|(d, _)| *d)
+    .map(|(_, c)| c)
+}
+
+/// 按已知的 Codex 配置结构检查 config.toml 内容（可为编辑器中尚未保存的文本）
+///
+/// 报告未知键（附拼写建议）、已弃用的键、类型错误、非法枚举值，以及指向不存在节点 / profile 的引用；
+/// 给出 installed 时，还会提示需要更高 Codex 版本的配置项。TOML 语法错误时只返回该错误
+pub fn validate_config(content: &str, installed: Option<&Version>) -> Vec<Diagnostic> {
+  let mut linter = Linter { source: content, installed, diagnostics: Vec::new() };
+  let doc = match ImDocument::parse(content) {
+    Ok(doc) => doc,
+    Err(e) => {
+      linter.push(Severity::Error, &[], e.span(), e.message().trim().to_string(), None);
+      return linter.diagnostics;
+    }
+  };
+  linter.check_table(doc.as_table(), ROOT_FIELDS, &mut Vec::new());
+  linter.check_references(doc.as_table());
+  linter.diagnostics.sort_by_key(|d| (d.line, d.col));
+  linter.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lint(content: &str) -> Vec<Diagnostic> {
+    validate_config(content, None)
+  }
+
+  #[test]
+  fn valid_config_has_no_diagnostics() {
+    let content = r#"
+model = "gpt-5"
+model_provider = "packy"
+profile = "fast"
+notify = ["notify-send"]
+
+[model_providers.packy]
+name = "packy"
+base_url = "https://api.example.com/v1"
+wire_api = "chat"
+query_params = { api-version = "2025-04-01-preview" }
+
+[profiles.fast]
+model_provider = "openai"
+model_reasoning_effort = "low"
+
+[mcp_servers.docs]
+command = "npx"
+args = ["-y", "docs-mcp"]
+startup_timeout_sec = 12.5
+
+[projects."/home/me/repo"]
+trust_level = "trusted"
+
+[tui]
+anything = { goes = true }
+"#;
+    let diagnostics = lint(content);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+  }
+
+  #[test]
+  fn unknown_key_with_suggestion_and_position() {
+    let diagnostics = lint("model = \"gpt-5\"\nmodle_provider = \"openai\"\n");
+    assert_eq!(diagnostics.len(), 1);
+    let d = &diagnostics[0];
+    assert_eq!(d.severity, Severity::Warning);
+    assert_eq!(d.path, "modle_provider");
+    assert_eq!((d.line, d.col), (Some(2), Some(1)));
+    assert_eq!(d.suggestion.as_deref(), Some("model_provider"));
+  }
+
+  #[test]
+  fn type_and_enum_errors() {
+    let content = "approval_policy = \"on-requst\"\nhide_agent_reasoning = \"yes\"\n[model_providers.a]\nbase_url = \"x\"\nrequest_max_retries = 1.5\n";
+    let diagnostics = lint(content);
+    let find = |path: &str| diagnostics.iter().find(|d| d.path == path).unwrap_or_else(|| panic!("no diagnostic for {}", path));
+    assert_eq!(find("approval_policy").severity, Severity::Error);
+    assert_eq!(find("approval_policy").suggestion.as_deref(), Some("on-request"));
+    assert!(find("hide_agent_reasoning").message.contains("expected a boolean, found string"));
+    assert_eq!(find("model_providers.a.request_max_retries").line, Some(5));
+  }
+
+  #[test]
+  fn dangling_references() {
+    let content = "model_provider = \"packi\"\nprofile = \"slow\"\n[model_providers.packy]\nbase_url = \"x\"\n[profiles.fast]\nmodel_provider = \"nope\"\n";
+    let diagnostics = lint(content);
+    let paths: Vec<&str> = diagnostics.iter().map(|d| d.path.as_str()).collect();
+    assert_eq!(paths, ["model_provider", "profile", "profiles.fast.model_provider"]);
+    assert_eq!(diagnostics[0].suggestion.as_deref(), Some("packy"));
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+  }
+
+  #[test]
+  fn syntax_error_is_reported_alone() {
+    let diagnostics = lint("model = \"gpt-5\"\nmodel_provider = \n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].path, "");
+    assert_eq!(diagnostics[0].line, Some(2));
+  }
+
+  #[test]
+  fn deprecated_keys_point_to_migration() {
+    let content = "disable_response_storage = true\npreferred_auth_method = \"apikey\"\n[tools]\nweb_search = true\n[profiles.fast]\ninclude_apply_patch_tool = true\n";
+    let diagnostics = lint(content);
+    assert_eq!(diagnostics.len(), 4, "{:?}", diagnostics);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning && d.message.contains("config migrate")));
+    assert!(diagnostics[0].message.contains("no longer read by Codex"));
+    assert!(diagnostics[2].message.contains("use 'features.web_search_request' instead"));
+    assert!(diagnostics[3].message.contains("use 'profiles.fast.features.apply_patch_freeform' instead"));
+  }
+
+  #[test]
+  fn version_warnings_need_installed_version() {
+    let content = "[features]\nunified_exec = true\n";
+    assert!(lint(content).is_empty());
+    let old = Version::new(0, 40, 0);
+    let diagnostics = validate_config(content, Some(&old));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0].message.contains("requires Codex 0.48.0"));
+    assert!(validate_config(content, Some(&Version::new(0, 48, 0))).is_empty());
+  }
+
+  #[test]
+  fn edit_distance_counts_transpositions() {
+    assert_eq!(edit_distance("model", "model"), 0);
+    assert_eq!(edit_distance("modle", "model"), 1);
+    assert_eq!(edit_distance("mdl", "model"), 2);
+    assert_eq!(closest("Model", ["model", "profile"]), Some("model"));
+    assert_eq!(closest("xyz", ["model", "profile"]), None);
+  }
+}
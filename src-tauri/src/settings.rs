@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::backup::BackupSettings;
+use crate::error::AppResult;
+use crate::logging::LogSettings;
+use crate::presets::PresetSettings;
+use crate::relay::RelaySettings;
+use crate::storage::{atomic_write, codex_dir};
+
+/// 应用设置文件: ~/.codex/codex-mate/settings.json
+pub fn settings_path() -> AppResult<PathBuf> {
+  Ok(codex_dir()?.join("codex-mate").join("settings.json"))
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AppSettings {
+  pub backup: BackupSettings,
+  pub log: LogSettings,
+  pub relay: RelaySettings,
+  pub presets: PresetSettings,
+}
+
+/// 设置文件的版本标识：修改时间与大小，Unix 上再加 inode（atomic_write 每次写入都会换新文件）
+type FileStamp = (Option<SystemTime>, u64, u64);
+
+fn file_stamp(meta: &fs::Metadata) -> FileStamp {
+  #[cfg(unix)]
+  let inode = std::os::unix::fs::MetadataExt::ino(meta);
+  #[cfg(not(unix))]
+  let inode = 0;
+  (meta.modified().ok(), meta.len(), inode)
+}
+
+/// 最近一次读取的设置；中转每个请求都会读取设置，文件未变化时直接复用，不重复读取解析
+static CACHE: Mutex<Option<(PathBuf, FileStamp, AppSettings)>> = Mutex::new(None);
+
+/// 读取应用设置，文件不存在或损坏时使用默认值；其他进程（如 CLI）修改后下次读取即生效
+pub fn read_settings() -> AppSettings {
+  let Ok(path) = settings_path() else {
+    return AppSettings::default();
+  };
+  let Ok(meta) = fs::metadata(&path) else {
+    return AppSettings::default();
+  };
+  let stamp = file_stamp(&meta);
+  if let Ok(guard) = CACHE.lock() {
+    if let Some((_, _, settings)) = guard.as_ref().filter(|(p, s, _)| *p == path && *s == stamp) {
+      return settings.clone();
+    }
+  }
+  let settings = match fs::read_to_string(&path) {
+    Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+      log::warn!("parse {} failed ({}), using defaults", path.display(), e);
+      AppSettings::default()
+    }),
+    Err(_) => return AppSettings::default(),
+  };
+  if let Ok(mut guard) = CACHE.lock() {
+    *guard = Some((path, stamp, settings.clone()));
+  }
+  settings
+}
+
+pub fn write_settings(settings: &AppSettings) -> AppResult<()> {
+  let s = serde_json::to_string_pretty(settings)?;
+  let path = settings_path()?;
+  atomic_write(&path, &s)?;
+  if let Ok(mut guard) = CACHE.lock() {
+    *guard = fs::metadata(&path).ok().map(|meta| (path, file_stamp(&meta), settings.clone()));
+  }
+  Ok(())
+}
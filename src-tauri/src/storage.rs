@@ -0,0 +1,121 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::backup::backup_before_write;
+use crate::error::{AppError, AppResult};
+use crate::permissions::{self, is_secret_path};
+
+/// 当前进程选中的 Codex 目录（GUI 中切换的目录或 CLI 的 --home 参数），为空时使用默认目录
+static HOME_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 设置当前进程操作的 Codex 目录，传入 None 恢复默认目录
+pub fn set_codex_dir_override(path: Option<PathBuf>) {
+  if let Ok(mut guard) = HOME_OVERRIDE.write() {
+    *guard = path;
+  }
+}
+
+/// 默认 Codex 目录：与 Codex 自身一致，优先使用 CODEX_HOME 环境变量，否则为 ~/.codex
+pub fn default_codex_dir() -> AppResult<PathBuf> {
+  if let Some(v) = std::env::var_os("CODEX_HOME").filter(|v| !v.is_empty()) {
+    return Ok(PathBuf::from(v));
+  }
+  dirs::home_dir()
+    .map(|h| h.join(".codex"))
+    .ok_or(AppError::HomeDirUnavailable)
+}
+
+/// 获取当前操作的 Codex 配置目录路径
+pub fn codex_dir() -> AppResult<PathBuf> {
+  let selected = HOME_OVERRIDE.read().ok().and_then(|g| g.clone());
+  match selected {
+    Some(p) => Ok(p),
+    None => default_codex_dir(),
+  }
+}
+
+/// 原子写入文件
+/// 使用临时文件先写入，再重命名，确保数据不会因为写入中断而损坏
+/// 密钥文件（auth.json、config.toml、codex-mate 下的文件）的临时文件以 0600 创建，其余文件沿用原有权限
+pub fn atomic_write(path: &std::path::Path, content: &str) -> AppResult<()> {
+  atomic_write_with_backup(path, content).map(|_| ())
+}
+
+/// 同 atomic_write，返回写入前生成的备份 ID；未生成备份（如新文件、内容未变化）时为 None
+pub fn atomic_write_with_backup(path: &std::path::Path, content: &str) -> AppResult<Option<String>> {
+  log::debug!("atomic_write: {} ({} bytes)", path.display(), content.len());
+
+  // 0. 写入前备份旧版本
+  let backup = backup_before_write(path, content)?;
+  
+  // 1. 获取父目录并创建
+  let parent = path.parent().ok_or_else(|| AppError::invalid(format!("invalid path {}", path.display())))?;
+  
+  let secret = is_secret_path(path);
+  permissions::create_dir_all(parent).map_err(|e| AppError::io("create dir", parent, e))?;
+  
+  // 2. 创建临时文件
+  let mut tmp = parent.to_path_buf();
+  tmp.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+  
+  {
+    let mut f = permissions::create_file(&tmp, secret, Some(path)).map_err(|e| AppError::io("create temp file", &tmp, e))?;
+    
+    f.write_all(content.as_bytes()).map_err(|e| AppError::io("write temp file", &tmp, e))?;
+    f.sync_all().ok();
+  }
+  
+  // 3. 重命名临时文件为目标文件（失败则尝试降级写入）
+  if let Err(e) = fs::rename(&tmp, path) {
+    log::warn!("atomic_write: rename to {} failed ({}), trying fallback write", path.display(), e);
+    // fallback: 读取临时文件内容，直接写入目标文件
+    let data = fs::read(&tmp).map_err(|e| AppError::io("fallback read temp", &tmp, e))?;
+    let mut f2 = permissions::create_file(path, secret, None).map_err(|e| AppError::io("fallback create", path, e))?;
+    f2.write_all(&data).map_err(|e| AppError::io("fallback write", path, e))?;
+    f2.sync_all().ok();
+    // 删除临时文件
+    let _ = fs::remove_file(&tmp);
+  }
+
+  // 4. 目录级别 fsync，确保重命名对文件系统可见（macOS/Unix 推荐）
+  if let Some(parent_dir) = path.parent() {
+    match fs::File::open(parent_dir) {
+      Ok(dir_file) => {
+        let _ = dir_file.sync_all();
+      }
+      Err(e) => log::debug!("atomic_write: open {} for fsync failed ({})", parent_dir.display(), e),
+    }
+  }
+  
+  Ok(backup)
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+  use std::fs;
+  use std::path::Path;
+  use std::sync::Mutex;
+
+  use super::set_codex_dir_override;
+
+  /// Codex 目录是进程级设置，使用临时目录的测试需串行执行
+  static LOCK: Mutex<()> = Mutex::new(());
+
+  /// 在写入了 files（相对路径, 内容）的临时 Codex 目录中运行 f，结束后恢复默认目录并删除临时目录
+  pub fn with_codex_home<T>(files: &[(&str, &str)], f: impl FnOnce(&Path) -> T) -> T {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = std::env::temp_dir().join(format!("codex-mate-test-{}", uuid::Uuid::new_v4()));
+    for (name, content) in files {
+      let path = dir.join(name);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(&path, content).unwrap();
+    }
+    set_codex_dir_override(Some(dir.clone()));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&dir)));
+    set_codex_dir_override(None);
+    let _ = fs::remove_dir_all(&dir);
+    result.unwrap_or_else(|e| std::panic::resume_unwind(e))
+  }
+}
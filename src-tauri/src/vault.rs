@@ -0,0 +1,332 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::XChaCha20Poly1305;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::backup::purge_backups;
+use crate::credentials::{credentials_path, read_credentials_value, write_credentials_value, STORED_ENV_KEY_FIELD};
+use crate::error::{AppError, AppResult};
+use crate::keys::KEY_POOL_FIELD;
+use crate::storage::{atomic_write, codex_dir};
+
+/// 加密后的凭据值前缀：enc:v1:<base64(nonce || ciphertext)>
+const ENC_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 24;
+/// 用于校验口令是否正确的固定明文
+const CHECK_PLAINTEXT: &str = "codex-mate-vault";
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// 加密库头信息: ~/.codex/codex-mate/vault.json
+/// 仅保存密钥派生参数与口令校验值，凭据本身仍在 credentials.json 中（值被逐个加密，节点名保持明文）
+#[derive(Serialize, Deserialize)]
+struct VaultHeader {
+  version: u32,
+  kdf: String,
+  salt: String,
+  m_cost: u32,
+  t_cost: u32,
+  p_cost: u32,
+  check: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct VaultStatus {
+  pub enabled: bool,
+  pub unlocked: bool,
+}
+
+/// 已解锁的密钥，与对应的 vault.json 路径绑定，切换 Codex 目录后自动失效
+static VAULT_KEY: Mutex<Option<(PathBuf, [u8; 32])>> = Mutex::new(None);
+
+fn vault_path() -> AppResult<PathBuf> {
+  Ok(codex_dir()?.join("codex-mate").join("vault.json"))
+}
+
+fn read_header() -> AppResult<Option<VaultHeader>> {
+  let path = vault_path()?;
+  match fs::read_to_string(&path) {
+    Ok(content) => serde_json::from_str(&content).map(Some).map_err(|e| AppError::json(&path, e)),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+    Err(e) => Err(AppError::io("read", &path, e)),
+  }
+}
+
+fn derive_key(passphrase: &str, header: &VaultHeader) -> AppResult<[u8; 32]> {
+  let salt = STANDARD.decode(&header.salt).map_err(|e| AppError::Internal { message: format!("invalid vault salt: {}", e) })?;
+  let params = Params::new(header.m_cost, header.t_cost, header.p_cost, Some(32))
+    .map_err(|e| AppError::Internal { message: format!("invalid vault parameters: {}", e) })?;
+  let mut key = [0u8; 32];
+  Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+    .map_err(|e| AppError::Internal { message: format!("derive vault key failed: {}", e) })?;
+  Ok(key)
+}
+
+fn encrypt_with(key: &[u8; 32], plaintext: &str) -> AppResult<String> {
+  let cipher = XChaCha20Poly1305::new(key.into());
+  let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+  let ciphertext = cipher
+    .encrypt(&nonce, plaintext.as_bytes())
+    .map_err(|_| AppError::Internal { message: "encrypt credential failed".into() })?;
+  let mut blob = nonce.to_vec();
+  blob.extend_from_slice(&ciphertext);
+  Ok(format!("{}{}", ENC_PREFIX, STANDARD.encode(blob)))
+}
+
+/// 解密失败返回 None（口令错误或数据被篡改）
+fn decrypt_with(key: &[u8; 32], value: &str) -> Option<String> {
+  let blob = STANDARD.decode(value.strip_prefix(ENC_PREFIX)?).ok()?;
+  if blob.len() <= NONCE_LEN {
+    return None;
+  }
+  let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+  let plaintext = XChaCha20Poly1305::new(key.into()).decrypt(nonce.into(), ciphertext).ok()?;
+  String::from_utf8(plaintext).ok()
+}
+
+fn set_key(key: Option<[u8; 32]>) -> AppResult<()> {
+  let path = vault_path()?;
+  if let Ok(mut guard) = VAULT_KEY.lock() {
+    if let Some((_, old)) = guard.as_mut() {
+      old.fill(0);
+    }
+    *guard = key.map(|k| (path, k));
+  }
+  Ok(())
+}
+
+fn current_key() -> AppResult<Option<[u8; 32]>> {
+  let path = vault_path()?;
+  let guard = VAULT_KEY.lock().map_err(|_| AppError::Internal { message: "vault key lock poisoned".into() })?;
+  Ok(guard.as_ref().filter(|(p, _)| *p == path).map(|(_, k)| *k))
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+  value.starts_with(ENC_PREFIX)
+}
+
+pub fn vault_enabled() -> AppResult<bool> {
+  Ok(vault_path()?.exists())
+}
+
+pub fn get_vault_status() -> AppResult<VaultStatus> {
+  let enabled = vault_enabled()?;
+  Ok(VaultStatus { enabled, unlocked: enabled && current_key()?.is_some() })
+}
+
+/// 写入新凭据前调用：已启用加密但尚未解锁时提前报错，避免只写入一半
+pub fn ensure_unlocked() -> AppResult<()> {
+  if vault_enabled()? && current_key()?.is_none() {
+    return Err(AppError::VaultLocked);
+  }
+  Ok(())
+}
+
+/// 校验口令并返回派生密钥
+fn open_vault(passphrase: &str) -> AppResult<[u8; 32]> {
+  let header = read_header()?.ok_or_else(|| AppError::invalid("credential vault is not enabled"))?;
+  check_passphrase(passphrase, &header)
+}
+
+fn check_passphrase(passphrase: &str, header: &VaultHeader) -> AppResult<[u8; 32]> {
+  let key = derive_key(passphrase, header)?;
+  match decrypt_with(&key, &header.check) {
+    Some(check) if check == CHECK_PLAINTEXT => Ok(key),
+    _ => Err(AppError::InvalidPassphrase),
+  }
+}
+
+pub fn unlock_vault(passphrase: String) -> AppResult<()> {
+  let key = open_vault(&passphrase)?;
+  set_key(Some(key))?;
+  log::info!("credential vault unlocked");
+  Ok(())
+}
+
+pub fn lock_vault() -> AppResult<()> {
+  set_key(None)?;
+  log::info!("credential vault locked");
+  Ok(())
+}
+
+/// 启用加密：生成 vault.json，将现有明文凭据全部加密，并清除 credentials.json 的明文备份
+pub fn enable_vault(passphrase: String) -> AppResult<()> {
+  if vault_enabled()? {
+    return Err(AppError::invalid("credential vault is already enabled"));
+  }
+  if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+    return Err(AppError::invalid(format!("passphrase must be at least {} characters", MIN_PASSPHRASE_LEN)));
+  }
+
+  let mut salt = [0u8; 16];
+  OsRng.fill_bytes(&mut salt);
+  let params = Params::default();
+  let mut header = VaultHeader {
+    version: 1,
+    kdf: "argon2id".into(),
+    salt: STANDARD.encode(salt),
+    m_cost: params.m_cost(),
+    t_cost: params.t_cost(),
+    p_cost: params.p_cost(),
+    check: String::new(),
+  };
+  let key = derive_key(&passphrase, &header)?;
+  header.check = encrypt_with(&key, CHECK_PLAINTEXT)?;
+
+  // 先写头信息再加密凭据：中途失败时明文值仍可读取，不会出现无法解密的数据
+  atomic_write(&vault_path()?, &serde_json::to_string_pretty(&header)?)?;
+  set_key(Some(key))?;
+  let map = read_credentials_value()?;
+  write_credentials_value(&map)?;
+  purge_backups(&credentials_path()?)?;
+  log::info!("credential vault enabled, {} providers encrypted", map.len());
+  Ok(())
+}
+
+/// 节点凭据中需要加密的值：顶层的字符串字段（字段名记录除外）以及密钥池中的每个密钥
+fn secret_slots(fields: &mut serde_json::Map<String, serde_json::Value>) -> Vec<&mut serde_json::Value> {
+  let mut slots = Vec::new();
+  for (name, value) in fields.iter_mut() {
+    if name == KEY_POOL_FIELD {
+      if let Some(keys) = value.get_mut("keys").and_then(|v| v.as_array_mut()) {
+        slots.extend(keys.iter_mut().filter_map(|k| k.get_mut("key")));
+      }
+    } else if name != STORED_ENV_KEY_FIELD && value.is_string() {
+      slots.push(value);
+    }
+  }
+  slots
+}
+
+/// 关闭加密：校验口令后将凭据还原为明文并删除 vault.json
+pub fn disable_vault(passphrase: String) -> AppResult<()> {
+  let key = open_vault(&passphrase)?;
+  let mut map = read_credentials_value()?;
+  for fields in map.values_mut().filter_map(|v| v.as_object_mut()) {
+    for value in secret_slots(fields) {
+      if let Some(s) = value.as_str().filter(|s| is_encrypted(s)) {
+        let plain = decrypt_with(&key, s).ok_or(AppError::InvalidPassphrase)?;
+        *value = serde_json::Value::String(plain);
+      }
+    }
+  }
+  // 直接写入明文，再删除头信息，避免 write_credentials_value 重新加密
+  let s = serde_json::to_string_pretty(&serde_json::Value::Object(map))?;
+  atomic_write(&credentials_path()?, &s)?;
+  let path = vault_path()?;
+  fs::remove_file(&path).map_err(|e| AppError::io("remove", &path, e))?;
+  purge_backups(&path)?;
+  set_key(None)?;
+  log::info!("credential vault disabled");
+  Ok(())
+}
+
+/// 写入前加密：启用加密时将每个节点下尚未加密的密钥值加密，已加密的值原样保留（锁定状态下也可删除节点）
+pub fn seal_credentials(map: &mut serde_json::Map<String, serde_json::Value>) -> AppResult<()> {
+  if !vault_enabled()? {
+    return Ok(());
+  }
+  let mut key = None;
+  for fields in map.values_mut().filter_map(|v| v.as_object_mut()) {
+    for value in secret_slots(fields) {
+      let Some(s) = value.as_str().filter(|s| !s.is_empty() && !is_encrypted(s)).map(str::to_string) else {
+        continue;
+      };
+      if key.is_none() {
+        key = Some(current_key()?.ok_or(AppError::VaultLocked)?);
+      }
+      if let Some(k) = key.as_ref() {
+        *value = serde_json::Value::String(encrypt_with(k, &s)?);
+      }
+    }
+  }
+  Ok(())
+}
+
+/// 取出凭据明文：加密值需先解锁，仅在真正使用密钥时调用（如切换节点）
+pub fn reveal(value: &str) -> AppResult<String> {
+  if !is_encrypted(value) {
+    return Ok(value.to_string());
+  }
+  let key = current_key()?.ok_or(AppError::VaultLocked)?;
+  decrypt_with(&key, value).ok_or_else(|| AppError::Internal { message: "decrypt credential failed, the vault may be corrupted".into() })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// 使用较小的 Argon2 参数，避免测试耗时
+  fn header(passphrase: &str) -> VaultHeader {
+    let mut header = VaultHeader {
+      version: 1,
+      kdf: "argon2id".into(),
+      salt: STANDARD.encode([7u8; 16]),
+      m_cost: 256,
+      t_cost: 1,
+      p_cost: 1,
+      check: String::new(),
+    };
+    header.check = encrypt_with(&derive_key(passphrase, &header).unwrap(), CHECK_PLAINTEXT).unwrap();
+    header
+  }
+
+  #[test]
+  fn encrypt_decrypt_round_trip() {
+    let header = header("correct horse");
+    let key = check_passphrase("correct horse", &header).unwrap();
+    let sealed = encrypt_with(&key, "sk-secret").unwrap();
+    assert!(is_encrypted(&sealed));
+    assert!(!sealed.contains("sk-secret"));
+    assert_eq!(decrypt_with(&key, &sealed).as_deref(), Some("sk-secret"));
+    // 每次加密使用新的 nonce
+    assert_ne!(encrypt_with(&key, "sk-secret").unwrap(), sealed);
+  }
+
+  #[test]
+  fn wrong_passphrase_is_rejected() {
+    let header = header("correct horse");
+    assert!(matches!(check_passphrase("battery staple", &header), Err(AppError::InvalidPassphrase)));
+
+    let key = check_passphrase("correct horse", &header).unwrap();
+    let sealed = encrypt_with(&key, "sk-secret").unwrap();
+    let wrong = derive_key("battery staple", &header).unwrap();
+    assert_eq!(decrypt_with(&wrong, &sealed), None);
+  }
+
+  #[test]
+  fn corrupted_values_do_not_decrypt() {
+    let key = derive_key("correct horse", &header("correct horse")).unwrap();
+    let sealed = encrypt_with(&key, "sk-secret").unwrap();
+    let mut blob = STANDARD.decode(sealed.strip_prefix(ENC_PREFIX).unwrap()).unwrap();
+    let last = blob.len() - 1;
+    blob[last] ^= 1;
+    assert_eq!(decrypt_with(&key, &format!("{}{}", ENC_PREFIX, STANDARD.encode(blob))), None);
+    assert_eq!(decrypt_with(&key, "sk-plain"), None);
+    assert_eq!(decrypt_with(&key, &format!("{}{}", ENC_PREFIX, STANDARD.encode([0u8; NONCE_LEN]))), None);
+  }
+
+  #[test]
+  fn plaintext_values_are_revealed_as_is() {
+    assert_eq!(reveal("sk-plain").unwrap(), "sk-plain");
+  }
+
+  #[test]
+  fn secret_slots_cover_keys_and_pool_only() {
+    let mut fields = serde_json::json!({
+      "B_KEY": "sk-current",
+      STORED_ENV_KEY_FIELD: "B_KEY",
+      KEY_POOL_FIELD: { "keys": [{ "label": "a", "key": "sk-a" }], "active": "a", "rotation": "manual" },
+    });
+    let slots = secret_slots(fields.as_object_mut().unwrap());
+    let mut values: Vec<&str> = slots.iter().filter_map(|v| v.as_str()).collect();
+    values.sort();
+    assert_eq!(values, ["sk-a", "sk-current"]);
+  }
+}
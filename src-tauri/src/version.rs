@@ -0,0 +1,199 @@
+use semver::Version;
+use serde::Serialize;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::{parse_key_path, parse_literal};
+
+#[derive(Serialize, Clone)]
+pub struct CodexVersion {
+  pub installed: bool,
+  pub version: Option<String>,
+  /// 从 version 中解析出的语义化版本号，如 "0.46.0"
+  pub semver: Option<String>,
+  pub error: Option<String>,
+}
+
+/// 本进程检测到的 Codex 版本；校验、写入配置时反复使用，避免每次都执行 codex --version
+static DETECTED: Mutex<Option<CodexVersion>> = Mutex::new(None);
+
+/// Codex 版本，首次调用时检测并缓存到进程结束；需要重新检测（如升级 Codex 后）时使用 refresh_codex_version
+pub fn get_codex_version() -> CodexVersion {
+  if let Some(cached) = DETECTED.lock().ok().and_then(|g| g.clone()) {
+    return cached;
+  }
+  refresh_codex_version()
+}
+
+/// 重新执行 codex --version 并更新缓存
+pub fn refresh_codex_version() -> CodexVersion {
+  let detected = detect_codex_version();
+  if let Ok(mut guard) = DETECTED.lock() {
+    *guard = Some(detected.clone());
+  }
+  detected
+}
+
+fn detect_codex_version() -> CodexVersion {
+  match Command::new("codex").arg("--version").output() {
+    Ok(out) => {
+      if out.status.success() {
+        let v = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        let semver = parse_version(&v).map(|s| s.to_string());
+        CodexVersion { installed: true, version: Some(v), semver, error: None }
+      } else {
+        CodexVersion { installed: false, version: None, semver: None, error: Some(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+      }
+    }
+    Err(e) => CodexVersion { installed: false, version: None, semver: None, error: Some(e.to_string()) },
+  }
+}
+
+/// 从 `codex-cli 0.46.0` 之类的输出中解析版本号，兼容 `v` 前缀和预发布后缀（如 0.47.0-alpha.2）
+pub fn parse_version(raw: &str) -> Option<Version> {
+  raw.split_whitespace().find_map(|word| Version::parse(word.trim_start_matches('v')).ok())
+}
+
+/// 已安装的 Codex 版本，未安装或无法解析时为 None
+pub fn installed_version() -> Option<Version> {
+  get_codex_version().version.as_deref().and_then(parse_version)
+}
+
+/// 已安装版本是否不低于 since；预发布版本视同正式版（0.48.0-alpha.1 满足 0.48.0）
+pub fn at_least(installed: &Version, (major, minor, patch): (u64, u64, u64)) -> bool {
+  (installed.major, installed.minor, installed.patch) >= (major, minor, patch)
+}
+
+pub fn version_string((major, minor, patch): (u64, u64, u64)) -> String {
+  format!("{}.{}.{}", major, minor, patch)
+}
+
+/// 配置项对 Codex 版本的要求；路径中的 `*` 匹配任意键名
+pub struct Requirement {
+  pub path: &'static str,
+  /// 仅当取值为该字符串时才有要求（如某个枚举值），None 表示设置该键即有要求
+  pub value: Option<&'static str>,
+  pub since: (u64, u64, u64),
+  pub feature: &'static str,
+}
+
+const fn requires(path: &'static str, since: (u64, u64, u64), feature: &'static str) -> Requirement {
+  Requirement { path, value: None, since, feature }
+}
+
+const fn requires_value(path: &'static str, value: &'static str, since: (u64, u64, u64), feature: &'static str) -> Requirement {
+  Requirement { path, value: Some(value), since, feature }
+}
+
+/// 兼容性表：各配置项与功能最早受支持的 Codex 版本，按 Codex 发布说明维护
+pub const COMPATIBILITY: &[Requirement] = &[
+  requires("profile", (0, 2, 0), "profiles"),
+  requires("profiles", (0, 2, 0), "profiles"),
+  requires("sandbox_mode", (0, 2, 0), "sandbox_mode"),
+  requires("sandbox_workspace_write", (0, 2, 0), "workspace-write sandbox options"),
+  requires("sandbox_workspace_write.exclude_tmpdir_env_var", (0, 20, 0), "workspace-write sandbox options"),
+  requires("sandbox_workspace_write.exclude_slash_tmp", (0, 20, 0), "workspace-write sandbox options"),
+  requires("model_verbosity", (0, 20, 0), "model_verbosity"),
+  requires("profiles.*.model_verbosity", (0, 20, 0), "model_verbosity"),
+  requires("projects", (0, 25, 0), "project trust levels"),
+  requires("mcp_servers.*.url", (0, 39, 0), "streamable HTTP MCP servers"),
+  requires("mcp_servers.*.bearer_token_env_var", (0, 44, 0), "streamable HTTP MCP servers"),
+  requires("mcp_servers.*.startup_timeout_sec", (0, 40, 0), "MCP startup timeout in seconds"),
+  requires("mcp_servers.*.tool_timeout_sec", (0, 40, 0), "MCP tool timeout"),
+  requires("mcp_servers.*.enabled", (0, 48, 0), "disabling MCP servers"),
+  requires("forced_login_method", (0, 45, 0), "forced_login_method"),
+  requires("mcp_oauth_credentials_store", (0, 46, 0), "MCP OAuth credential storage"),
+  requires("cli_auth_credentials_store", (0, 47, 0), "keyring credential storage"),
+  requires("features", (0, 48, 0), "[features] flags"),
+  requires("profiles.*.features", (0, 48, 0), "[features] flags"),
+  requires_value("model_reasoning_effort", "none", (0, 57, 0), "reasoning effort 'none'"),
+  requires_value("model_reasoning_effort", "xhigh", (0, 59, 0), "reasoning effort 'xhigh'"),
+  requires_value("profiles.*.model_reasoning_effort", "none", (0, 57, 0), "reasoning effort 'none'"),
+  requires_value("profiles.*.model_reasoning_effort", "xhigh", (0, 59, 0), "reasoning effort 'xhigh'"),
+];
+
+/// 查找已安装版本不满足的要求；path 为具体键路径，value 为该键的字符串取值（如有）
+pub fn unsupported(installed: &Version, path: &[String], value: Option<&str>) -> Option<&'static Requirement> {
+  COMPATIBILITY.iter().find(|r| {
+    let pattern: Vec<&str> = r.path.split('.').collect();
+    pattern.len() == path.len()
+      && pattern.iter().zip(path).all(|(p, k)| *p == "*" || p == k)
+      && r.value.is_none_or(|v| value == Some(v))
+      && !at_least(installed, r.since)
+  })
+}
+
+/// 不满足要求时的提示文本
+pub fn requirement_message(req: &Requirement, installed: &Version) -> String {
+  format!("{} requires Codex {} or newer (installed: {})", req.feature, version_string(req.since), installed)
+}
+
+/// 写入配置项时的版本提示，同时检查各级父表（features.x 受 features 的要求约束）；
+/// value 为写入的字符串取值（如有），已安装版本满足要求或未知时为 None
+pub fn path_warning(path: &[String], value: Option<&str>) -> Option<String> {
+  let installed = installed_version()?;
+  path_requirement(&installed, path, value).map(|req| requirement_message(req, &installed))
+}
+
+fn path_requirement(installed: &Version, path: &[String], value: Option<&str>) -> Option<&'static Requirement> {
+  (1..=path.len()).find_map(|n| unsupported(installed, &path[..n], if n == path.len() { value } else { None }))
+}
+
+/// 按点分路径写入配置时的版本提示（如 `config set`），value 按 TOML 字面量解析
+pub fn key_warning(path: &str, value: &str) -> Option<String> {
+  let path = parse_key_path(path).ok()?;
+  path_warning(&path, parse_literal(value).as_str())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn path(p: &str) -> Vec<String> {
+    p.split('.').map(str::to_string).collect()
+  }
+
+  #[test]
+  fn parses_version_output() {
+    assert_eq!(parse_version("codex-cli 0.46.0"), Some(Version::new(0, 46, 0)));
+    assert_eq!(parse_version("codex v0.47.1\n"), Some(Version::new(0, 47, 1)));
+    assert_eq!(parse_version("codex-cli 0.48.0-alpha.2").map(|v| v.to_string()).as_deref(), Some("0.48.0-alpha.2"));
+    assert_eq!(parse_version("codex-cli dev"), None);
+  }
+
+  #[test]
+  fn compares_versions_ignoring_prerelease() {
+    assert!(at_least(&Version::new(0, 48, 0), (0, 48, 0)));
+    assert!(at_least(&Version::new(1, 0, 0), (0, 59, 3)));
+    assert!(!at_least(&Version::new(0, 47, 9), (0, 48, 0)));
+    assert!(at_least(&Version::parse("0.48.0-alpha.1").unwrap(), (0, 48, 0)));
+    assert_eq!(version_string((0, 48, 0)), "0.48.0");
+  }
+
+  #[test]
+  fn compatibility_table_matches_wildcards_and_values() {
+    let old = Version::new(0, 45, 0);
+    assert_eq!(unsupported(&old, &path("mcp_servers.docs.enabled"), None).map(|r| r.since), Some((0, 48, 0)));
+    assert_eq!(unsupported(&old, &path("profiles.fast.model_reasoning_effort"), Some("none")).map(|r| r.feature), Some("reasoning effort 'none'"));
+    assert!(unsupported(&old, &path("profiles.fast.model_reasoning_effort"), Some("high")).is_none());
+    assert!(unsupported(&old, &path("mcp_servers.enabled"), None).is_none());
+    assert!(unsupported(&Version::new(0, 60, 0), &path("model_reasoning_effort"), Some("xhigh")).is_none());
+  }
+
+  #[test]
+  fn parent_tables_carry_requirements() {
+    let old = Version::new(0, 40, 0);
+    let req = path_requirement(&old, &path("features.unified_exec"), Some("true")).unwrap();
+    assert_eq!(req.path, "features");
+    assert_eq!(requirement_message(req, &old), "[features] flags requires Codex 0.48.0 or newer (installed: 0.40.0)");
+    assert!(path_requirement(&old, &path("model"), Some("gpt-5")).is_none());
+  }
+
+  #[test]
+  fn compatibility_entries_are_well_formed() {
+    for req in COMPATIBILITY {
+      assert!(!req.path.is_empty() && !req.path.split('.').any(str::is_empty), "{}", req.path);
+      assert!(!req.feature.is_empty());
+    }
+  }
+}
@@ -1,7 +1,11 @@
 import type { MenuItem } from '../types'
+import { Accounts } from '../ui/pages/Accounts'
+import { Backups } from '../ui/pages/Backups'
 import { ConfigEditor } from '../ui/pages/ConfigEditor'
+import { Logs } from '../ui/pages/Logs'
 import { Mcp } from '../ui/pages/Mcp'
 import { Nodes } from '../ui/pages/Nodes'
+import { Profiles } from '../ui/pages/Profiles'
 import { Projects } from '../ui/pages/Projects'
 import { Settings } from '../ui/pages/Settings'
 
@@ -24,11 +28,31 @@ export const menuConfig: MenuItem[] = [
     label: '节点管理',
     component: Nodes,
   },
+  {
+    id: 'accounts',
+    label: '账号管理',
+    component: Accounts,
+  },
+  {
+    id: 'profiles',
+    label: 'Profile 管理',
+    component: Profiles,
+  },
   {
     id: 'projects',
     label: '项目信任',
     component: Projects,
   },
+  {
+    id: 'backups',
+    label: '备份',
+    component: Backups,
+  },
+  {
+    id: 'logs',
+    label: '日志',
+    component: Logs,
+  },
   {
     id: 'settings',
     label: '设置',
@@ -1,6 +1,7 @@
 import { useCallback, useEffect, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import type { LoadingState } from '../types'
+import { formatError } from '../utils'
 
 /**
  * 通用的数据加载 Hook
@@ -20,7 +21,7 @@ export function useInvoke<T>(
       const result = await invoke<T>(command, params)
       setData(result)
     } catch (e: any) {
-      setError(String(e))
+      setError(formatError(e))
     } finally {
       setLoading(false)
     }
@@ -74,7 +75,7 @@ export function useAsyncAction() {
         setLoadingState(false, null, successMessage || '操作成功')
         return result
       } catch (e: any) {
-        setLoadingState(false, String(e))
+        setLoadingState(false, formatError(e))
         return null
       }
     },
@@ -16,30 +16,283 @@ export type McpServer = {
   args?: string[]
 }
 
+// 后端错误类型：code 为稳定的错误码，可用于分支处理
+export type AppError = {
+  code: string
+  message: string
+  path?: string
+  line?: number
+  col?: number
+  provider?: string
+  name?: string
+  kind?: string
+}
+
+// config.toml 中 [model_providers.<name>] 的字段（upsert_node 时未给出的字段保持原值，空串/空表删除该字段）
+export type ProviderConfig = {
+  base_url?: string
+  wire_api?: 'responses' | 'chat'
+  requires_openai_auth?: boolean
+  env_key?: string
+  env_key_instructions?: string
+  experimental_bearer_token?: string
+  query_params?: Record<string, string>
+  http_headers?: Record<string, string>
+  // 请求头名 -> 环境变量名
+  env_http_headers?: Record<string, string>
+  request_max_retries?: number
+  stream_max_retries?: number
+  stream_idle_timeout_ms?: number
+}
+
+// 节点模板（内置或团队模板文件）
+export type ProviderPreset = {
+  id: string
+  label: string
+  description?: string | null
+  node_name?: string | null
+  default_key?: string | null
+  // 字段中的 {变量} 在创建节点时替换
+  fields: ProviderConfig
+  variables: { name: string; description?: string | null; default?: string | null }[]
+  // builtin 或模板文件路径
+  source: string
+}
+
+export type PresetCatalog = {
+  presets: ProviderPreset[]
+  catalogs: string[]
+  errors: string[]
+}
+
 // Node Provider 类型
 export type Provider = {
   name: string
+  // api_key: model_providers 中配置的节点；chatgpt: ChatGPT 账号登录节点
+  kind: 'api_key' | 'chatgpt'
   base_url?: string
   wire_api?: string
   requires_openai_auth?: boolean
+  // 凭据对应的环境变量名（config.toml 中的 env_key，默认 OPENAI_API_KEY）
+  env_key: string
   has_credential: boolean
+  // 密钥池中的密钥数量
+  key_count: number
+  // 节点的完整配置
+  config: ProviderConfig
+  // config.toml 中无法解析而被跳过的字段
+  errors: string[]
 }
 
 export type NodeList = {
   current_provider?: string
+  relay: boolean
   providers: Provider[]
 }
 
+// config.toml 中 [profiles.<name>] 的常用字段（upsert_profile 时空串删除该字段）
+export type ProfileConfig = {
+  model?: string
+  model_provider?: string
+  approval_policy?: string
+  sandbox_mode?: string
+  model_reasoning_effort?: string
+  model_reasoning_summary?: string
+  model_verbosity?: string
+  // 其他字段原样返回，只能在配置编辑器中修改
+  [key: string]: unknown
+}
+
+export type ProfileInfo = {
+  name: string
+  // 是否为根级 profile 指定的默认 profile
+  active: boolean
+  config: ProfileConfig
+  // 类型不符、已被跳过的字段及原因
+  errors: string[]
+}
+
+export type ProfileList = {
+  default_profile?: string | null
+  profiles: ProfileInfo[]
+}
+
+// 配置检查结果，line / col 从 1 开始
+export type Diagnostic = {
+  severity: 'error' | 'warning'
+  path: string
+  message: string
+  line?: number | null
+  col?: number | null
+  suggestion?: string | null
+}
+
+// 已改名 / 移除的配置键迁移
+export type KeyMigration = {
+  from: string
+  to?: string | null
+  since: string
+  note: string
+  supported: boolean
+}
+
+export type MigrationPlan = {
+  codex_version?: string | null
+  migrations: KeyMigration[]
+  diff: string
+  backup?: string | null
+}
+
+// 生效配置：每项的来源
+export type ValueSource = 'root' | 'profile' | 'override' | 'default'
+
+export type ResolvedValue = {
+  key: string
+  value: unknown
+  source: ValueSource
+}
+
+export type EffectiveConfig = {
+  profile?: string | null
+  profile_source?: ValueSource | null
+  values: ResolvedValue[]
+  provider?: ProviderConfig | null
+}
+
+// 本地中转状态
+export type RelayStatus = {
+  enabled: boolean
+  running: boolean
+  port: number
+  base_url: string
+  target?: string | null
+  failover: string[]
+  cooldown_secs: number
+  degraded: { name: string; reason: string; remaining_secs: number }[]
+}
+
+// 节点健康检查结果
+export type ProviderHealth = {
+  name: string
+  url?: string | null
+  ok: boolean
+  status?: number | null
+  latency_ms?: number | null
+  has_credential: boolean
+  issue?: 'config' | 'vault_locked' | 'unauthorized' | 'http_status' | 'timeout' | 'tls' | 'connect' | 'network' | null
+  message?: string | null
+}
+
+// 节点模型列表（GET {base_url}/models，按节点缓存）
+export type ModelList = {
+  provider: string
+  models: string[]
+  fetched_at: number
+  cached: boolean
+}
+
+// 节点密钥池（仅含指纹）
+export type KeyRotation = 'manual' | 'round_robin' | 'on_429'
+
+export type KeyPoolInfo = {
+  provider: string
+  rotation: KeyRotation
+  active?: string | null
+  keys: { label: string; active: boolean; encrypted: boolean; fingerprint?: string | null }[]
+}
+
+// 凭据诊断类型（不含密钥明文）
+export type ProviderKeyInfo = {
+  name: string
+  // 凭据字段名（节点的 env_key）
+  env_key: string
+  configured: boolean
+  has_key: boolean
+  encrypted: boolean
+  fingerprint?: string | null
+}
+
+export type CredentialDiagnostics = {
+  path: string
+  exists: boolean
+  size?: number | null
+  parse_ok: boolean
+  parse_error?: string | null
+  vault_enabled: boolean
+  providers: ProviderKeyInfo[]
+  problems: string[]
+}
+
+// ChatGPT 账号快照类型
+export type AccountSnapshot = {
+  name: string
+  email?: string | null
+  plan?: string | null
+  account_id?: string | null
+  saved_at?: number | null
+  is_current: boolean
+}
+
+// 备份条目类型
+export type BackupEntry = {
+  id: string
+  file: string
+  created_at: number
+  size: number
+}
+
+export type BackupSettings = {
+  max_count: number
+  max_age_days: number
+}
+
 // Project 类型
 export type Project = {
   path: string
   trust_level: string
 }
 
+// Codex 目录类型
+export type HomeInfo = {
+  name: string
+  path: string
+  exists: boolean
+  is_active: boolean
+}
+
+export type HomeList = {
+  active: string
+  homes: HomeInfo[]
+}
+
+// 凭据加密状态
+export type VaultStatus = {
+  enabled: boolean
+  unlocked: boolean
+}
+
+// 文件权限审计结果
+export type PermissionIssue = {
+  path: string
+  is_dir: boolean
+  // 无法读取该路径时为 null
+  mode: string | null
+  expected: string | null
+  fixed: boolean
+  error?: string | null
+}
+
+export type PermissionReport = {
+  supported: boolean
+  checked: number
+  issues: PermissionIssue[]
+}
+
 // Codex Version 类型
 export type CodexVersion = {
   installed: boolean
   version?: string | null
+  semver?: string | null
   error?: string | null
 }
 
@@ -49,3 +302,17 @@ export type LoadingState = {
   error: string | null
   success: string | null
 }
+
+// 日志条目类型
+export type LogEntry = {
+  ts: number
+  level: 'ERROR' | 'WARN' | 'INFO' | 'DEBUG' | 'TRACE'
+  target: string
+  message: string
+}
+
+export type LogSettings = {
+  level: string
+  max_file_kb: number
+  max_files: number
+}
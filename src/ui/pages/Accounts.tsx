@@ -0,0 +1,153 @@
+import React, { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import type { AccountSnapshot } from '../../types'
+import { useAsyncAction, useFormState } from '../../hooks'
+import { validateRequired } from '../../utils'
+import { Loading, ErrorMessage, Message } from '../components/Common'
+
+/**
+ * ChatGPT 账号管理页面：保存当前登录为快照，在多个账号之间切换
+ */
+export function Accounts() {
+  const [list, setList] = useState<AccountSnapshot[]>([])
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+  const [form, updateField, updateForm, resetForm] = useFormState({
+    name: '',
+  })
+
+  const loadAccounts = async () => {
+    await execute(async () => {
+      const data = await invoke<AccountSnapshot[]>('list_account_snapshots')
+      setList(data)
+    })
+  }
+
+  useEffect(() => {
+    loadAccounts()
+  }, [])
+
+  const handleSave = async () => {
+    const validationError = validateRequired(form, ['name'])
+    if (validationError) {
+      return execute(async () => {
+        throw new Error(validationError)
+      })
+    }
+    if (list.some((a) => a.name === form.name.trim()) && !confirm(`快照 "${form.name.trim()}" 已存在，确定覆盖吗？`)) {
+      return
+    }
+
+    await execute(async () => {
+      await invoke('save_account_snapshot', { name: form.name })
+      resetForm()
+      await loadAccounts()
+    }, '当前登录已保存')
+  }
+
+  const handleSwitch = async (name: string) => {
+    await execute(async () => {
+      await invoke('restore_account_snapshot', { name })
+      await loadAccounts()
+    }, `已切换到账号 "${name}"`)
+  }
+
+  const handleDelete = async (name: string) => {
+    if (!confirm(`确定要删除账号快照 "${name}" 吗？`)) return
+
+    await execute(async () => {
+      await invoke('delete_account_snapshot', { name })
+      await loadAccounts()
+    }, '快照已删除')
+  }
+
+  return (
+    <div>
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">保存当前登录</h3>
+        </div>
+
+        {loading && <Loading />}
+        {error && <ErrorMessage error={error} />}
+        {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+        <div className="form-group">
+          <div className="input-group">
+            <input
+              type="text"
+              placeholder="快照名称，如 work"
+              value={form.name}
+              onChange={(e) => updateField('name', e.target.value)}
+              style={{ minWidth: 300 }}
+            />
+            <button onClick={handleSave} disabled={loading} className="btn btn-primary">
+              保存
+            </button>
+          </div>
+          <div style={{ marginTop: 8, color: 'var(--text-secondary)' }}>
+            保存的是 auth.json 中的 ChatGPT 登录信息；切换账号时当前登录会先写回其对应的快照，并切换到 ChatGPT 账号节点。
+          </div>
+        </div>
+      </div>
+
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">账号列表</h3>
+        </div>
+
+        <div className="table-container">
+          <table>
+            <thead>
+              <tr>
+                <th style={{ width: 150 }}>名称</th>
+                <th>账号</th>
+                <th style={{ width: 100 }}>套餐</th>
+                <th style={{ width: 180 }}>保存时间</th>
+                <th style={{ width: 200 }}>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {list.length === 0 ? (
+                <tr>
+                  <td colSpan={5} style={{ textAlign: 'center', color: 'var(--text-secondary)' }}>
+                    暂无账号快照
+                  </td>
+                </tr>
+              ) : (
+                list.map((account) => (
+                  <tr key={account.name}>
+                    <td>
+                      {account.name}
+                      {account.is_current && (
+                        <span className="badge badge-success" style={{ marginLeft: 8 }}>
+                          当前
+                        </span>
+                      )}
+                    </td>
+                    <td>{account.email || '未知账号'}</td>
+                    <td>{account.plan || '-'}</td>
+                    <td>{account.saved_at ? new Date(account.saved_at * 1000).toLocaleString() : '-'}</td>
+                    <td>
+                      <div className="btn-group">
+                        <button
+                          onClick={() => handleSwitch(account.name)}
+                          disabled={loading || account.is_current}
+                          className="btn btn-sm btn-success"
+                        >
+                          切换
+                        </button>
+                        <button onClick={() => handleDelete(account.name)} className="btn btn-sm btn-danger">
+                          删除
+                        </button>
+                      </div>
+                    </td>
+                  </tr>
+                ))
+              )}
+            </tbody>
+          </table>
+        </div>
+      </div>
+    </div>
+  )
+}
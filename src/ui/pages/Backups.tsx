@@ -0,0 +1,175 @@
+import React, { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import type { BackupEntry, BackupSettings } from '../../types'
+import { useAsyncAction } from '../../hooks'
+import { Loading, ErrorMessage, Message } from '../components/Common'
+
+/**
+ * 备份管理页面：查看写入前自动创建的备份，对比差异并恢复
+ */
+export function Backups() {
+  const [entries, setEntries] = useState<BackupEntry[]>([])
+  const [file, setFile] = useState('')
+  const [settings, setSettings] = useState<BackupSettings | null>(null)
+  const [diff, setDiff] = useState<{ id: string; text: string } | null>(null)
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const loadBackups = async () => {
+    await execute(async () => {
+      const data = await invoke<BackupEntry[]>('list_backups', { file: null })
+      setEntries(data)
+    })
+  }
+
+  useEffect(() => {
+    loadBackups()
+    invoke<BackupSettings>('get_backup_settings').then(setSettings).catch(() => {})
+  }, [])
+
+  const handleSaveSettings = async () => {
+    if (!settings) return
+    await execute(async () => {
+      await invoke('set_backup_settings', { settings })
+      await loadBackups()
+    }, '保留策略已保存')
+  }
+
+  const handleDiff = async (id: string) => {
+    await execute(async () => {
+      const text = await invoke<string>('diff_backup', { id })
+      setDiff({ id, text })
+    })
+  }
+
+  const handleRestore = async (entry: BackupEntry) => {
+    if (!confirm(`确定要将 ${entry.file} 恢复到 ${new Date(entry.created_at).toLocaleString()} 的备份吗？当前内容会先被备份。`)) return
+
+    await execute(async () => {
+      await invoke('restore_backup', { id: entry.id })
+      setDiff(null)
+      await loadBackups()
+    }, '备份已恢复')
+  }
+
+  const files = Array.from(new Set(entries.map((e) => e.file))).sort()
+  const visible = file ? entries.filter((e) => e.file === file) : entries
+
+  return (
+    <div>
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">保留策略</h3>
+        </div>
+
+        {error && <ErrorMessage error={error} />}
+        {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+        {settings && (
+          <div className="form-group">
+            <div className="input-group">
+              <label style={{ display: 'flex', alignItems: 'center', gap: 8, whiteSpace: 'nowrap' }}>
+                每个文件最多保留
+                <input
+                  type="number"
+                  min={0}
+                  value={settings.max_count}
+                  onChange={(e) => setSettings({ ...settings, max_count: Number(e.target.value) })}
+                  style={{ width: 80 }}
+                />
+                份
+              </label>
+              <label style={{ display: 'flex', alignItems: 'center', gap: 8, whiteSpace: 'nowrap' }}>
+                最长保留
+                <input
+                  type="number"
+                  min={0}
+                  value={settings.max_age_days}
+                  onChange={(e) => setSettings({ ...settings, max_age_days: Number(e.target.value) })}
+                  style={{ width: 80 }}
+                />
+                天
+              </label>
+              <button onClick={handleSaveSettings} disabled={loading} className="btn btn-primary">
+                保存
+              </button>
+            </div>
+            <div style={{ marginTop: 8, color: 'var(--text-secondary)' }}>
+              0 表示不限制；保存后立即清理超出策略的已有备份。
+            </div>
+          </div>
+        )}
+      </div>
+
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">备份列表</h3>
+          <div className="btn-group">
+            <select value={file} onChange={(e) => setFile(e.target.value)}>
+              <option value="">全部文件</option>
+              {files.map((f) => (
+                <option key={f} value={f}>{f}</option>
+              ))}
+            </select>
+            <button onClick={loadBackups} disabled={loading} className="btn btn-outline">
+              刷新
+            </button>
+          </div>
+        </div>
+
+        {loading && <Loading />}
+
+        <div className="table-container">
+          <table>
+            <thead>
+              <tr>
+                <th>文件</th>
+                <th style={{ width: 180 }}>备份时间</th>
+                <th style={{ width: 100 }}>大小</th>
+                <th style={{ width: 200 }}>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {visible.length === 0 ? (
+                <tr>
+                  <td colSpan={4} style={{ textAlign: 'center', color: 'var(--text-secondary)' }}>
+                    暂无备份
+                  </td>
+                </tr>
+              ) : (
+                visible.map((entry) => (
+                  <tr key={entry.id}>
+                    <td>{entry.file}</td>
+                    <td>{new Date(entry.created_at).toLocaleString()}</td>
+                    <td>{entry.size} B</td>
+                    <td>
+                      <div className="btn-group">
+                        <button onClick={() => handleDiff(entry.id)} className="btn btn-sm btn-outline">
+                          对比
+                        </button>
+                        <button onClick={() => handleRestore(entry)} disabled={loading} className="btn btn-sm btn-danger">
+                          恢复
+                        </button>
+                      </div>
+                    </td>
+                  </tr>
+                ))
+              )}
+            </tbody>
+          </table>
+        </div>
+      </div>
+
+      {diff && (
+        <div className="card">
+          <div className="card-header">
+            <h3 className="card-title">与当前文件对比：{diff.id}</h3>
+            <button onClick={() => setDiff(null)} className="btn btn-outline">
+              关闭
+            </button>
+          </div>
+          <pre className="code-editor">{diff.text || '与当前文件相同'}</pre>
+        </div>
+      )}
+    </div>
+  )
+}
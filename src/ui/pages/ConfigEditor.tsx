@@ -1,13 +1,181 @@
-import React, { useEffect, useState } from 'react'
+import React, { useEffect, useRef, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import type { Diagnostic, EffectiveConfig, MigrationPlan, ValueSource } from '../../types'
 import { useAsyncAction } from '../../hooks'
 import { Loading, ErrorMessage, Message } from '../components/Common'
 
+const SOURCE_LABELS: Record<ValueSource, string> = {
+  root: '根级',
+  profile: 'Profile',
+  override: '覆盖',
+  default: 'Codex 默认',
+}
+
+/**
+ * 生效配置：按 profile 与 -c 覆盖项计算 Codex 实际使用的取值及来源
+ */
+function EffectivePanel() {
+  const [profile, setProfile] = useState('')
+  const [overrides, setOverrides] = useState('')
+  const [result, setResult] = useState<EffectiveConfig | null>(null)
+  const { loading, error, execute } = useAsyncAction()
+
+  const resolve = async () => {
+    await execute(async () => {
+      const lines = overrides.split('\n').map((l) => l.trim()).filter(Boolean)
+      setResult(await invoke<EffectiveConfig>('resolve_config', { profile: profile.trim() || null, overrides: lines }))
+    })
+  }
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">生效配置</h3>
+        <button onClick={resolve} disabled={loading} className="btn btn-primary">
+          计算
+        </button>
+      </div>
+
+      {error && <ErrorMessage error={error} />}
+
+      <div className="form-group">
+        <div className="input-group">
+          <input
+            type="text"
+            placeholder="Profile（留空使用默认 profile）"
+            value={profile}
+            onChange={(e) => setProfile(e.target.value)}
+          />
+        </div>
+        <textarea
+          value={overrides}
+          onChange={(e) => setOverrides(e.target.value)}
+          placeholder={'-c 覆盖项，每行一个，如\nmodel="o3"\nprofiles.fast.sandbox_mode="workspace-write"'}
+          rows={3}
+        />
+      </div>
+
+      {result && (
+        <div className="table-container">
+          <Message type="info">
+            Profile: <strong>{result.profile || '未使用'}</strong>
+            {result.profile_source && `（${SOURCE_LABELS[result.profile_source]}）`}
+            {result.provider?.base_url && `，节点地址: ${result.provider.base_url}`}
+          </Message>
+          <table>
+            <thead>
+              <tr>
+                <th>键</th>
+                <th>取值</th>
+                <th>来源</th>
+              </tr>
+            </thead>
+            <tbody>
+              {result.values.map((v) => (
+                <tr key={v.key}>
+                  <td><code>{v.key}</code></td>
+                  <td><code>{v.value === null ? '（随 Codex 版本）' : JSON.stringify(v.value)}</code></td>
+                  <td>{SOURCE_LABELS[v.source]}</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
+    </div>
+  )
+}
+
+/**
+ * 配置迁移：检查已改名或移除的键，预览 diff 后应用（应用前自动备份）
+ */
+function MigrationPanel({ onApplied }: { onApplied: () => void }) {
+  const [plan, setPlan] = useState<MigrationPlan | null>(null)
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const check = async () => {
+    await execute(async () => {
+      setPlan(await invoke<MigrationPlan>('plan_migrations'))
+    })
+  }
+
+  const apply = async () => {
+    if (!confirm('确定应用迁移吗？config.toml 会先被备份')) return
+    await execute(async () => {
+      await invoke<MigrationPlan>('apply_migrations')
+      setPlan(null)
+      onApplied()
+    }, '迁移已应用，原 config.toml 已备份')
+  }
+
+  const applicable = plan ? plan.migrations.filter((m) => m.supported).length : 0
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">配置迁移</h3>
+        <div className="btn-group">
+          <button onClick={check} disabled={loading} className="btn btn-outline">
+            检查过时的键
+          </button>
+          {applicable > 0 && (
+            <button onClick={apply} disabled={loading} className="btn btn-primary">
+              应用 {applicable} 项迁移
+            </button>
+          )}
+        </div>
+      </div>
+
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+      {plan && (
+        <div>
+          <Message type="info">
+            Codex 版本: {plan.codex_version || '未检测到'}
+            {plan.migrations.length === 0 && '，未发现过时的键'}
+          </Message>
+          {plan.migrations.length > 0 && (
+            <div className="table-container">
+              <table>
+                <thead>
+                  <tr>
+                    <th>旧键</th>
+                    <th>新键</th>
+                    <th>说明</th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {plan.migrations.map((m) => (
+                    <tr key={m.from}>
+                      <td><code>{m.from}</code></td>
+                      <td>{m.to ? <code>{m.to}</code> : '删除'}</td>
+                      <td>
+                        {m.note}
+                        {!m.supported && (
+                          <span style={{ color: 'var(--warning-color)' }}>（需要 Codex {m.since} 或更高版本，暂不迁移）</span>
+                        )}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            </div>
+          )}
+          {plan.diff && <pre className="code-editor">{plan.diff}</pre>}
+        </div>
+      )}
+    </div>
+  )
+}
+
 /**
  * 配置文件编辑器页面
  */
 export function ConfigEditor() {
   const [content, setContent] = useState('')
+  const [diagnostics, setDiagnostics] = useState<Diagnostic[] | null>(null)
+  const editorRef = useRef<HTMLTextAreaElement>(null)
   const { loading, error, success, execute, clearMessages } = useAsyncAction()
 
   const loadConfig = async () => {
@@ -26,12 +194,32 @@ export function ConfigEditor() {
     loadConfig()
   }, [])
 
+  const validate = async () => {
+    const result = await invoke<Diagnostic[]>('validate_config', { content })
+    setDiagnostics(result)
+    return result
+  }
+
   const saveConfig = async () => {
+    // 检查结果仅作提示：Codex 新版本可能支持尚未收录的键
+    const errors = (await validate()).filter((d) => d.severity === 'error')
+    if (errors.length > 0 && !confirm(`配置中有 ${errors.length} 个错误，仍要保存吗？`)) return
+
     await execute(async () => {
       await invoke('write_config_raw', { content })
     }, '配置已保存')
   }
 
+  // 将光标移动到诊断所在位置
+  const jumpTo = (d: Diagnostic) => {
+    const editor = editorRef.current
+    if (!editor || !d.line) return
+    const lines = content.split('\n')
+    const offset = lines.slice(0, d.line - 1).reduce((n, l) => n + l.length + 1, 0) + (d.col || 1) - 1
+    editor.focus()
+    editor.setSelectionRange(offset, offset)
+  }
+
   return (
     <div>
       <div className="card">
@@ -45,6 +233,9 @@ export function ConfigEditor() {
             >
               保存
             </button>
+            <button onClick={() => execute(validate)} disabled={loading} className="btn btn-outline">
+              检查
+            </button>
             <button 
               onClick={loadConfig} 
               disabled={loading} 
@@ -59,8 +250,29 @@ export function ConfigEditor() {
         {error && <ErrorMessage error={error} onRetry={loadConfig} />}
         {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
 
+        {diagnostics && (
+          <div style={{ marginBottom: 16 }}>
+            {diagnostics.length === 0 ? (
+              <Message type="success" onClose={() => setDiagnostics(null)}>未发现问题</Message>
+            ) : (
+              <Message type="warning" onClose={() => setDiagnostics(null)}>
+                {diagnostics.map((d, i) => (
+                  <div key={i} onClick={() => jumpTo(d)} style={{ cursor: d.line ? 'pointer' : undefined }}>
+                    <span style={{ color: d.severity === 'error' ? 'var(--danger-color)' : 'var(--warning-color)' }}>
+                      {d.severity === 'error' ? '错误' : '警告'}
+                    </span>
+                    {d.line && ` 第 ${d.line} 行第 ${d.col} 列`}
+                    {d.path && <code style={{ marginLeft: 4 }}>{d.path}</code>}: {d.message}
+                  </div>
+                ))}
+              </Message>
+            )}
+          </div>
+        )}
+
         <div className="form-group">
           <textarea
+            ref={editorRef}
             value={content}
             onChange={(e) => setContent(e.target.value)}
             className="code-editor"
@@ -68,6 +280,10 @@ export function ConfigEditor() {
           />
         </div>
       </div>
+
+      <MigrationPanel onApplied={loadConfig} />
+
+      <EffectivePanel />
     </div>
   )
 }
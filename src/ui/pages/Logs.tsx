@@ -0,0 +1,150 @@
+import React, { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import type { LogEntry, LogSettings } from '../../types'
+import { useAsyncAction } from '../../hooks'
+import { Loading, ErrorMessage, Message } from '../components/Common'
+
+const LEVELS = ['error', 'warn', 'info', 'debug', 'trace']
+
+/**
+ * 日志查看页面
+ */
+export function Logs() {
+  const [entries, setEntries] = useState<LogEntry[]>([])
+  const [filter, setFilter] = useState('info')
+  const [settings, setSettings] = useState<LogSettings | null>(null)
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const loadLogs = async (level = filter) => {
+    await execute(async () => {
+      const data = await invoke<LogEntry[]>('tail_logs', { limit: 500, level })
+      setEntries(data.reverse())
+    })
+  }
+
+  useEffect(() => {
+    loadLogs()
+    invoke<LogSettings>('get_log_settings').then(setSettings).catch(() => {})
+  }, [])
+
+  const handleFilterChange = async (level: string) => {
+    setFilter(level)
+    await loadLogs(level)
+  }
+
+  const handleSaveSettings = async () => {
+    if (!settings) return
+    await execute(async () => {
+      await invoke('set_log_settings', { settings })
+    }, '日志设置已保存')
+  }
+
+  return (
+    <div>
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">日志设置</h3>
+        </div>
+
+        {error && <ErrorMessage error={error} />}
+        {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+        {settings && (
+          <div className="form-group">
+            <div className="input-group">
+              <label style={{ display: 'flex', alignItems: 'center', gap: 8, whiteSpace: 'nowrap' }}>
+                记录级别
+                <select
+                  value={settings.level}
+                  onChange={(e) => setSettings({ ...settings, level: e.target.value })}
+                >
+                  {LEVELS.map((l) => (
+                    <option key={l} value={l}>{l}</option>
+                  ))}
+                </select>
+              </label>
+              <label style={{ display: 'flex', alignItems: 'center', gap: 8, whiteSpace: 'nowrap' }}>
+                单文件上限 (KB)
+                <input
+                  type="number"
+                  min={1}
+                  value={settings.max_file_kb}
+                  onChange={(e) => setSettings({ ...settings, max_file_kb: Number(e.target.value) })}
+                  style={{ width: 100 }}
+                />
+              </label>
+              <label style={{ display: 'flex', alignItems: 'center', gap: 8, whiteSpace: 'nowrap' }}>
+                保留文件数
+                <input
+                  type="number"
+                  min={0}
+                  value={settings.max_files}
+                  onChange={(e) => setSettings({ ...settings, max_files: Number(e.target.value) })}
+                  style={{ width: 80 }}
+                />
+              </label>
+              <button onClick={handleSaveSettings} disabled={loading} className="btn btn-primary">
+                保存
+              </button>
+            </div>
+          </div>
+        )}
+      </div>
+
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">最近日志</h3>
+          <div className="btn-group">
+            <select value={filter} onChange={(e) => handleFilterChange(e.target.value)}>
+              {LEVELS.map((l) => (
+                <option key={l} value={l}>{l} 及以上</option>
+              ))}
+            </select>
+            <button onClick={() => loadLogs()} disabled={loading} className="btn btn-outline">
+              刷新
+            </button>
+          </div>
+        </div>
+
+        {loading && <Loading />}
+
+        <div className="table-container">
+          <table>
+            <thead>
+              <tr>
+                <th style={{ width: 180 }}>时间</th>
+                <th style={{ width: 80 }}>级别</th>
+                <th style={{ width: 200 }}>模块</th>
+                <th>内容</th>
+              </tr>
+            </thead>
+            <tbody>
+              {entries.length === 0 ? (
+                <tr>
+                  <td colSpan={4} style={{ textAlign: 'center', color: 'var(--text-secondary)' }}>
+                    暂无日志
+                  </td>
+                </tr>
+              ) : (
+                entries.map((entry, i) => (
+                  <tr key={`${entry.ts}-${i}`}>
+                    <td>{new Date(entry.ts).toLocaleString()}</td>
+                    <td>
+                      {entry.level === 'ERROR' || entry.level === 'WARN' ? (
+                        <span className="badge badge-danger">{entry.level}</span>
+                      ) : (
+                        entry.level
+                      )}
+                    </td>
+                    <td>{entry.target.replace(/^codex_mate::/, '')}</td>
+                    <td style={{ wordBreak: 'break-all' }}>{entry.message}</td>
+                  </tr>
+                ))
+              )}
+            </tbody>
+          </table>
+        </div>
+      </div>
+    </div>
+  )
+}
@@ -1,8 +1,8 @@
 import React, { useEffect, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
-import type { NodeList, Provider } from '../../types'
+import type { CredentialDiagnostics, KeyPoolInfo, KeyRotation, ModelList, NodeList, PresetCatalog, Provider, ProviderConfig, ProviderHealth, RelayStatus } from '../../types'
 import { useAsyncAction, useFormState } from '../../hooks'
-import { validateRequired } from '../../utils'
+import { formatError, validateRequired } from '../../utils'
 import { Loading, ErrorMessage, Message } from '../components/Common'
 
 /**
@@ -10,11 +10,15 @@ import { Loading, ErrorMessage, Message } from '../components/Common'
  */
 export function Nodes() {
   const [data, setData] = useState<NodeList | null>(null)
+  const [health, setHealth] = useState<Record<string, ProviderHealth>>({})
+  // 切换到 env_key 不是 OPENAI_API_KEY 的节点时，需提示用户导出对应的环境变量
+  const [switchWarning, setSwitchWarning] = useState<string | null>(null)
   const { loading, error, success, execute, clearMessages } = useAsyncAction()
   const [form, updateField, updateForm, resetForm] = useFormState({
     name: '',
     base_url: '',
     requires_openai_auth: false,
+    env_key: '',
     key: '',
   })
 
@@ -38,11 +42,11 @@ export function Nodes() {
     }
 
     await execute(async () => {
-      const providerFields = {
-        name: form.name,
+      const providerFields: ProviderConfig = {
         base_url: form.base_url,
         wire_api: 'responses',
         requires_openai_auth: form.requires_openai_auth,
+        ...(form.env_key ? { env_key: form.env_key } : {}),
       }
       const credential = form.key || undefined
       await invoke('upsert_node', {
@@ -55,9 +59,17 @@ export function Nodes() {
     }, '节点已保存')
   }
 
+  const handleCheckAll = async () => {
+    await execute(async () => {
+      const results = await invoke<ProviderHealth[]>('check_providers', {})
+      setHealth(Object.fromEntries(results.map((r) => [r.name, r])))
+    }, '连通性检测完成')
+  }
+
   const handleSwitchNode = async (name: string) => {
+    setSwitchWarning(null)
     await execute(async () => {
-      await invoke('switch_node', { name })
+      setSwitchWarning(await invoke<string | null>('switch_node', { name }))
       await loadNodes()
     }, `已切换到节点: ${name}`)
   }
@@ -73,6 +85,19 @@ export function Nodes() {
     }, '节点已删除')
   }
 
+  // 重命名会同步更新凭据、model_provider 及各 profile 的引用；复制则保留原节点
+  const handleCopyNode = async (name: string, rename: boolean) => {
+    const newName = prompt(rename ? `将节点 ${name} 重命名为：` : `复制节点 ${name} 为：`, rename ? name : `${name}-copy`)?.trim()
+    if (!newName || newName === name) {
+      return
+    }
+
+    await execute(async () => {
+      await invoke(rename ? 'rename_node' : 'duplicate_node', { name, newName })
+      await loadNodes()
+    }, rename ? `已重命名为: ${newName}` : `已复制为: ${newName}`)
+  }
+
   /**
    * 更新节点凭据
    * @param name 节点名称
@@ -92,17 +117,12 @@ export function Nodes() {
   await invoke('update_node_credential', { name, openaiApiKey: trimmed })
       console.log('[DEBUG] Credential updated, reloading nodes...')
       await loadNodes()
-      try {
-        const creds = await invoke<Record<string, any>>('get_credentials')
-      } catch (e) {
-        console.warn('[WARN] get_credentials invoke failed:', e)
-      }
       console.log('[DEBUG] Nodes reloaded successfully')
       return true
     } catch (e: any) {
       console.error('[ERROR] update_node_credential invoke failed:', e)
       // 将后端错误抛给用户，便于迅速定位
-      alert(`保存凭据失败: ${String(e)}`)
+      alert(`保存凭据失败: ${formatError(e)}`)
       return false
     }
   }
@@ -117,11 +137,13 @@ export function Nodes() {
         {loading && <Loading />}
         {error && <ErrorMessage error={error} />}
         {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+        {switchWarning && <Message type="warning" onClose={() => setSwitchWarning(null)}>{switchWarning}</Message>}
 
         {data && (
           <div style={{ marginBottom: 16 }}>
             <Message type="info">
               当前激活节点: <strong>{data.current_provider || '未设置'}</strong>
+              {data.relay && '（经本地中转，切换即时生效）'}
             </Message>
           </div>
         )}
@@ -149,9 +171,16 @@ export function Nodes() {
               />
               需要 OpenAI 认证
             </label>
+            <input
+              type="text"
+              placeholder="env_key（默认 OPENAI_API_KEY）"
+              value={form.env_key}
+              onChange={(e) => updateField('env_key', e.target.value.trim())}
+              style={{ minWidth: 200 }}
+            />
             <input
               type="password"
-              placeholder="OPENAI_API_KEY（可选）"
+              placeholder={`${form.env_key || 'OPENAI_API_KEY'}（可选）`}
               value={form.key}
               onChange={(e) => updateField('key', e.target.value)}
               style={{ minWidth: 240 }}
@@ -167,9 +196,14 @@ export function Nodes() {
         <div className="card">
           <div className="card-header">
             <h3 className="card-title">节点列表</h3>
-            <button onClick={loadNodes} disabled={loading} className="btn btn-outline">
-              刷新
-            </button>
+            <div className="btn-group">
+              <button onClick={handleCheckAll} disabled={loading} className="btn btn-outline">
+                检测连通性
+              </button>
+              <button onClick={loadNodes} disabled={loading} className="btn btn-outline">
+                刷新
+              </button>
+            </div>
           </div>
 
           <div className="table-container">
@@ -181,13 +215,14 @@ export function Nodes() {
                   <th>Wire API</th>
                   <th>需要认证</th>
                   <th>有凭据</th>
+                  <th>连通性</th>
                   <th style={{ width: 280 }}>操作</th>
                 </tr>
               </thead>
               <tbody>
                 {data.providers.length === 0 ? (
                   <tr>
-                    <td colSpan={6} style={{ textAlign: 'center', color: 'var(--text-secondary)' }}>
+                    <td colSpan={7} style={{ textAlign: 'center', color: 'var(--text-secondary)' }}>
                       暂无节点
                     </td>
                   </tr>
@@ -197,8 +232,10 @@ export function Nodes() {
                       key={provider.name}
                       provider={provider}
                       isActive={provider.name === data.current_provider}
+                      health={health[provider.name]}
                       onSwitch={handleSwitchNode}
                       onDelete={handleDeleteNode}
+                      onCopy={handleCopyNode}
                       onUpdateCredential={handleUpdateCredential}
                     />
                   ))
@@ -208,6 +245,567 @@ export function Nodes() {
           </div>
         </div>
       )}
+
+      <PresetPicker onChange={loadNodes} />
+
+      <RelayCard onChange={loadNodes} />
+
+      {data && (
+        <ModelPicker
+          providers={data.providers.filter((p) => p.kind === 'api_key').map((p) => p.name)}
+          current={data.current_provider}
+        />
+      )}
+
+      {data && (
+        <KeyPoolManager
+          providers={data.providers.filter((p) => p.kind === 'api_key').map((p) => p.name)}
+          onChange={loadNodes}
+        />
+      )}
+
+      <CredentialCheck />
+    </div>
+  )
+}
+
+/**
+ * 节点模板：从内置或团队模板文件创建节点，只需填写模板中的变量
+ */
+function PresetPicker({ onChange }: { onChange: () => Promise<void> }) {
+  const [catalog, setCatalog] = useState<PresetCatalog | null>(null)
+  const [id, setId] = useState('')
+  const [name, setName] = useState('')
+  const [values, setValues] = useState<Record<string, string>>({})
+  const [key, setKey] = useState('')
+  const [catalogInput, setCatalogInput] = useState('')
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const applyCatalog = (result: PresetCatalog) => {
+    setCatalog(result)
+    setCatalogInput(result.catalogs.join(', '))
+    if (!result.presets.some((p) => p.id === id)) {
+      setId(result.presets[0]?.id || '')
+      setValues({})
+    }
+  }
+
+  useEffect(() => {
+    invoke<PresetCatalog>('list_presets').then(applyCatalog).catch(() => {})
+  }, [])
+
+  const preset = catalog?.presets.find((p) => p.id === id)
+
+  const handleApply = async () => {
+    await execute(async () => {
+      await invoke<string>('apply_preset', {
+        id,
+        name: name.trim() || null,
+        values,
+        credential: key.trim() || null,
+      })
+      setName('')
+      setKey('')
+      await onChange()
+    }, `已从模板 ${preset?.label} 创建节点`)
+  }
+
+  const handleSaveCatalogs = async () => {
+    const paths = catalogInput.split(',').map((s) => s.trim()).filter(Boolean)
+    await execute(async () => applyCatalog(await invoke<PresetCatalog>('set_preset_catalogs', { paths })), '模板文件已更新')
+  }
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">从模板添加</h3>
+      </div>
+
+      {loading && <Loading />}
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+      {catalog?.errors.map((e) => (
+        <Message key={e} type="warning">{e}</Message>
+      ))}
+
+      {catalog && (
+        <div className="form-group">
+          <div className="input-group">
+            <select
+              value={id}
+              onChange={(e) => {
+                setId(e.target.value)
+                setValues({})
+              }}
+            >
+              {catalog.presets.map((p) => (
+                <option key={p.id} value={p.id}>{p.label}{p.source !== 'builtin' ? '（团队）' : ''}</option>
+              ))}
+            </select>
+            <input
+              type="text"
+              placeholder={`节点名称（默认 ${preset?.node_name || id}）`}
+              value={name}
+              onChange={(e) => setName(e.target.value.trim())}
+            />
+            {preset?.variables.map((v) => (
+              <input
+                key={v.name}
+                type="text"
+                placeholder={v.default ? `${v.name}（默认 ${v.default}）` : v.name}
+                title={v.description || undefined}
+                value={values[v.name] || ''}
+                onChange={(e) => setValues({ ...values, [v.name]: e.target.value })}
+              />
+            ))}
+            <input
+              type="password"
+              placeholder={`${preset?.fields.env_key || 'OPENAI_API_KEY'}${preset?.default_key ? '（可选）' : ''}`}
+              value={key}
+              onChange={(e) => setKey(e.target.value)}
+            />
+            <button onClick={handleApply} disabled={loading || !preset} className="btn btn-primary">
+              创建节点
+            </button>
+          </div>
+          {preset?.description && <div style={{ marginTop: 8, color: 'var(--text-secondary)' }}>{preset.description}</div>}
+        </div>
+      )}
+
+      <div className="form-group">
+        <div className="input-group">
+          <input
+            type="text"
+            placeholder="团队模板文件路径，多个以逗号分隔"
+            value={catalogInput}
+            onChange={(e) => setCatalogInput(e.target.value)}
+            style={{ minWidth: 320 }}
+          />
+          <button onClick={handleSaveCatalogs} disabled={loading} className="btn btn-outline">
+            保存模板文件
+          </button>
+        </div>
+      </div>
+    </div>
+  )
+}
+
+/**
+ * 本地中转：Codex 固定使用 codex-mate 节点，由中转将请求转发到当前节点，切换节点无需重启 Codex
+ */
+function RelayCard({ onChange }: { onChange: () => Promise<void> }) {
+  const [status, setStatus] = useState<RelayStatus | null>(null)
+  const [port, setPort] = useState('')
+  const [failover, setFailover] = useState('')
+  const [disableWarning, setDisableWarning] = useState<string | null>(null)
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const loadStatus = async () => {
+    const result = await invoke<RelayStatus>('get_relay_status')
+    setStatus(result)
+    setPort(String(result.port))
+    setFailover(result.failover.join(', '))
+  }
+
+  useEffect(() => {
+    loadStatus().catch(() => {})
+  }, [])
+
+  const handleEnable = async () => {
+    await execute(async () => {
+      setStatus(await invoke<RelayStatus>('enable_relay', { port: Number(port) || null }))
+      await onChange()
+    }, '本地中转已启用，请重启一次 Codex 使其连接中转')
+  }
+
+  const handleSaveFailover = async () => {
+    const nodes = failover.split(/[,\s]+/).filter(Boolean)
+    await execute(async () => {
+      setStatus(await invoke<RelayStatus>('set_relay_failover', { nodes }))
+    }, nodes.length ? '故障转移组已保存' : '已关闭故障转移')
+  }
+
+  const handleDisable = async () => {
+    setDisableWarning(null)
+    await execute(async () => {
+      setDisableWarning(await invoke<string | null>('disable_relay'))
+      await loadStatus()
+      await onChange()
+    }, '本地中转已关闭')
+  }
+
+  if (!status) return null
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">本地中转</h3>
+        {status.enabled && (
+          <span className={`badge ${status.running ? 'badge-success' : 'badge-danger'}`}>
+            {status.running ? '运行中' : '未运行'}
+          </span>
+        )}
+      </div>
+
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+      {disableWarning && <Message type="warning" onClose={() => setDisableWarning(null)}>{disableWarning}</Message>}
+
+      <div className="form-group">
+        {status.enabled ? (
+          <div className="input-group">
+            <span>
+              {status.base_url} → <strong>{status.target || '-'}</strong>
+            </span>
+            <button onClick={handleDisable} disabled={loading} className="btn btn-outline">
+              关闭中转
+            </button>
+          </div>
+        ) : (
+          <div className="input-group">
+            <label style={{ display: 'flex', alignItems: 'center', gap: 8, whiteSpace: 'nowrap' }}>
+              端口
+              <input type="number" value={port} onChange={(e) => setPort(e.target.value)} style={{ width: 100 }} />
+            </label>
+            <button onClick={handleEnable} disabled={loading} className="btn btn-primary">
+              启用中转
+            </button>
+          </div>
+        )}
+      </div>
+
+      {status.enabled && (
+        <div className="form-group">
+          <div className="input-group">
+            <input
+              type="text"
+              placeholder="备用节点，按顺序以逗号分隔"
+              value={failover}
+              onChange={(e) => setFailover(e.target.value)}
+              style={{ minWidth: 280 }}
+            />
+            <button onClick={handleSaveFailover} disabled={loading} className="btn btn-outline">
+              保存故障转移组
+            </button>
+          </div>
+          <div style={{ marginTop: 8, color: 'var(--text-secondary)' }}>
+            当前节点返回 5xx / 429 或超时时依次改用备用节点，失败节点降级 {status.cooldown_secs} 秒
+          </div>
+          {status.degraded.map((d) => (
+            <div key={d.name} style={{ marginTop: 4 }}>
+              <span className="badge badge-danger" title={d.reason}>
+                {d.name} 降级中，剩余 {d.remaining_secs}s
+              </span>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
+
+/**
+ * 模型选择：从节点的 /models 接口拉取模型列表（结果按节点缓存），设置根级或指定 profile 的 model
+ */
+function ModelPicker({ providers, current }: { providers: string[]; current?: string | null }) {
+  const [provider, setProvider] = useState(current && providers.includes(current) ? current : providers[0] || '')
+  const [list, setList] = useState<ModelList | null>(null)
+  const [model, setModel] = useState('')
+  const [profile, setProfile] = useState('')
+  const [warnings, setWarnings] = useState<string[]>([])
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const loadModels = async (refresh: boolean) => {
+    if (!provider) return
+    await execute(async () => {
+      const result = await invoke<ModelList>('list_provider_models', { name: provider, refresh })
+      setList(result)
+      setModel(result.models[0] || '')
+    })
+  }
+
+  const handleSetModel = async () => {
+    const target = profile.trim() ? `profiles.${profile.trim()}.model` : 'model'
+    setWarnings([])
+    await execute(async () => {
+      setWarnings(await invoke<string[]>('set_model', { model, provider, profile: profile.trim() || null }))
+    }, `${target} 已设置为 ${model}`)
+  }
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">选择模型</h3>
+      </div>
+
+      {loading && <Loading />}
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+      {warnings.map((w) => (
+        <Message key={w} type="warning">{w}</Message>
+      ))}
+
+      <div className="form-group">
+        <div className="input-group">
+          <select
+            value={provider}
+            onChange={(e) => {
+              setProvider(e.target.value)
+              setList(null)
+            }}
+          >
+            {providers.map((p) => (
+              <option key={p} value={p}>{p}</option>
+            ))}
+          </select>
+          <button onClick={() => loadModels(false)} disabled={loading || !provider} className="btn btn-outline">
+            加载模型
+          </button>
+          <button onClick={() => loadModels(true)} disabled={loading || !provider} className="btn btn-outline">
+            重新拉取
+          </button>
+        </div>
+      </div>
+
+      {list && (
+        <div className="form-group">
+          <div style={{ marginBottom: 8, color: 'var(--text-secondary)' }}>
+            共 {list.models.length} 个模型，{list.cached ? '缓存于' : '拉取于'} {new Date(list.fetched_at).toLocaleString()}
+          </div>
+          <div className="input-group">
+            <select value={model} onChange={(e) => setModel(e.target.value)} style={{ minWidth: 240 }}>
+              {list.models.map((m) => (
+                <option key={m} value={m}>{m}</option>
+              ))}
+            </select>
+            <input
+              type="text"
+              placeholder="profile（留空设置根级 model）"
+              value={profile}
+              onChange={(e) => setProfile(e.target.value)}
+            />
+            <button onClick={handleSetModel} disabled={loading || !model} className="btn btn-primary">
+              设为模型
+            </button>
+          </div>
+        </div>
+      )}
+    </div>
+  )
+}
+
+const ROTATION_LABELS: Record<KeyRotation, string> = {
+  manual: '手动选择',
+  round_robin: '轮询',
+  on_429: '遇 429 切换',
+}
+
+/**
+ * 多密钥管理：每个节点可保存多个带标签的密钥，并按轮换策略使用
+ */
+function KeyPoolManager({ providers, onChange }: { providers: string[]; onChange: () => Promise<void> }) {
+  const [provider, setProvider] = useState(providers[0] || '')
+  const [pool, setPool] = useState<KeyPoolInfo | null>(null)
+  const [label, setLabel] = useState('')
+  const [key, setKey] = useState('')
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const loadPool = async (name = provider) => {
+    if (!name) return
+    setPool(await invoke<KeyPoolInfo>('list_provider_keys', { name }))
+  }
+
+  useEffect(() => {
+    loadPool().catch(() => {})
+  }, [provider])
+
+  const run = async (action: () => Promise<unknown>, message: string) => {
+    await execute(async () => {
+      await action()
+      await loadPool()
+      await onChange()
+    }, message)
+  }
+
+  const handleAdd = () =>
+    run(async () => {
+      await invoke('add_provider_key', { name: provider, label: label.trim(), key: key.trim() })
+      setLabel('')
+      setKey('')
+    }, '密钥已添加')
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">多密钥</h3>
+        <div className="btn-group">
+          <select value={provider} onChange={(e) => setProvider(e.target.value)}>
+            {providers.map((p) => (
+              <option key={p} value={p}>{p}</option>
+            ))}
+          </select>
+          {pool && (
+            <select
+              value={pool.rotation}
+              onChange={(e) => run(() => invoke('set_key_rotation', { name: provider, rotation: e.target.value }), '轮换策略已保存')}
+            >
+              {Object.entries(ROTATION_LABELS).map(([value, text]) => (
+                <option key={value} value={value}>{text}</option>
+              ))}
+            </select>
+          )}
+        </div>
+      </div>
+
+      {loading && <Loading />}
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+      <div className="form-group">
+        <div className="input-group">
+          <input type="text" placeholder="标签，如 alice" value={label} onChange={(e) => setLabel(e.target.value)} />
+          <input
+            type="password"
+            placeholder="API Key"
+            value={key}
+            onChange={(e) => setKey(e.target.value)}
+            style={{ minWidth: 240 }}
+          />
+          <button onClick={handleAdd} disabled={loading || !label.trim() || !key.trim()} className="btn btn-primary">
+            添加密钥
+          </button>
+        </div>
+      </div>
+
+      {pool && pool.keys.length > 0 && (
+        <div className="table-container">
+          <table>
+            <thead>
+              <tr>
+                <th>标签</th>
+                <th>密钥指纹</th>
+                <th style={{ width: 200 }}>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {pool.keys.map((k) => (
+                <tr key={k.label} style={{ fontWeight: k.active ? 600 : 400 }}>
+                  <td>
+                    {k.label}
+                    {k.active && <span className="badge badge-success" style={{ marginLeft: 8 }}>使用中</span>}
+                  </td>
+                  <td>{k.fingerprint || (k.encrypted ? '已加密（未解锁）' : '-')}</td>
+                  <td>
+                    <div className="btn-group">
+                      <button
+                        onClick={() => run(() => invoke('select_provider_key', { name: provider, label: k.label }), `已改用密钥 ${k.label}`)}
+                        disabled={loading || k.active}
+                        className="btn btn-sm btn-primary"
+                      >
+                        使用
+                      </button>
+                      <button
+                        onClick={() => run(() => invoke('remove_provider_key', { name: provider, label: k.label }), '密钥已删除')}
+                        disabled={loading}
+                        className="btn btn-sm btn-danger"
+                      >
+                        删除
+                      </button>
+                    </div>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
+    </div>
+  )
+}
+
+/**
+ * 凭据诊断：展示 credentials.json 状态与各节点密钥指纹，查看明文需显式确认并记入审计日志
+ */
+function CredentialCheck() {
+  const [report, setReport] = useState<CredentialDiagnostics | null>(null)
+  const [revealed, setRevealed] = useState<Record<string, string>>({})
+  const { loading, error, execute } = useAsyncAction()
+
+  const runCheck = async () => {
+    await execute(async () => {
+      setReport(await invoke<CredentialDiagnostics>('credentials_diagnostics'))
+      setRevealed({})
+    })
+  }
+
+  const handleReveal = async (name: string) => {
+    if (!confirm(`确定要显示节点 "${name}" 的密钥明文吗？此操作会记录到日志。`)) return
+    await execute(async () => {
+      const key = await invoke<string>('reveal_credential', { name })
+      setRevealed((prev) => ({ ...prev, [name]: key }))
+    })
+  }
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">凭据诊断</h3>
+        <button onClick={runCheck} disabled={loading} className="btn btn-outline">
+          {report ? '重新检查' : '检查'}
+        </button>
+      </div>
+
+      {loading && <Loading />}
+      {error && <ErrorMessage error={error} />}
+
+      {report && (
+        <>
+          <p style={{ marginBottom: 8 }}>
+            <strong>{report.path}</strong>
+            {' · '}
+            {!report.exists
+              ? '文件不存在'
+              : report.parse_error
+                ? `解析失败: ${report.parse_error}`
+                : `${report.size ?? 0} 字节${report.vault_enabled ? ' · 已加密' : ''}`}
+          </p>
+
+          {report.problems.map((p) => (
+            <Message key={p} type="warning">{p}</Message>
+          ))}
+
+          <div className="table-container">
+            <table>
+              <thead>
+                <tr>
+                  <th>节点</th>
+                  <th>已配置</th>
+                  <th>密钥指纹</th>
+                  <th style={{ width: 200 }}>操作</th>
+                </tr>
+              </thead>
+              <tbody>
+                {report.providers.map((p) => (
+                  <tr key={p.name}>
+                    <td>{p.name}</td>
+                    <td>{p.configured ? '是' : '否'}</td>
+                    <td>
+                      {revealed[p.name] ?? (p.fingerprint || (p.has_key ? '（已加密，未解锁）' : '无密钥'))}
+                    </td>
+                    <td>
+                      {p.has_key && !revealed[p.name] && (
+                        <button onClick={() => handleReveal(p.name)} className="btn btn-sm btn-outline">
+                          显示明文
+                        </button>
+                      )}
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        </>
+      )}
     </div>
   )
 }
@@ -215,12 +813,38 @@ export function Nodes() {
 type NodeRowProps = {
   provider: Provider
   isActive: boolean
+  health?: ProviderHealth
   onSwitch: (name: string) => void
   onDelete: (name: string, isActive: boolean) => void
+  onCopy: (name: string, rename: boolean) => void
   onUpdateCredential: (name: string, key: string) => Promise<boolean>
 }
 
-function NodeRow({ provider, isActive, onSwitch, onDelete, onUpdateCredential }: NodeRowProps) {
+const HEALTH_ISSUE_LABELS: Record<string, string> = {
+  config: '配置不完整',
+  vault_locked: '凭据未解锁',
+  unauthorized: '鉴权失败',
+  http_status: '请求失败',
+  timeout: '超时',
+  tls: 'TLS 错误',
+  connect: '无法连接',
+  network: '网络错误',
+}
+
+function HealthBadge({ health }: { health?: ProviderHealth }) {
+  if (!health) return <>-</>
+  if (health.ok) {
+    return <span className="badge badge-success">{health.status} · {health.latency_ms}ms</span>
+  }
+  const label = HEALTH_ISSUE_LABELS[health.issue || ''] || '失败'
+  return (
+    <span className="badge badge-danger" title={health.message || ''}>
+      {health.status ? `${health.status} ${label}` : label}
+    </span>
+  )
+}
+
+function NodeRow({ provider, isActive, health, onSwitch, onDelete, onCopy, onUpdateCredential }: NodeRowProps) {
   const [showKeyEditor, setShowKeyEditor] = useState(false)
   const [keyValue, setKeyValue] = useState('')
 
@@ -248,26 +872,29 @@ function NodeRow({ provider, isActive, onSwitch, onDelete, onUpdateCredential }:
       setShowKeyEditor(false)
     } else {
       // 失败时保留输入，避免用户二次输入；并弹出提示
-      alert('保存凭据失败，请查看页面顶部错误信息或后端日志')
-      try {
-        const info = await invoke('debug_credentials_info')
-        console.log('[DEBUG] debug_credentials_info:', info)
-      } catch (e) {
-        console.warn('[WARN] debug_credentials_info invoke failed:', e)
-      }
+      alert('保存凭据失败，请查看页面顶部错误信息或下方凭据诊断')
     }
   }
 
   return (
     <tr style={{ fontWeight: isActive ? 600 : 400 }}>
       <td>
-        {provider.name}
+        {provider.kind === 'chatgpt' ? `${provider.name} (ChatGPT 账号)` : provider.name}
         {isActive && <span className="badge badge-success" style={{ marginLeft: 8 }}>活跃</span>}
+        {provider.errors.length > 0 && (
+          <span className="badge badge-danger" style={{ marginLeft: 8 }} title={provider.errors.join('\n')}>
+            {provider.errors.length} 个字段无效
+          </span>
+        )}
       </td>
       <td>{provider.base_url || '-'}</td>
       <td>{provider.wire_api || '-'}</td>
       <td>{provider.requires_openai_auth ? '是' : '否'}</td>
-      <td>{provider.has_credential ? '✓' : '✗'}</td>
+      <td>
+        {provider.has_credential ? '✓' : '✗'}
+        {provider.key_count > 1 && <span style={{ marginLeft: 4, color: 'var(--text-secondary)' }}>×{provider.key_count}</span>}
+      </td>
+      <td>{provider.kind === 'api_key' ? <HealthBadge health={health} /> : '-'}</td>
       <td>
         {!showKeyEditor ? (
           <div className="btn-group">
@@ -278,24 +905,34 @@ function NodeRow({ provider, isActive, onSwitch, onDelete, onUpdateCredential }:
             >
               切换
             </button>
-            <button
-              onClick={() => setShowKeyEditor(true)}
-              className="btn btn-sm btn-outline"
-            >
-              {provider.has_credential ? '更新凭据' : '设置凭据'}
-            </button>
-            <button
-              onClick={() => onDelete(provider.name, isActive)}
-              className="btn btn-sm btn-danger"
-            >
-              删除
-            </button>
+            {provider.kind === 'api_key' && (
+              <>
+                <button
+                  onClick={() => setShowKeyEditor(true)}
+                  className="btn btn-sm btn-outline"
+                >
+                  {provider.has_credential ? '更新凭据' : '设置凭据'}
+                </button>
+                <button onClick={() => onCopy(provider.name, true)} className="btn btn-sm btn-outline">
+                  重命名
+                </button>
+                <button onClick={() => onCopy(provider.name, false)} className="btn btn-sm btn-outline">
+                  复制
+                </button>
+                <button
+                  onClick={() => onDelete(provider.name, isActive)}
+                  className="btn btn-sm btn-danger"
+                >
+                  删除
+                </button>
+              </>
+            )}
           </div>
         ) : (
           <div className="input-group">
             <input
               type="password"
-              placeholder="OPENAI_API_KEY"
+              placeholder={provider.env_key}
               value={keyValue}
               onChange={(e) => setKeyValue(e.target.value)}
               style={{ minWidth: 180 }}
@@ -0,0 +1,221 @@
+import React, { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import type { NodeList, ProfileConfig, ProfileInfo, ProfileList } from '../../types'
+import { useAsyncAction, useFormState } from '../../hooks'
+import { validateRequired } from '../../utils'
+import { Loading, ErrorMessage, Message } from '../components/Common'
+
+const APPROVAL_POLICIES = ['untrusted', 'on-failure', 'on-request', 'never']
+const SANDBOX_MODES = ['read-only', 'workspace-write', 'danger-full-access']
+const REASONING_EFFORTS = ['minimal', 'low', 'medium', 'high']
+
+const EMPTY_FORM = {
+  name: '',
+  model_provider: '',
+  model: '',
+  approval_policy: '',
+  sandbox_mode: '',
+  model_reasoning_effort: '',
+}
+
+/**
+ * Profile 管理页面：[profiles.*] 的增删改，以及根级 `profile`（默认 profile）
+ */
+export function Profiles() {
+  const [data, setData] = useState<ProfileList | null>(null)
+  const [providers, setProviders] = useState<string[]>([])
+  // 已安装的 Codex 版本过低、无法识别所写字段时的提示
+  const [warnings, setWarnings] = useState<string[]>([])
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+  const [form, updateField, updateForm, resetForm] = useFormState(EMPTY_FORM)
+
+  const loadProfiles = async () => {
+    await execute(async () => {
+      const [profiles, nodes] = await Promise.all([invoke<ProfileList>('list_profiles'), invoke<NodeList>('list_nodes')])
+      setData(profiles)
+      setProviders(nodes.providers.map((p) => p.name))
+    })
+  }
+
+  useEffect(() => {
+    loadProfiles()
+  }, [])
+
+  const handleSave = async () => {
+    const validationError = validateRequired(form, ['name'])
+    if (validationError) {
+      return execute(async () => {
+        throw new Error(validationError)
+      })
+    }
+
+    // 空值会删除对应字段
+    const fields: ProfileConfig = {
+      model_provider: form.model_provider,
+      model: form.model.trim(),
+      approval_policy: form.approval_policy,
+      sandbox_mode: form.sandbox_mode,
+      model_reasoning_effort: form.model_reasoning_effort,
+    }
+    setWarnings([])
+    await execute(async () => {
+      setWarnings(await invoke<string[]>('upsert_profile', { name: form.name, fields }))
+      resetForm()
+      await loadProfiles()
+    }, 'Profile 已保存')
+  }
+
+  const handleEdit = (profile: ProfileInfo) => {
+    updateForm({
+      name: profile.name,
+      model_provider: profile.config.model_provider || '',
+      model: profile.config.model || '',
+      approval_policy: profile.config.approval_policy || '',
+      sandbox_mode: profile.config.sandbox_mode || '',
+      model_reasoning_effort: profile.config.model_reasoning_effort || '',
+    })
+  }
+
+  const handleActivate = async (name: string | null) => {
+    await execute(async () => {
+      await invoke('activate_profile', { name })
+      await loadProfiles()
+    }, name ? `默认 profile 已设为: ${name}` : '已取消默认 profile')
+  }
+
+  const handleDelete = async (profile: ProfileInfo) => {
+    const message = profile.active ? `"${profile.name}" 是默认 profile，确定仍要删除？` : `确定要删除 profile "${profile.name}" 吗？`
+    if (!confirm(message)) return
+
+    await execute(async () => {
+      await invoke('delete_profile', { name: profile.name, force: profile.active })
+      await loadProfiles()
+    }, 'Profile 已删除')
+  }
+
+  const select = (field: keyof typeof EMPTY_FORM, label: string, options: string[]) => (
+    <select value={form[field]} onChange={(e) => updateField(field, e.target.value)}>
+      <option value="">{label}</option>
+      {options.map((o) => (
+        <option key={o} value={o}>{o}</option>
+      ))}
+    </select>
+  )
+
+  return (
+    <div>
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">添加 / 编辑 Profile</h3>
+        </div>
+
+        {loading && <Loading />}
+        {error && <ErrorMessage error={error} />}
+        {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+        {warnings.map((w) => (
+          <Message key={w} type="warning">{w}</Message>
+        ))}
+
+        {data && (
+          <div style={{ marginBottom: 16 }}>
+            <Message type="info">
+              默认 profile: <strong>{data.default_profile || '未设置'}</strong>
+              {data.default_profile && '（其 model_provider 优先于节点管理中的当前节点）'}
+            </Message>
+          </div>
+        )}
+
+        <div className="form-group">
+          <div className="input-group">
+            <input
+              type="text"
+              placeholder="Profile 名称"
+              value={form.name}
+              onChange={(e) => updateField('name', e.target.value.trim())}
+            />
+            {select('model_provider', 'model_provider（未设置）', providers)}
+            <input
+              type="text"
+              placeholder="model（留空则不设置）"
+              value={form.model}
+              onChange={(e) => updateField('model', e.target.value)}
+            />
+            {select('approval_policy', 'approval_policy（未设置）', APPROVAL_POLICIES)}
+            {select('sandbox_mode', 'sandbox_mode（未设置）', SANDBOX_MODES)}
+            {select('model_reasoning_effort', 'reasoning effort（未设置）', REASONING_EFFORTS)}
+            <button onClick={handleSave} disabled={loading} className="btn btn-primary">
+              保存
+            </button>
+          </div>
+        </div>
+      </div>
+
+      <div className="card">
+        <div className="card-header">
+          <h3 className="card-title">Profile 列表</h3>
+        </div>
+
+        <div className="table-container">
+          <table>
+            <thead>
+              <tr>
+                <th>名称</th>
+                <th>节点</th>
+                <th>模型</th>
+                <th>审批</th>
+                <th>沙箱</th>
+                <th>推理强度</th>
+                <th style={{ width: 260 }}>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {!data || data.profiles.length === 0 ? (
+                <tr>
+                  <td colSpan={7} style={{ textAlign: 'center', color: 'var(--text-secondary)' }}>
+                    暂无 Profile
+                  </td>
+                </tr>
+              ) : (
+                data.profiles.map((p) => (
+                  <tr key={p.name}>
+                    <td>
+                      {p.name}
+                      {p.active && <span style={{ marginLeft: 4, color: 'var(--success-color)' }}>（默认）</span>}
+                      {p.errors.length > 0 && (
+                        <span className="badge badge-danger" style={{ marginLeft: 8 }} title={p.errors.join('\n')}>
+                          {p.errors.length} 个字段无效
+                        </span>
+                      )}
+                    </td>
+                    <td>{p.config.model_provider || '-'}</td>
+                    <td>{p.config.model || '-'}</td>
+                    <td>{p.config.approval_policy || '-'}</td>
+                    <td>{p.config.sandbox_mode || '-'}</td>
+                    <td>{p.config.model_reasoning_effort || '-'}</td>
+                    <td>
+                      <div className="btn-group">
+                        <button
+                          onClick={() => handleActivate(p.active ? null : p.name)}
+                          disabled={loading}
+                          className="btn btn-sm btn-primary"
+                        >
+                          {p.active ? '取消默认' : '设为默认'}
+                        </button>
+                        <button onClick={() => handleEdit(p)} className="btn btn-sm btn-outline">
+                          编辑
+                        </button>
+                        <button onClick={() => handleDelete(p)} disabled={loading} className="btn btn-sm btn-danger">
+                          删除
+                        </button>
+                      </div>
+                    </td>
+                  </tr>
+                ))
+              )}
+            </tbody>
+          </table>
+        </div>
+      </div>
+    </div>
+  )
+}
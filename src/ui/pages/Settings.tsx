@@ -1,6 +1,8 @@
 import React, { useCallback, useEffect, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
-import type { CodexVersion } from '../../types'
+import type { CodexVersion, HomeList, PermissionReport, VaultStatus } from '../../types'
+import { useAsyncAction, useFormState } from '../../hooks'
+import { formatError } from '../../utils'
 import { Loading, ErrorMessage, Message } from '../components/Common'
 
 /**
@@ -19,7 +21,7 @@ export function Settings() {
       setState({
         installed: false,
         version: null,
-        error: String(e),
+        error: formatError(e),
       })
     } finally {
       setLoading(false)
@@ -80,6 +82,12 @@ export function Settings() {
         )}
       </div>
 
+      <CodexHomes />
+
+      <CredentialVault />
+
+      <FilePermissions />
+
       <div className="card">
         <div className="card-header">
           <h3 className="card-title">关于</h3>
@@ -93,3 +101,309 @@ export function Settings() {
     </div>
   )
 }
+
+/**
+ * Codex 目录管理：默认目录（CODEX_HOME 或 ~/.codex）及额外登记的目录
+ */
+function CodexHomes() {
+  const [data, setData] = useState<HomeList | null>(null)
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+  const [form, updateField, , resetForm] = useFormState({ name: '', path: '' })
+
+  const loadHomes = async () => {
+    await execute(async () => {
+      setData(await invoke<HomeList>('list_codex_homes'))
+    })
+  }
+
+  useEffect(() => {
+    loadHomes()
+  }, [])
+
+  const handleUse = async (name: string) => {
+    await execute(async () => {
+      await invoke('set_active_codex_home', { name })
+      await loadHomes()
+    }, `已切换到目录: ${name}`)
+  }
+
+  const handleAdd = async () => {
+    await execute(async () => {
+      await invoke('add_codex_home', { name: form.name, path: form.path })
+      resetForm()
+      await loadHomes()
+    }, '目录已添加')
+  }
+
+  const handleRemove = async (name: string) => {
+    await execute(async () => {
+      await invoke('remove_codex_home', { name })
+      await loadHomes()
+    }, '目录已移除')
+  }
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">Codex 目录</h3>
+      </div>
+
+      {loading && <Loading />}
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+      <div className="form-group">
+        <div className="input-group">
+          <input
+            type="text"
+            placeholder="名称"
+            value={form.name}
+            onChange={(e) => updateField('name', e.target.value.trim())}
+          />
+          <input
+            type="text"
+            placeholder="目录路径"
+            value={form.path}
+            onChange={(e) => updateField('path', e.target.value)}
+            style={{ minWidth: 280 }}
+          />
+          <button onClick={handleAdd} disabled={loading} className="btn btn-primary">
+            添加目录
+          </button>
+        </div>
+      </div>
+
+      {data && (
+        <div className="table-container">
+          <table>
+            <thead>
+              <tr>
+                <th>名称</th>
+                <th>路径</th>
+                <th style={{ width: 200 }}>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {data.homes.map((home) => (
+                <tr key={home.name} style={{ fontWeight: home.is_active ? 600 : 400 }}>
+                  <td>
+                    {home.name}
+                    {home.is_active && <span className="badge badge-success" style={{ marginLeft: 8 }}>当前</span>}
+                  </td>
+                  <td>
+                    {home.path}
+                    {!home.exists && <span style={{ marginLeft: 8, color: 'var(--text-secondary)' }}>(不存在)</span>}
+                  </td>
+                  <td>
+                    <div className="btn-group">
+                      <button
+                        onClick={() => handleUse(home.name)}
+                        disabled={home.is_active}
+                        className="btn btn-sm btn-primary"
+                      >
+                        切换
+                      </button>
+                      {home.name !== 'default' && (
+                        <button onClick={() => handleRemove(home.name)} className="btn btn-sm btn-danger">
+                          移除
+                        </button>
+                      )}
+                    </div>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
+    </div>
+  )
+}
+
+/**
+ * 凭据加密：启用后 credentials.json 中的密钥以口令派生的密钥加密保存，切换节点前需解锁
+ */
+function CredentialVault() {
+  const [status, setStatus] = useState<VaultStatus | null>(null)
+  const [passphrase, setPassphrase] = useState('')
+  const [confirmation, setConfirmation] = useState('')
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const loadStatus = async () => {
+    await execute(async () => {
+      setStatus(await invoke<VaultStatus>('get_vault_status'))
+    })
+  }
+
+  useEffect(() => {
+    loadStatus()
+  }, [])
+
+  const run = async (command: string, message: string) => {
+    await execute(async () => {
+      await invoke(command, command === 'lock_vault' ? {} : { passphrase })
+      setPassphrase('')
+      setConfirmation('')
+      await loadStatus()
+    }, message)
+  }
+
+  const handleEnable = async () => {
+    if (passphrase !== confirmation) {
+      return execute(async () => {
+        throw new Error('两次输入的口令不一致')
+      })
+    }
+    await run('enable_vault', '凭据已加密')
+  }
+
+  const handleDisable = async () => {
+    if (!confirm('关闭加密后凭据将以明文保存，确定继续？')) return
+    await run('disable_vault', '已关闭凭据加密')
+  }
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">凭据加密</h3>
+        {status?.enabled && (
+          <span className={`badge ${status.unlocked ? 'badge-success' : 'badge-danger'}`}>
+            {status.unlocked ? '已解锁' : '已锁定'}
+          </span>
+        )}
+      </div>
+
+      {loading && <Loading />}
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+      {status && !status.enabled && (
+        <div className="form-group">
+          <p style={{ marginBottom: 8, color: 'var(--text-secondary)' }}>
+            当前凭据以明文保存在 credentials.json 中。启用后仅在切换节点时解密，当前节点的密钥仍会写入 Codex 使用的 auth.json。
+          </p>
+          <div className="input-group">
+            <input
+              type="password"
+              placeholder="口令（至少 8 位）"
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+            />
+            <input
+              type="password"
+              placeholder="确认口令"
+              value={confirmation}
+              onChange={(e) => setConfirmation(e.target.value)}
+            />
+            <button onClick={handleEnable} disabled={loading || !passphrase} className="btn btn-primary">
+              启用加密
+            </button>
+          </div>
+        </div>
+      )}
+
+      {status?.enabled && (
+        <div className="form-group">
+          <div className="input-group">
+            {status.unlocked ? (
+              <button onClick={() => run('lock_vault', '已锁定')} disabled={loading} className="btn btn-outline">
+                立即锁定
+              </button>
+            ) : (
+              <>
+                <input
+                  type="password"
+                  placeholder="口令"
+                  value={passphrase}
+                  onChange={(e) => setPassphrase(e.target.value)}
+                />
+                <button onClick={() => run('unlock_vault', '已解锁')} disabled={loading || !passphrase} className="btn btn-primary">
+                  解锁
+                </button>
+                <button onClick={handleDisable} disabled={loading || !passphrase} className="btn btn-danger">
+                  关闭加密
+                </button>
+              </>
+            )}
+          </div>
+        </div>
+      )}
+    </div>
+  )
+}
+
+/**
+ * 文件权限检查：密钥文件（含 auth.json、config.toml）应为 0600，codex-mate 目录应为 0700
+ */
+function FilePermissions() {
+  const [report, setReport] = useState<PermissionReport | null>(null)
+  const { loading, error, success, execute, clearMessages } = useAsyncAction()
+
+  const runAudit = async (fix: boolean) => {
+    await execute(async () => {
+      setReport(await invoke<PermissionReport>('audit_permissions', { fix }))
+    }, fix ? '权限已修复' : '检查完成')
+  }
+
+  // 无法读取的路径不能自动修复，不计入待修复数量
+  const pending = report ? report.issues.filter((i) => !i.fixed && i.mode).length : 0
+
+  return (
+    <div className="card">
+      <div className="card-header">
+        <h3 className="card-title">文件权限</h3>
+        <div className="btn-group">
+          <button onClick={() => runAudit(false)} disabled={loading} className="btn btn-outline">
+            检查
+          </button>
+          <button onClick={() => runAudit(true)} disabled={loading || pending === 0} className="btn btn-primary">
+            一键修复
+          </button>
+        </div>
+      </div>
+
+      {loading && <Loading />}
+      {error && <ErrorMessage error={error} />}
+      {success && <Message type="success" onClose={clearMessages}>{success}</Message>}
+
+      {report && !report.supported && <Message type="info">当前平台不支持 Unix 权限检查</Message>}
+
+      {report && report.supported && report.issues.length === 0 && (
+        <Message type="success">已检查 {report.checked} 个路径，未发现权限过宽的文件</Message>
+      )}
+
+      {report && report.issues.length > 0 && (
+        <div className="table-container">
+          <table>
+            <thead>
+              <tr>
+                <th>路径</th>
+                <th style={{ width: 100 }}>当前</th>
+                <th style={{ width: 100 }}>建议</th>
+                <th style={{ width: 160 }}>状态</th>
+              </tr>
+            </thead>
+            <tbody>
+              {report.issues.map((issue) => (
+                <tr key={issue.path}>
+                  <td style={{ wordBreak: 'break-all' }}>{issue.path}</td>
+                  <td>{issue.mode ?? '-'}</td>
+                  <td>{issue.expected ?? '-'}</td>
+                  <td>
+                    {!issue.mode
+                      ? `无法读取: ${issue.error}`
+                      : issue.error
+                        ? `修复失败: ${issue.error}`
+                        : issue.fixed
+                          ? '已修复'
+                          : '待修复'}
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
+    </div>
+  )
+}
@@ -2,6 +2,8 @@
  * 通用工具函数
  */
 
+import type { AppError } from '../types'
+
 /**
  * 防抖函数
  */
@@ -33,6 +35,18 @@ export function throttle<T extends (...args: any[]) => any>(
   }
 }
 
+/**
+ * 判断是否为后端返回的结构化错误
+ */
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as AppError).code === 'string' &&
+    typeof (error as AppError).message === 'string'
+  )
+}
+
 /**
  * 格式化错误消息
  */
@@ -40,6 +54,9 @@ export function formatError(error: unknown): string {
   if (error instanceof Error) {
     return error.message
   }
+  if (isAppError(error)) {
+    return error.message
+  }
   return String(error)
 }
 